@@ -0,0 +1,148 @@
+use std::{fmt, mem};
+
+use bytemuck::{Pod, Zeroable};
+
+/// Maximum number of images in the atlas, the size of the images storage buffer.
+pub(super) const MAX_IMAGES: usize = 1024;
+/// Space left between images so that linear sampling doesn't bleed into neighbours, in pixels.
+const ATLAS_PADDING: u32 = 1;
+
+/// How an image is scaled to the limits of its widget.
+#[derive(Copy, Clone, Debug)]
+pub(super) enum ImageFit {
+    /// Stretch the image over the widget.
+    Fill,
+    /// Scale the image to fit inside the widget, keeping its aspect ratio.
+    Contain,
+    /// Scale the image to cover the widget, keeping its aspect ratio and cropping it.
+    Cover,
+    /// Keep the corners given by the image's insets unscaled, and stretch the edges and center.
+    NineSlice,
+}
+impl ImageFit {
+    pub(super) fn ty(&self) -> u32 {
+        match self {
+            Self::Fill => 0,
+            Self::Contain => 1,
+            Self::Cover => 2,
+            Self::NineSlice => 3,
+        }
+    }
+}
+
+/// How the image's texels are filtered when scaled.
+#[derive(Copy, Clone, Debug)]
+pub(super) enum ImageFilter {
+    Nearest,
+    Linear,
+}
+
+/// Where an image is in the atlas, as read by the shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub(super) struct ImageRegion {
+    /// Atlas texture coordinates of the centers of its edge texels: left, top, right, bottom.
+    pub(super) uv: [f32; 4],
+    /// Nine-slice insets in pixels of the image: left, top, right, bottom.
+    pub(super) insets: [f32; 4],
+    /// Size of the image in pixels.
+    pub(super) size: [f32; 2],
+    _padding: [f32; 2],
+}
+
+#[derive(Debug)]
+pub(super) enum AtlasError {
+    /// The bytes couldn't be decoded as an image.
+    Decode(image::ImageError),
+    /// There is no room left for the image.
+    Full,
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(e) => write!(f, "could not decode image: {e}"),
+            Self::Full => write!(f, "the atlas is full"),
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+/// Images packed in rows ("shelves") into a single texture, shared by every image widget.
+pub(super) struct Atlas {
+    size: u32,
+    /// Position of the next image on the current shelf.
+    cursor: [u32; 2],
+    shelf_height: u32,
+    regions: Vec<ImageRegion>,
+    /// Images packed but not uploaded to the texture yet, with their position in pixels.
+    pending: Vec<([u32; 2], image::RgbaImage)>,
+}
+
+impl Atlas {
+    /// An empty atlas of `size`x`size` pixels.
+    pub(super) fn new(size: u32) -> Self {
+        Self {
+            size,
+            cursor: [0, 0],
+            shelf_height: 0,
+            regions: vec![],
+            pending: vec![],
+        }
+    }
+
+    pub(super) fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Decode a PNG, JPEG or any format supported by `image`, and pack it. Returns the index of the image.
+    pub(super) fn add(&mut self, bytes: &[u8]) -> Result<u32, AtlasError> {
+        let image = image::load_from_memory(bytes).map_err(AtlasError::Decode)?;
+        self.add_rgba(image.to_rgba8())
+    }
+
+    /// Pack an already decoded image. Returns the index of the image.
+    pub(super) fn add_rgba(&mut self, image: image::RgbaImage) -> Result<u32, AtlasError> {
+        if self.regions.len() >= MAX_IMAGES {
+            return Err(AtlasError::Full);
+        }
+        let (width, height) = image.dimensions();
+        if self.cursor[0] + width > self.size {
+            // Start a new shelf
+            self.cursor = [0, self.cursor[1] + self.shelf_height];
+            self.shelf_height = 0;
+        }
+        if self.cursor[0] + width > self.size || self.cursor[1] + height > self.size {
+            return Err(AtlasError::Full);
+        }
+
+        let [x, y] = self.cursor;
+        let size = self.size as f32;
+        // From the centers of the edge texels, so that linear filtering doesn't blend in the texels around the image
+        self.regions.push(ImageRegion {
+            uv: [(x as f32 + 0.5) / size, (y as f32 + 0.5) / size, ((x + width) as f32 - 0.5) / size, ((y + height) as f32 - 0.5) / size],
+            insets: [0.0; 4],
+            size: [width as f32, height as f32],
+            _padding: [0.0; 2],
+        });
+        self.pending.push(([x, y], image));
+        self.cursor[0] += width + ATLAS_PADDING;
+        self.shelf_height = self.shelf_height.max(height + ATLAS_PADDING);
+        Ok(self.regions.len() as u32 - 1)
+    }
+
+    /// Set the nine-slice insets of `image`, in pixels: left, top, right, bottom.
+    pub(super) fn set_insets(&mut self, image: u32, insets: [f32; 4]) {
+        self.regions[image as usize].insets = insets;
+    }
+
+    pub(super) fn regions(&self) -> &[ImageRegion] {
+        &self.regions
+    }
+
+    /// The images waiting to be uploaded to the atlas texture, with their position in pixels.
+    pub(super) fn take_pending(&mut self) -> Vec<([u32; 2], image::RgbaImage)> {
+        mem::take(&mut self.pending)
+    }
+}
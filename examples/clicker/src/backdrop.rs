@@ -0,0 +1,240 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct BlurParams {
+    direction: [f32; 2],
+    radius: f32,
+    _padding: f32,
+}
+
+/// Offscreen targets and passes used to blur what is behind translucent widgets.
+///
+/// When some widgets have a backdrop blur, the widgets are drawn to `scene_view` instead of the surface.
+/// Before drawing each of those widgets, the scene is blurred into `blurred_view`, which the widget samples,
+/// and once every widget is drawn the scene is blitted to the surface.
+pub(super) struct Backdrop {
+    scene_view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    params_buffers: [wgpu::Buffer; 2],
+    blur_bind_group_layout: wgpu::BindGroupLayout,
+    /// Reads the scene, for the horizontal blur and the blit.
+    scene_bind_group: wgpu::BindGroup,
+    /// Reads the horizontally blurred scene, for the vertical blur.
+    half_blurred_bind_group: wgpu::BindGroup,
+    half_blurred_view: wgpu::TextureView,
+    blurred_view: wgpu::TextureView,
+    blur_pipeline: wgpu::RenderPipeline,
+    blit_pipeline: wgpu::RenderPipeline,
+}
+
+impl Backdrop {
+    pub(super) fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Backdrop Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params_buffers = [[1.0, 0.0], [0.0, 1.0]].map(|direction| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Blur Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[BlurParams { direction, radius: 0.0, _padding: 0.0 }]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        });
+
+        let blur_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                texture_entry(0),
+                sampler_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("blur_bind_group_layout"),
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Blur Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("blur.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blur Pipeline Layout"),
+            bind_group_layouts: &[&blur_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |entry_point: &str| device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blur Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let blur_pipeline = pipeline("fs_blur");
+        let blit_pipeline = pipeline("fs_blit");
+
+        let [scene_view, half_blurred_view, blurred_view] = create_views(device, config);
+        let scene_bind_group = create_blur_bind_group(device, &blur_bind_group_layout, &scene_view, &sampler, &params_buffers[0]);
+        let half_blurred_bind_group = create_blur_bind_group(device, &blur_bind_group_layout, &half_blurred_view, &sampler, &params_buffers[1]);
+
+        Self {
+            scene_view,
+            sampler,
+            params_buffers,
+            blur_bind_group_layout,
+            scene_bind_group,
+            half_blurred_bind_group,
+            half_blurred_view,
+            blurred_view,
+            blur_pipeline,
+            blit_pipeline,
+        }
+    }
+
+    /// Recreate the offscreen targets at the size of the surface.
+    pub(super) fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        [self.scene_view, self.half_blurred_view, self.blurred_view] = create_views(device, config);
+        self.scene_bind_group = create_blur_bind_group(device, &self.blur_bind_group_layout, &self.scene_view, &self.sampler, &self.params_buffers[0]);
+        self.half_blurred_bind_group = create_blur_bind_group(device, &self.blur_bind_group_layout, &self.half_blurred_view, &self.sampler, &self.params_buffers[1]);
+    }
+
+    /// The blurred scene, sampled by widgets with a backdrop blur. Changes when resized.
+    pub(super) fn blurred_view(&self) -> &wgpu::TextureView {
+        &self.blurred_view
+    }
+
+    /// A linear sampler, clamping to the edges.
+    pub(super) fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    /// The target widgets are drawn to when some of them have a backdrop blur.
+    pub(super) fn scene_view(&self) -> &wgpu::TextureView {
+        &self.scene_view
+    }
+
+    /// Blur the scene by `radius` pixels into `blurred_view`.
+    /// The radius is written with `queue`, so the encoder must be submitted before the next call.
+    pub(super) fn blur(&self, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, config: &wgpu::SurfaceConfiguration, radius: f32) {
+        let texel = [1.0 / config.width as f32, 1.0 / config.height as f32];
+        queue.write_buffer(&self.params_buffers[0], 0, bytemuck::cast_slice(&[BlurParams { direction: [texel[0], 0.0], radius, _padding: 0.0 }]));
+        queue.write_buffer(&self.params_buffers[1], 0, bytemuck::cast_slice(&[BlurParams { direction: [0.0, texel[1]], radius, _padding: 0.0 }]));
+
+        fullscreen_pass(encoder, &self.blur_pipeline, &self.scene_bind_group, &self.half_blurred_view);
+        fullscreen_pass(encoder, &self.blur_pipeline, &self.half_blurred_bind_group, &self.blurred_view);
+    }
+
+    /// Copy the scene to `target`.
+    pub(super) fn blit(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        fullscreen_pass(encoder, &self.blit_pipeline, &self.scene_bind_group, target);
+    }
+}
+
+fn fullscreen_pass(encoder: &mut wgpu::CommandEncoder, pipeline: &wgpu::RenderPipeline, bind_group: &wgpu::BindGroup, target: &wgpu::TextureView) {
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Backdrop Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(0, bind_group, &[]);
+    render_pass.draw(0..3, 0..1);
+}
+
+pub(super) fn texture_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+pub(super) fn sampler_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+        count: None,
+    }
+}
+
+/// The scene, horizontally blurred scene and blurred scene targets.
+fn create_views(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> [wgpu::TextureView; 3] {
+    ["Backdrop Scene Texture", "Backdrop Half Blurred Texture", "Backdrop Blurred Texture"].map(|label| {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+    })
+}
+
+fn create_blur_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, view: &wgpu::TextureView, sampler: &wgpu::Sampler, params: &wgpu::Buffer) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Buffer(params.as_entire_buffer_binding()),
+            },
+        ],
+        label: Some("blur_bind_group"),
+    })
+}
@@ -0,0 +1,49 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+struct BlurParams {
+    // Size of a texel along the blur direction, the other component is 0
+    direction: vec2<f32>,
+    // Blur radius in pixels, twice the standard deviation
+    radius: f32,
+};
+
+@group(0) @binding(0)
+var src: texture_2d<f32>;
+@group(0) @binding(1)
+var src_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: BlurParams;
+
+// Fullscreen triangle
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// One direction of a separable gaussian blur
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sigma = max(params.radius * 0.5, 0.5);
+    let taps = i32(min(ceil(params.radius * 1.5), 64.0));
+    var sum = vec4<f32>(0.0);
+    var weights = 0.0;
+    for (var i = -taps; i <= taps; i++) {
+        let x = f32(i);
+        let w = exp(-x * x / (2.0 * sigma * sigma));
+        sum += w * textureSampleLevel(src, src_sampler, in.uv + x * params.direction, 0.0);
+        weights += w;
+    }
+    return sum / weights;
+}
+
+@fragment
+fn fs_blit(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSampleLevel(src, src_sampler, in.uv, 0.0);
+}
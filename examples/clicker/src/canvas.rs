@@ -0,0 +1,395 @@
+use bytemuck::{Pod, Zeroable};
+
+/// Shift of the hit region + 1 in the ids written to the ID buffer by the primitives of canvases, the widget index + 1 being below it.
+/// Must match `HIT_REGION_SHIFT` in `w__fragment.wgsl`.
+pub(super) const HIT_REGION_SHIFT: u32 = 20;
+/// Hit regions are below this, fitting in the bits above `HIT_REGION_SHIFT` with 1 added.
+pub(super) const MAX_HIT_REGIONS: u32 = (1 << (32 - HIT_REGION_SHIFT)) - 1;
+/// Primitives of all the canvases the data buffer holds at first, grown as needed.
+pub(super) const INITIAL_PRIMITIVES: usize = 256;
+/// Limits of a canvas that wasn't clipped, in pixels, the quads of its primitives being cut to the widget anyway.
+const UNCLIPPED: [f32; 4] = [-1.0e6, -1.0e6, 1.0e6, 1.0e6];
+
+/// ASCII from ' ' to '~', 3x5 pixels each, bit row * 3 + column from the top left. Lowercase letters are drawn as capitals.
+/// The digits, '-' and '.' must match `GLYPHS` in `w__fragment.wgsl`.
+const FONT: [u16; 95] = [
+    0x0000, 0x2092, 0x002d, 0x5f7d, 0x3c9e, 0x42a1, 0x6aaa, 0x0012, 0x4494, 0x1491, 0x0aa8, 0x05d0,
+    0x1400, 0x01c0, 0x2000, 0x12a4, 0x7b6f, 0x749a, 0x73e7, 0x79e7, 0x49ed, 0x79cf, 0x7bcf, 0x4927,
+    0x7bef, 0x79ef, 0x0410, 0x1410, 0x4454, 0x0e38, 0x1511, 0x20a7, 0x636f, 0x5bea, 0x3aeb, 0x624e,
+    0x3b6b, 0x72cf, 0x12cf, 0x6b4e, 0x5bed, 0x7497, 0x2b24, 0x5aed, 0x7249, 0x5bfd, 0x5b6b, 0x2b6a,
+    0x12eb, 0x676a, 0x5aeb, 0x388e, 0x2497, 0x7b6d, 0x2b6d, 0x5fed, 0x5aad, 0x24ad, 0x72a7, 0x324b,
+    0x4889, 0x6926, 0x002a, 0x7000, 0x0011, 0x5bea, 0x3aeb, 0x624e, 0x3b6b, 0x72cf, 0x12cf, 0x6b4e,
+    0x5bed, 0x7497, 0x2b24, 0x5aed, 0x7249, 0x5bfd, 0x5b6b, 0x2b6a, 0x12eb, 0x676a, 0x5aeb, 0x388e,
+    0x2497, 0x7b6d, 0x2b6d, 0x5fed, 0x5aad, 0x24ad, 0x72a7, 0x64d6, 0x2492, 0x3593, 0x0198,
+];
+
+/// What a primitive draws. Must match the `CANVAS_` kinds in `w__fragment.wgsl`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum PrimitiveKind {
+    /// A rectangle (left, top, right, bottom), its corners rounded by the radius in `param`.
+    Rect,
+    /// An ellipse (center x, center y, radius x, radius y).
+    Ellipse,
+    /// A segment between (x, y) and (z, w), with round caps.
+    Line,
+    /// The glyph of the bits in `param`, in the cell (left, top, right, bottom).
+    Glyph,
+    /// The image at index `param` of the atlas, stretched over (left, top, right, bottom).
+    Image,
+    /// The fill of the path at index `param`, fitted to (left, top, right, bottom).
+    PathFill,
+    /// The stroke of the path at index `param`, fitted to (left, top, right, bottom).
+    PathStroke,
+}
+
+impl PrimitiveKind {
+    fn ty(&self) -> u32 {
+        match self {
+            Self::Rect => 0,
+            Self::Ellipse => 1,
+            Self::Line => 2,
+            Self::Glyph => 3,
+            Self::Image => 4,
+            Self::PathFill => 5,
+            Self::PathStroke => 6,
+        }
+    }
+}
+
+/// A shape drawn by a canvas, as read by the shaders, each drawn by an instance of the quad of its canvas.
+/// Its size must match `PRIMITIVE_SIZE` in the shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub(super) struct CanvasPrimitive {
+    color: [f32; 4],
+    /// Left, top, right, bottom of its quad in pixels from the top left of the canvas, cut to the clip.
+    bounds: [f32; 4],
+    /// The rows of the inverse of the transform, mapping pixels of the canvas to the coordinates of `shape`,
+    /// the last column of the first row being how many pixels a unit of those coordinates spans.
+    to_local: [[f32; 4]; 2],
+    /// Depending on the kind, see `PrimitiveKind`.
+    shape: [f32; 4],
+    kind: u32,
+    /// Width of the outline of rectangles, ellipses and lines, 0 filling rectangles and ellipses.
+    line_width: f32,
+    /// Radius as `f32` bits, glyph bits, or index of an image or path.
+    param: u32,
+    /// Hit region + 1 reported to the ID buffer where it covers the canvas, 0 for none.
+    region: u32,
+}
+
+impl CanvasPrimitive {
+    /// Index of the atlas image it draws, if it is an image.
+    #[allow(unused)]
+    pub(super) fn image(&self) -> Option<u32> {
+        (self.kind == PrimitiveKind::Image.ty()).then_some(self.param)
+    }
+
+    /// Depending on the kind, see `PrimitiveKind`.
+    #[allow(unused)]
+    pub(super) fn shape(&self) -> [f32; 4] {
+        self.shape
+    }
+}
+
+/// A 2D affine transform, (a, b, c, d, e, f) mapping (x, y) to (a x + c y + e, b x + d y + f) like `setTransform` of HTML canvases.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct Transform(pub(super) [f32; 6]);
+
+impl Transform {
+    pub(super) const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// `other` applied before `self`.
+    fn then(&self, other: &Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Self([
+            a * a2 + c * b2,
+            b * a2 + d * b2,
+            a * c2 + c * d2,
+            b * c2 + d * d2,
+            a * e2 + c * f2 + e,
+            b * e2 + d * f2 + f,
+        ])
+    }
+
+    fn apply(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let [a, b, c, d, e, f] = self.0;
+        [a * x + c * y + e, b * x + d * y + f]
+    }
+
+    /// The rows of the inverse as read by the shaders, see `CanvasPrimitive::to_local`. `None` if it flattens the plane.
+    fn inverse_rows(&self) -> Option<[[f32; 4]; 2]> {
+        let [a, b, c, d, e, f] = self.0;
+        let det = a * d - b * c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some([
+            [d / det, -c / det, (c * f - d * e) / det, det.abs().sqrt()],
+            [-b / det, a / det, (b * e - a * f) / det, 0.0],
+        ])
+    }
+}
+
+/// What `Canvas::save` saves.
+#[derive(Copy, Clone, Debug)]
+struct DrawState {
+    transform: Transform,
+    /// Left, top, right, bottom in pixels.
+    clip: [f32; 4],
+    fill: [f32; 4],
+    stroke: [f32; 4],
+    line_width: f32,
+    alpha: f32,
+    region: Option<u32>,
+}
+
+impl Default for DrawState {
+    fn default() -> Self {
+        Self {
+            transform: Transform::IDENTITY,
+            clip: UNCLIPPED,
+            fill: [0.0, 0.0, 0.0, 1.0],
+            stroke: [0.0, 0.0, 0.0, 1.0],
+            line_width: 1.0,
+            alpha: 1.0,
+            region: None,
+        }
+    }
+}
+
+/// A drawing of a canvas widget, recorded again by the application whenever it changes and given to `State::set_canvas`.
+/// Like the 2D context of an HTML canvas, it draws in pixels from the top left of the canvas through the current transform,
+/// in the current colors, each call batched into a primitive the canvas quad is instanced over.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Canvas {
+    primitives: Vec<CanvasPrimitive>,
+    state: DrawState,
+    saved: Vec<DrawState>,
+}
+
+#[allow(unused)]
+impl Canvas {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn primitives(&self) -> &[CanvasPrimitive] {
+        &self.primitives
+    }
+
+    /// Push the transform, clip, colors, line width, alpha and hit region, restored by `restore`.
+    pub(super) fn save(&mut self) {
+        self.saved.push(self.state);
+    }
+
+    /// Pop what the last `save` pushed, if any.
+    pub(super) fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.state = state;
+        }
+    }
+
+    pub(super) fn translate(&mut self, x: f32, y: f32) {
+        self.transform(Transform([1.0, 0.0, 0.0, 1.0, x, y]));
+    }
+
+    /// Rotate by `angle` radians, clockwise on the screen.
+    pub(super) fn rotate(&mut self, angle: f32) {
+        let (sin, cos) = angle.sin_cos();
+        self.transform(Transform([cos, sin, -sin, cos, 0.0, 0.0]));
+    }
+
+    pub(super) fn scale(&mut self, x: f32, y: f32) {
+        self.transform(Transform([x, 0.0, 0.0, y, 0.0, 0.0]));
+    }
+
+    /// Apply `transform` before the current transform.
+    pub(super) fn transform(&mut self, transform: Transform) {
+        self.state.transform = self.state.transform.then(&transform);
+    }
+
+    pub(super) fn set_transform(&mut self, transform: Transform) {
+        self.state.transform = transform;
+    }
+
+    /// Cut what is drawn next to the rectangle, or to the box around it on the screen when rotated, within the current clip.
+    pub(super) fn clip_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let [left, top, right, bottom] = self.screen_box([x, y, x + width, y + height], 0.0);
+        let clip = &mut self.state.clip;
+        *clip = [clip[0].max(left), clip[1].max(top), clip[2].min(right), clip[3].min(bottom)];
+    }
+
+    /// Color of fills, glyphs, and the fill of paths, as RGBA from 0 to 1.
+    pub(super) fn set_fill_color(&mut self, color: [f32; 4]) {
+        self.state.fill = color;
+    }
+
+    /// Color of outlines, lines, and the stroke of paths, as RGBA from 0 to 1.
+    pub(super) fn set_stroke_color(&mut self, color: [f32; 4]) {
+        self.state.stroke = color;
+    }
+
+    /// Width of outlines and lines, before the transform.
+    pub(super) fn set_line_width(&mut self, width: f32) {
+        self.state.line_width = width.max(0.0);
+    }
+
+    /// Opacity multiplying the colors and images, from 0 to 1.
+    pub(super) fn set_global_alpha(&mut self, alpha: f32) {
+        self.state.alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    /// Report the pixels covered by what is drawn next as the hit region `region`, or stop with `None`.
+    /// Primitives without a hit region leave those below them picked.
+    /// Panics if `region` isn't below `MAX_HIT_REGIONS`.
+    pub(super) fn set_hit_region(&mut self, region: Option<u32>) {
+        if let Some(region) = region {
+            assert!(region < MAX_HIT_REGIONS, "Hit region {region} isn't below {MAX_HIT_REGIONS}");
+        }
+        self.state.region = region;
+    }
+
+    pub(super) fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.fill_rounded_rect(x, y, width, height, 0.0);
+    }
+
+    pub(super) fn fill_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32) {
+        self.push(PrimitiveKind::Rect, self.state.fill, [x, y, x + width, y + height], 0.0, radius.max(0.0).to_bits());
+    }
+
+    /// Outline the rectangle with a line centered on its edges.
+    pub(super) fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.stroke_rounded_rect(x, y, width, height, 0.0);
+    }
+
+    pub(super) fn stroke_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32) {
+        let line_width = self.state.line_width;
+        self.push(PrimitiveKind::Rect, self.state.stroke, [x, y, x + width, y + height], line_width, radius.max(0.0).to_bits());
+    }
+
+    pub(super) fn fill_ellipse(&mut self, center: [f32; 2], radii: [f32; 2]) {
+        self.push(PrimitiveKind::Ellipse, self.state.fill, [center[0], center[1], radii[0], radii[1]], 0.0, 0);
+    }
+
+    pub(super) fn stroke_ellipse(&mut self, center: [f32; 2], radii: [f32; 2]) {
+        let line_width = self.state.line_width;
+        self.push(PrimitiveKind::Ellipse, self.state.stroke, [center[0], center[1], radii[0], radii[1]], line_width, 0);
+    }
+
+    pub(super) fn fill_circle(&mut self, center: [f32; 2], radius: f32) {
+        self.fill_ellipse(center, [radius; 2]);
+    }
+
+    pub(super) fn stroke_circle(&mut self, center: [f32; 2], radius: f32) {
+        self.stroke_ellipse(center, [radius; 2]);
+    }
+
+    /// A segment from `a` to `b`, with round caps.
+    pub(super) fn line(&mut self, a: [f32; 2], b: [f32; 2]) {
+        let line_width = self.state.line_width;
+        self.push(PrimitiveKind::Line, self.state.stroke, [a[0], a[1], b[0], b[1]], line_width, 0);
+    }
+
+    /// Segments between consecutive `points`.
+    pub(super) fn polyline(&mut self, points: &[[f32; 2]]) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1]);
+        }
+    }
+
+    /// The path at index `path` of the paths given to `State::new`, its viewbox fitted to the rectangle like path widgets,
+    /// filled with the fill color and stroked with the stroke color.
+    pub(super) fn draw_path(&mut self, path: u32, x: f32, y: f32, width: f32, height: f32) {
+        let rect = [x, y, x + width, y + height];
+        self.push(PrimitiveKind::PathFill, self.state.fill, rect, 0.0, path);
+        self.push(PrimitiveKind::PathStroke, self.state.stroke, rect, 0.0, path);
+    }
+
+    /// The image at index `image` of the atlas, stretched over the rectangle.
+    pub(super) fn draw_image(&mut self, image: u32, x: f32, y: f32, width: f32, height: f32) {
+        self.push(PrimitiveKind::Image, [1.0; 4], [x, y, x + width, y + height], 0.0, image);
+    }
+
+    /// The image at index `image` of the atlas multiplied by the fill color, stretched over the rectangle,
+    /// like the white glyphs of `Fonts`.
+    pub(super) fn fill_image(&mut self, image: u32, x: f32, y: f32, width: f32, height: f32) {
+        self.push(PrimitiveKind::Image, self.state.fill, [x, y, x + width, y + height], 0.0, image);
+    }
+
+    /// `text` in the fill color with the pixel font of the charts, from its top left `origin`, `height` tall.
+    /// Characters outside of printable ASCII are drawn as '?'.
+    pub(super) fn fill_text(&mut self, text: &str, origin: [f32; 2], height: f32) {
+        let pixel = height / 5.0;
+        for (i, c) in text.chars().enumerate() {
+            let bits = glyph(c);
+            if bits == 0 {
+                continue;
+            }
+            let left = origin[0] + i as f32 * 4.0 * pixel;
+            self.push(PrimitiveKind::Glyph, self.state.fill, [left, origin[1], left + 3.0 * pixel, origin[1] + height], 0.0, bits as u32);
+        }
+    }
+
+    /// Width of `text` drawn `height` tall by `fill_text`, before the transform.
+    pub(super) fn measure_text(text: &str, height: f32) -> f32 {
+        let count = text.chars().count() as f32;
+        (count * 4.0 - 1.0).max(0.0) * height / 5.0
+    }
+
+    /// Add a primitive of `shape` with the current transform, clip, alpha and hit region, unless it is flattened or clipped out.
+    fn push(&mut self, kind: PrimitiveKind, color: [f32; 4], shape: [f32; 4], line_width: f32, param: u32) {
+        let Some(to_local) = self.state.transform.inverse_rows() else { return };
+        let local_box = match kind {
+            PrimitiveKind::Ellipse => [shape[0] - shape[2], shape[1] - shape[3], shape[0] + shape[2], shape[1] + shape[3]],
+            PrimitiveKind::Line => [shape[0].min(shape[2]), shape[1].min(shape[3]), shape[0].max(shape[2]), shape[1].max(shape[3])],
+            _ => shape,
+        };
+        let [left, top, right, bottom] = self.screen_box(local_box, line_width * 0.5);
+        let clip = self.state.clip;
+        // With a pixel around it to antialias
+        let bounds = [(left - 1.0).max(clip[0]), (top - 1.0).max(clip[1]), (right + 1.0).min(clip[2]), (bottom + 1.0).min(clip[3])];
+        if bounds[0] >= bounds[2] || bounds[1] >= bounds[3] {
+            return;
+        }
+        let alpha = color[3] * self.state.alpha;
+        let (kind, region) = (kind.ty(), self.state.region.map_or(0, |r| r + 1));
+        self.primitives.push(CanvasPrimitive { color: [color[0], color[1], color[2], alpha], bounds, to_local, shape, kind, line_width, param, region });
+    }
+
+    /// The box on the screen around `rect` grown by `margin`, through the current transform.
+    fn screen_box(&self, rect: [f32; 4], margin: f32) -> [f32; 4] {
+        let [left, top, right, bottom] = [rect[0] - margin, rect[1] - margin, rect[2] + margin, rect[3] + margin];
+        let corners = [[left, top], [right, top], [right, bottom], [left, bottom]].map(|p| self.state.transform.apply(p));
+        corners.iter().fold([f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY], |b, &[x, y]| {
+            [b[0].min(x), b[1].min(y), b[2].max(x), b[3].max(y)]
+        })
+    }
+}
+
+/// The bits of the glyph of `c` in `FONT`.
+fn glyph(c: char) -> u16 {
+    match c {
+        ' '..='~' => FONT[c as usize - ' ' as usize],
+        _ => FONT['?' as usize - ' ' as usize],
+    }
+}
+
+/// The primitives of a canvas widget.
+pub(super) struct CanvasModel {
+    pub(super) canvas: Canvas,
+    /// Index of its first primitive in the canvas buffer.
+    pub(super) start: u32,
+}
+
+impl CanvasModel {
+    pub(super) fn new() -> Self {
+        Self { canvas: Canvas::new(), start: 0 }
+    }
+
+    /// The range of the canvas buffer holding its primitives, instancing its quad.
+    pub(super) fn instances(&self) -> std::ops::Range<u32> {
+        self.start..self.start + self.canvas.primitives.len() as u32
+    }
+}
@@ -0,0 +1,264 @@
+use bytemuck::{Pod, Zeroable};
+
+/// Most series a chart shows, each drawn by a quad instanced over its points.
+/// Must match `MAX_CHART_SERIES` in the shaders.
+pub(super) const MAX_CHART_SERIES: usize = 8;
+/// Space between the border of a chart and its plot, for the tick labels, in pixels: left, top, right, bottom.
+/// Must match `CHART_INSETS` in the shaders.
+pub(super) const CHART_INSETS: [f32; 4] = [48.0, 8.0, 8.0, 20.0];
+/// How far from a data point the pointer shows its readout, in pixels.
+pub(super) const CHART_HOVER_RADIUS: f32 = 16.0;
+/// How much a pixel of wheel scrolling zooms a chart in or out.
+pub(super) const CHART_ZOOM_SPEED: f32 = 0.002;
+/// Points of all the charts the data buffer holds at first, grown as needed.
+pub(super) const INITIAL_POINTS: usize = 1024;
+
+/// How a series is drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum ChartKind {
+    /// Segments between consecutive points.
+    Line,
+    /// A bar from 0 to each point, most of the way to its neighbors wide.
+    Bar,
+    /// A dot at each point.
+    Scatter,
+    /// A line over the region between it and 0.
+    Area,
+}
+
+impl ChartKind {
+    /// Must match the `CHART_` kinds in the shaders.
+    fn ty(&self) -> u32 {
+        match self {
+            Self::Line => 0,
+            Self::Bar => 1,
+            Self::Scatter => 2,
+            Self::Area => 3,
+        }
+    }
+}
+
+/// A numeric series of a chart, drawn in one color.
+#[derive(Clone, Debug)]
+pub(super) struct Series {
+    kind: ChartKind,
+    color: [f32; 4],
+    /// (x, y) data points, by increasing x for lines, bars and areas.
+    points: Vec<[f32; 2]>,
+}
+
+impl Series {
+    /// A series of `points`, sorted by x unless it is a scatter plot.
+    pub(super) fn new(kind: ChartKind, color: [f32; 4], mut points: Vec<[f32; 2]>) -> Self {
+        if kind != ChartKind::Scatter {
+            points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        }
+        Self { kind, color, points }
+    }
+
+    pub(super) fn points(&self) -> &[[f32; 2]] {
+        &self.points
+    }
+
+    /// Whether the x of its points increase, so that those near an x can be found by bisection.
+    fn is_sorted(&self) -> bool {
+        self.kind != ChartKind::Scatter
+    }
+}
+
+/// A series as read by the shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct SeriesHeader {
+    color: [f32; 4],
+    kind: u32,
+    /// Index of its first point in the points buffer.
+    start: u32,
+    len: u32,
+    _padding: u32,
+}
+
+/// A chart as read by the shaders, indexed by `ty[1]` of its widget.
+/// Its size must match `CHART_SIZE` in the shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub(super) struct ChartHeader {
+    view: [f32; 4],
+    /// The series + 1 and point under the pointer, 0 for none.
+    hovered: [u32; 2],
+    series_count: u32,
+    _padding: u32,
+    series: [SeriesHeader; MAX_CHART_SERIES],
+}
+
+/// The series of a chart widget, and the part of them it shows.
+pub(super) struct ChartModel {
+    pub(super) series: Vec<Series>,
+    /// The data shown in the plot, (Xmin, Xmax, Ymin, Ymax).
+    pub(super) view: [f32; 4],
+    /// The series and point whose readout is shown.
+    pub(super) hovered: Option<(usize, usize)>,
+    /// Index of the first point of its first series in the points buffer.
+    pub(super) start: u32,
+}
+
+impl ChartModel {
+    pub(super) fn new() -> Self {
+        Self { series: vec![], view: [0.0, 1.0, 0.0, 1.0], hovered: None, start: 0 }
+    }
+
+    /// The view showing all the points, with a twentieth of their extent around them. Bars and areas rise from 0.
+    pub(super) fn fitted_view(&self) -> [f32; 4] {
+        let mut bounds = [f32::INFINITY, f32::NEG_INFINITY, f32::INFINITY, f32::NEG_INFINITY];
+        for series in &self.series {
+            if matches!(series.kind, ChartKind::Bar | ChartKind::Area) && !series.points.is_empty() {
+                bounds[2] = bounds[2].min(0.0);
+                bounds[3] = bounds[3].max(0.0);
+            }
+            for &[x, y] in &series.points {
+                bounds = [bounds[0].min(x), bounds[1].max(x), bounds[2].min(y), bounds[3].max(y)];
+            }
+        }
+        let fit = |min: f32, max: f32| {
+            if min > max {
+                return [0.0, 1.0];
+            }
+            let margin = if max > min { (max - min) * 0.05 } else { 1.0 };
+            [min - margin, max + margin]
+        };
+        let [left, right] = fit(bounds[0], bounds[1]);
+        let [bottom, top] = fit(bounds[2], bounds[3]);
+        [left, right, bottom, top]
+    }
+
+    /// Zoom the view by `factor` along each axis, keeping the data at `center` in place. Below 1 zooms in.
+    pub(super) fn zoom(&mut self, center: [f32; 2], factor: [f32; 2]) {
+        let [left, right, bottom, top] = self.view;
+        let scale = |min: f32, max: f32, center: f32, factor: f32| {
+            // Stop before the extent gets too small for f32
+            let factor = factor.max(center.abs().max(1.0) * 1e-5 / (max - min));
+            [center + (min - center) * factor, center + (max - center) * factor]
+        };
+        let [left, right] = scale(left, right, center[0], factor[0]);
+        let [bottom, top] = scale(bottom, top, center[1], factor[1]);
+        self.view = [left, right, bottom, top];
+    }
+
+    /// Move the view by `by`, in data units.
+    pub(super) fn pan(&mut self, by: [f32; 2]) {
+        let [x, y] = by;
+        self.view = [self.view[0] + x, self.view[1] + x, self.view[2] + y, self.view[3] + y];
+    }
+
+    /// The series and point nearest to `p` in the plot, within `radius`, both in pixels.
+    pub(super) fn nearest(&self, plot: &Plot, p: [f32; 2], radius: f32) -> Option<(usize, usize)> {
+        let [min_x, _] = plot.to_data([p[0] - radius, p[1]]);
+        let [max_x, _] = plot.to_data([p[0] + radius, p[1]]);
+        let mut nearest = None;
+        let mut distance = radius;
+        for (s, series) in self.series.iter().enumerate() {
+            let points = series.points();
+            let range = if series.is_sorted() {
+                points.partition_point(|q| q[0] < min_x)..points.partition_point(|q| q[0] <= max_x)
+            } else {
+                0..points.len()
+            };
+            for i in range {
+                let [x, y] = plot.to_pixels(points[i]);
+                let d = (x - p[0]).hypot(y - p[1]);
+                if d <= distance {
+                    distance = d;
+                    nearest = Some((s, i));
+                }
+            }
+        }
+        nearest
+    }
+
+    /// The chart as read by the shaders.
+    pub(super) fn header(&self) -> ChartHeader {
+        let mut header = ChartHeader::zeroed();
+        header.view = self.view;
+        header.hovered = self.hovered.map_or([0, 0], |(s, i)| [s as u32 + 1, i as u32]);
+        header.series_count = self.series.len() as u32;
+        let mut start = self.start;
+        for (h, series) in header.series.iter_mut().zip(&self.series) {
+            *h = SeriesHeader { color: series.color, kind: series.kind.ty(), start, len: series.points.len() as u32, _padding: 0 };
+            start += series.points.len() as u32;
+        }
+        header
+    }
+
+    /// The range of the points buffer holding the points of `series`, instancing its quad.
+    pub(super) fn instances(&self, series: usize) -> std::ops::Range<u32> {
+        let start = self.start + self.series[..series].iter().map(|s| s.points.len() as u32).sum::<u32>();
+        start..start + self.series[series].points.len() as u32
+    }
+}
+
+/// Maps data to pixels in the plot of a chart.
+pub(super) struct Plot {
+    /// Left, top, right, bottom in pixels.
+    rect: [f32; 4],
+    view: [f32; 4],
+}
+
+impl Plot {
+    /// The plot of a chart at `limits` in pixels, as given by `pixel_limits`, showing `view`.
+    pub(super) fn new(limits: [f32; 4], view: [f32; 4]) -> Self {
+        let [left, top, right, bottom] = limits;
+        let [inset_left, inset_top, inset_right, inset_bottom] = CHART_INSETS;
+        Self { rect: [left + inset_left, top + inset_top, right - inset_right, bottom - inset_bottom], view }
+    }
+
+    pub(super) fn size(&self) -> [f32; 2] {
+        [(self.rect[2] - self.rect[0]).max(1.0), (self.rect[3] - self.rect[1]).max(1.0)]
+    }
+
+    pub(super) fn contains(&self, p: [f32; 2]) -> bool {
+        p[0] >= self.rect[0] && p[0] <= self.rect[2] && p[1] >= self.rect[1] && p[1] <= self.rect[3]
+    }
+
+    pub(super) fn to_pixels(&self, data: [f32; 2]) -> [f32; 2] {
+        let [left, right, bottom, top] = self.view;
+        let [width, height] = self.size();
+        [self.rect[0] + (data[0] - left) / (right - left) * width, self.rect[3] - (data[1] - bottom) / (top - bottom) * height]
+    }
+
+    pub(super) fn to_data(&self, p: [f32; 2]) -> [f32; 2] {
+        [self.view[0] + self.delta([p[0] - self.rect[0], 0.0])[0], self.view[2] + self.delta([0.0, p[1] - self.rect[3]])[1]]
+    }
+
+    /// The data units moved by moving `by` pixels, y pointing down.
+    pub(super) fn delta(&self, by: [f32; 2]) -> [f32; 2] {
+        let [left, right, bottom, top] = self.view;
+        let [width, height] = self.size();
+        [by[0] / width * (right - left), -by[1] / height * (top - bottom)]
+    }
+}
+
+/// The mouse panning a chart.
+pub(super) struct ChartDrag {
+    pub(super) chart: usize,
+    /// Where the pointer was pressed, in pixels.
+    pub(super) start: [f64; 2],
+    /// The view when it was pressed.
+    pub(super) view: [f32; 4],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_in_unsorted_series() {
+        let mut chart = ChartModel::new();
+        chart.series.push(Series::new(ChartKind::Line, [1.0; 4], vec![[3.0, 0.0], [1.0, 0.0], [2.0, 0.0], [0.0, 0.0]]));
+        assert_eq!(chart.series[0].points(), [[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [3.0, 0.0]]);
+        let plot = Plot { rect: [0.0, 0.0, 300.0, 100.0], view: [0.0, 3.0, -1.0, 1.0] };
+        for x in 0..4 {
+            let p = plot.to_pixels([x as f32, 0.0]);
+            assert_eq!(chart.nearest(&plot, [p[0] + 5.0, p[1]], 10.0), Some((0, x)));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+/// A place text widgets can copy to and paste from.
+/// The system clipboard is used by default, tests and headless runs can use a `MemoryClipboard`.
+pub(super) trait Clipboard {
+    /// The current text content of the clipboard, if there is any.
+    fn get_text(&mut self) -> Option<String>;
+    /// Replace the content of the clipboard with `text`.
+    fn set_text(&mut self, text: String);
+}
+
+/// A clipboard that only lives in memory, and isn't shared with other applications.
+#[derive(Default)]
+pub(super) struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.text = Some(text);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// The clipboard of the OS.
+pub(super) struct SystemClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemClipboard {
+    pub(super) fn new() -> Result<Self, arboard::Error> {
+        Ok(Self { clipboard: arboard::Clipboard::new()? })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clipboard for SystemClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+
+    fn set_text(&mut self, text: String) {
+        if let Err(e) = self.clipboard.set_text(text) {
+            log::warn!("Could not write to the system clipboard: {e}");
+        }
+    }
+}
+
+/// The system clipboard if it is available, an in-memory one otherwise.
+pub(super) fn default_clipboard() -> Box<dyn Clipboard> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            Box::new(MemoryClipboard::default())
+        } else {
+            match SystemClipboard::new() {
+                Ok(clipboard) => Box::new(clipboard),
+                Err(e) => {
+                    log::warn!("System clipboard unavailable, falling back to memory: {e}");
+                    Box::new(MemoryClipboard::default())
+                }
+            }
+        }
+    }
+}
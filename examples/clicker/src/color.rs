@@ -0,0 +1,408 @@
+use std::ops::Range;
+
+use crate::{slider::Orientation, state::{Widget, WidgetType}};
+
+/// Set in `ty[1]` of a color area showing a wheel of hues and saturations, instead of a square of saturations and values.
+/// Must match `COLOR_WHEEL` in `w__fragment.wgsl`.
+pub(super) const COLOR_WHEEL: u32 = 1 << 16;
+/// Set in `ty[2]` of a color slider laid out vertically, its minimum being at the bottom.
+/// Must match `COLOR_VERTICAL` in `w__fragment.wgsl`.
+pub(super) const COLOR_VERTICAL: u32 = 1 << 16;
+/// Shift of the `ColorChannel` in `ty[2]` of a color slider.
+/// Must match `COLOR_CHANNEL_SHIFT` in `w__fragment.wgsl`.
+pub(super) const COLOR_CHANNEL_SHIFT: u32 = 17;
+/// Space between the parts of a color picker and its border, in NDC.
+const PICKER_MARGIN: f32 = 0.02;
+
+/// How the color area of a picker shows the colors of the current hue or value.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum PickerShape {
+    /// Saturation from left to right and value from bottom to top, for the hue of the hue slider.
+    Square,
+    /// Hue around the center from the right, counterclockwise, and saturation outward, for the value of the value slider.
+    Wheel,
+}
+
+/// What a color slider changes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum ColorChannel {
+    Hue,
+    Value,
+    Alpha,
+}
+
+impl ColorChannel {
+    pub(super) fn ty(&self) -> u32 {
+        match self {
+            Self::Hue => 0,
+            Self::Value => 1,
+            Self::Alpha => 2,
+        }
+    }
+}
+
+/// A color as hue, saturation, value and alpha, all from 0 to 1.
+/// Unlike RGB, it keeps the hue of grays and the saturation of black.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct Hsva {
+    pub(super) h: f32,
+    pub(super) s: f32,
+    pub(super) v: f32,
+    pub(super) a: f32,
+}
+
+impl Hsva {
+    /// The HSVA color of `rgba`, keeping the hue and saturation of `previous` where `rgba` has none.
+    pub(super) fn from_rgba(rgba: [f32; 4], previous: Hsva) -> Self {
+        let [r, g, b, a] = rgba.map(|c| c.clamp(0.0, 1.0));
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        if max <= 0.0 {
+            return Self { v: 0.0, a, ..previous };
+        }
+        if chroma <= 0.0 {
+            return Self { s: 0.0, v: max, a, ..previous };
+        }
+        let h = if max == r {
+            (g - b) / chroma
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        Self { h: (h / 6.0).rem_euclid(1.0), s: chroma / max, v: max, a }
+    }
+
+    pub(super) fn rgba(&self) -> [f32; 4] {
+        let channel = |n: f32| {
+            let k = (n + self.h * 6.0) % 6.0;
+            self.v - self.v * self.s * k.min(4.0 - k).clamp(0.0, 1.0)
+        };
+        [channel(5.0), channel(3.0), channel(1.0), self.a]
+    }
+}
+
+impl Default for Hsva {
+    /// Opaque white.
+    fn default() -> Self {
+        Self { h: 0.0, s: 0.0, v: 1.0, a: 1.0 }
+    }
+}
+
+/// `rgba` packed as `r | g << 8 | b << 16 | a << 24`, one byte per channel, read with `unpack4x8unorm` by the shader.
+pub(super) fn pack_rgba(rgba: [f32; 4]) -> u32 {
+    rgba.iter().enumerate().fold(0, |packed, (i, c)| packed | to_byte(*c) << (8 * i))
+}
+
+/// The color packed by `pack_rgba`.
+pub(super) fn unpack_rgba(packed: u32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|i| (packed >> (8 * i) & 0xff) as f32 / 255.0)
+}
+
+fn to_byte(channel: f32) -> u32 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u32
+}
+
+/// `rgba` as `#rrggbb`, followed by `aa` unless it is opaque.
+pub(super) fn to_hex(rgba: [f32; 4]) -> String {
+    let [r, g, b, a] = rgba.map(to_byte);
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+/// The color written as `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`, the `#` being optional.
+pub(super) fn parse_hex(text: &str) -> Option<[f32; 4]> {
+    let text = text.trim();
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channels: Vec<u32> = match digits.len() {
+        3 | 4 => digits.chars().map(|c| c.to_digit(16).unwrap() * 17).collect(),
+        6 | 8 => (0..digits.len()).step_by(2).map(|i| u32::from_str_radix(&digits[i..i + 2], 16).unwrap()).collect(),
+        _ => return None,
+    };
+    let channel = |i: usize| channels.get(i).map_or(1.0, |&c| c as f32 / 255.0);
+    Some([channel(0), channel(1), channel(2), channel(3)])
+}
+
+/// Where the parts of a color picker go, and their styles. From top to bottom: the color area, the hue or value slider,
+/// the alpha slider, a row with a swatch of the color then its hex and its red, green and blue fields, and the swatches.
+#[derive(Clone, Debug)]
+pub(super) struct ColorPickerLayout {
+    limits: [f32; 4],
+    shape: PickerShape,
+    /// Height of the sliders, fields and swatches, in NDC.
+    row_height: f32,
+    /// Colors shown below the fields, picked by clicking them.
+    pub(super) swatches: Vec<[f32; 4]>,
+    panel_style: u32,
+    slider_style: u32,
+    input_style: u32,
+    swatch_style: u32,
+}
+
+impl ColorPickerLayout {
+    /// A color picker at `limits`, with rows of `row_height` below its color area, all in NDC.
+    pub(super) fn new(limits: [f32; 4], shape: PickerShape, row_height: f32) -> Self {
+        Self {
+            limits,
+            shape,
+            row_height,
+            swatches: vec![],
+            panel_style: 0,
+            slider_style: 0,
+            input_style: 0,
+            swatch_style: 0,
+        }
+    }
+
+    /// Builder method adding a row of swatches of `colors` at the bottom.
+    pub(super) fn with_swatches(mut self, colors: &[[f32; 4]]) -> Self {
+        self.swatches = colors.to_vec();
+        self
+    }
+
+    /// Builder method setting the styles of the panel behind the picker, of the color area and sliders,
+    /// of the fields and of the swatches. The radii of the sliders and swatches round them.
+    pub(super) fn with_styles(mut self, panel: u32, slider: u32, input: u32, swatch: u32) -> Self {
+        self.panel_style = panel;
+        self.slider_style = slider;
+        self.input_style = input;
+        self.swatch_style = swatch;
+        self
+    }
+
+    /// The indices of the parts, for a picker at `index`.
+    pub(super) fn parts(&self, index: usize) -> ColorParts {
+        ColorParts {
+            area: index + 1,
+            channel: index + 2,
+            alpha: index + 3,
+            preview: index + 4,
+            hex: index + 5,
+            rgb: [index + 6, index + 7, index + 8],
+            swatches: index + 9..index + 9 + self.swatches.len(),
+        }
+    }
+
+    /// The widgets of the picker, to be put at the index given to `parts`: its panel, then its parts in the order of `ColorParts`.
+    pub(super) fn widgets(&self) -> Vec<Widget> {
+        let [left, right, bottom, top] = self.limits;
+        let [left, right, mut bottom, top] = [left + PICKER_MARGIN, right - PICKER_MARGIN, bottom + PICKER_MARGIN, top - PICKER_MARGIN];
+        let height = self.row_height;
+        // Rows are taken from the bottom, the color area getting what is left
+        let mut row = || {
+            let limits = [left, right, bottom, bottom + height];
+            bottom += height + PICKER_MARGIN;
+            limits
+        };
+        let swatches = if self.swatches.is_empty() { [0.0; 4] } else { row() };
+        let fields = row();
+        let alpha = row();
+        let channel = row();
+        let area = [left, right, bottom, top.max(bottom)];
+
+        let channel_kind = match self.shape {
+            PickerShape::Square => ColorChannel::Hue,
+            PickerShape::Wheel => ColorChannel::Value,
+        };
+        let mut widgets = vec![
+            Widget::new(self.limits, WidgetType::RoundedRect).with_style(self.panel_style),
+            Widget::color_area(area, self.shape).with_style(self.slider_style),
+            Widget::color_slider(channel, channel_kind, Orientation::Horizontal).with_style(self.slider_style),
+            Widget::color_slider(alpha, ColorChannel::Alpha, Orientation::Horizontal).with_style(self.slider_style),
+        ];
+        // The preview is as wide as two rows are high, the hex field takes a third of the rest and the others share it
+        let [_, _, field_bottom, field_top] = fields;
+        let preview_right = (left + 2.0 * height).min(right);
+        widgets.push(Widget::swatch([left, preview_right, field_bottom, field_top], [1.0; 4]).with_style(self.swatch_style));
+        let rest = right - preview_right - 4.0 * PICKER_MARGIN;
+        let widths = [rest / 3.0, rest * 2.0 / 9.0, rest * 2.0 / 9.0, rest * 2.0 / 9.0];
+        let mut x = preview_right;
+        for width in widths {
+            x += PICKER_MARGIN;
+            widgets.push(Widget::new([x, x + width, field_bottom, field_top], WidgetType::TextInput).with_style(self.input_style));
+            x += width;
+        }
+        let [_, _, swatch_bottom, swatch_top] = swatches;
+        let width = ((right - left + PICKER_MARGIN) / self.swatches.len().max(1) as f32 - PICKER_MARGIN).min(height);
+        widgets.extend(self.swatches.iter().enumerate().map(|(i, &color)| {
+            let x = left + i as f32 * (width + PICKER_MARGIN);
+            Widget::swatch([x, x + width, swatch_bottom, swatch_top], color).with_style(self.swatch_style)
+        }));
+        widgets
+    }
+}
+
+/// The widgets of a color picker made from a `ColorPickerLayout`.
+#[derive(Clone, Debug)]
+pub(super) struct ColorParts {
+    pub(super) area: usize,
+    /// The hue slider of square pickers, or the value slider of wheels.
+    pub(super) channel: usize,
+    pub(super) alpha: usize,
+    /// The swatch showing the color.
+    pub(super) preview: usize,
+    pub(super) hex: usize,
+    pub(super) rgb: [usize; 3],
+    pub(super) swatches: Range<usize>,
+}
+
+/// The color of a color picker, with its widgets.
+pub(super) struct ColorPicker {
+    pub(super) parts: ColorParts,
+    pub(super) shape: PickerShape,
+    pub(super) color: Hsva,
+}
+
+impl ColorPicker {
+    pub(super) fn new(layout: &ColorPickerLayout, index: usize) -> Self {
+        Self { parts: layout.parts(index), shape: layout.shape, color: Hsva::default() }
+    }
+
+    /// Whether the widget `index` is one of the parts, the panel excluded.
+    pub(super) fn contains(&self, index: usize) -> bool {
+        (self.parts.area..self.parts.swatches.end).contains(&index)
+    }
+
+    /// The channel changed by the slider `index`, if it is one of the picker.
+    pub(super) fn channel_of(&self, index: usize) -> Option<ColorChannel> {
+        if index == self.parts.alpha {
+            Some(ColorChannel::Alpha)
+        } else if index == self.parts.channel {
+            Some(match self.shape {
+                PickerShape::Square => ColorChannel::Hue,
+                PickerShape::Wheel => ColorChannel::Value,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn channel(&self, channel: ColorChannel) -> f32 {
+        match channel {
+            ColorChannel::Hue => self.color.h,
+            ColorChannel::Value => self.color.v,
+            ColorChannel::Alpha => self.color.a,
+        }
+    }
+
+    pub(super) fn set_channel(&mut self, channel: ColorChannel, value: f32) {
+        let value = value.clamp(0.0, 1.0);
+        match channel {
+            ColorChannel::Hue => self.color.h = value,
+            ColorChannel::Value => self.color.v = value,
+            ColorChannel::Alpha => self.color.a = value,
+        }
+    }
+
+    /// Move the marker of the color area to `p`: from (0, 0) top left to (1, 1) bottom right of a square,
+    /// or from the center of a wheel in radii, y pointing down.
+    pub(super) fn set_area(&mut self, p: [f32; 2]) {
+        match self.shape {
+            PickerShape::Square => {
+                self.color.s = p[0].clamp(0.0, 1.0);
+                self.color.v = 1.0 - p[1].clamp(0.0, 1.0);
+            }
+            PickerShape::Wheel => {
+                let [x, y] = p;
+                if x != 0.0 || y != 0.0 {
+                    self.color.h = (-y).atan2(x).rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+                }
+                self.color.s = x.hypot(y).min(1.0);
+            }
+        }
+    }
+
+    /// Move the marker of the color area by `steps` hundredths, right and up.
+    /// Wheels turn counterclockwise and grow their saturation instead.
+    pub(super) fn step_area(&mut self, steps: [f32; 2]) {
+        let [x, y] = steps.map(|s| s / 100.0);
+        match self.shape {
+            PickerShape::Square => {
+                self.color.s = (self.color.s + x).clamp(0.0, 1.0);
+                self.color.v = (self.color.v + y).clamp(0.0, 1.0);
+            }
+            PickerShape::Wheel => {
+                self.color.h = (self.color.h + x).rem_euclid(1.0);
+                self.color.s = (self.color.s + y).clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    /// `ty[1]` and `ty[2]` of the color area: the hue ORed with `COLOR_WHEEL` for wheels, and the saturation and value,
+    /// all in 1/65535ths, packed as `s | v << 16`.
+    pub(super) fn area_ty(&self) -> [u32; 2] {
+        let wheel = if self.shape == PickerShape::Wheel { COLOR_WHEEL } else { 0 };
+        [fraction(self.color.h) | wheel, fraction(self.color.s) | fraction(self.color.v) << 16]
+    }
+
+    /// `ty[1]` of the slider of `channel`: the packed color its gradient goes to.
+    pub(super) fn slider_color(&self, channel: ColorChannel) -> u32 {
+        match channel {
+            ColorChannel::Hue => 0,
+            ColorChannel::Value => pack_rgba(Hsva { v: 1.0, a: 1.0, ..self.color }.rgba()),
+            ColorChannel::Alpha => pack_rgba(Hsva { a: 1.0, ..self.color }.rgba()),
+        }
+    }
+}
+
+/// `value` from 0 to 1 in 1/65535ths.
+pub(super) fn fraction(value: f32) -> u32 {
+    (value.clamp(0.0, 1.0) * 65535.0).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rgba(actual: [f32; 4], expected: [f32; 4]) {
+        assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-5), "{actual:?} isn't {expected:?}");
+    }
+
+    #[test]
+    fn hsva_round_trip() {
+        for rgba in [[1.0, 0.0, 0.0, 1.0], [0.2, 0.6, 0.4, 0.5], [0.1, 0.2, 0.9, 0.0], [1.0, 1.0, 0.0, 1.0], [0.5, 0.0, 0.5, 1.0]] {
+            assert_rgba(Hsva::from_rgba(rgba, Hsva::default()).rgba(), rgba);
+        }
+        let red = Hsva::from_rgba([1.0, 0.0, 0.0, 1.0], Hsva::default());
+        assert_eq!(red, Hsva { h: 0.0, s: 1.0, v: 1.0, a: 1.0 });
+        // Grays keep the previous hue, and black the previous saturation too
+        let blue = Hsva { h: 2.0 / 3.0, s: 0.8, v: 1.0, a: 1.0 };
+        assert_eq!(Hsva::from_rgba([0.5, 0.5, 0.5, 1.0], blue), Hsva { h: 2.0 / 3.0, s: 0.0, v: 0.5, a: 1.0 });
+        assert_eq!(Hsva::from_rgba([0.0, 0.0, 0.0, 0.25], blue), Hsva { v: 0.0, a: 0.25, ..blue });
+    }
+
+    #[test]
+    fn packing() {
+        assert_eq!(pack_rgba([1.0, 0.0, 0.5, 1.0]), 0xff80_00ff);
+        assert_eq!(pack_rgba([2.0, -1.0, 0.0, 0.0]), 0x0000_00ff);
+        for packed in [0x0000_0000, 0xff80_00ff, 0x1234_5678, 0xffff_ffff] {
+            assert_eq!(pack_rgba(unpack_rgba(packed)), packed);
+        }
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(to_hex([1.0, 0.0, 0.5, 1.0]), "#ff0080");
+        assert_eq!(to_hex([1.0, 0.0, 0.5, 0.0]), "#ff008000");
+        for hex in ["#000000", "#ff0080", "#12345678", "#ffffff00"] {
+            assert_eq!(to_hex(parse_hex(hex).unwrap()), hex);
+        }
+        assert_rgba(parse_hex("#f08").unwrap(), [1.0, 0.0, 0.53333, 1.0]);
+        assert_rgba(parse_hex(" F08C ").unwrap(), [1.0, 0.0, 0.53333, 0.8]);
+        assert_rgba(parse_hex("ff008080").unwrap(), [1.0, 0.0, 0.50196, 0.50196]);
+    }
+
+    #[test]
+    fn malformed_hex() {
+        for text in ["", "#", "##fff", "###ffffff", "#ff", "#fffff", "#fffffff", "#fffffffff", "#ffg", "#-ff", "fff#", "# fff", "#ff ff"] {
+            assert_eq!(parse_hex(text), None, "{text:?} was parsed");
+        }
+    }
+}
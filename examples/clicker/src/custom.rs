@@ -0,0 +1,335 @@
+use std::fmt;
+
+use crate::color::pack_rgba;
+
+/// `ty[0]` of the first custom widget type, the ones below being left to the built-in widgets.
+pub(super) const CUSTOM_TYPE_BASE: u32 = 256;
+/// Replaced by the functions of the custom widgets in `w__fragment.wgsl`.
+const FUNCTIONS_MARKER: &str = "// CUSTOM WIDGET FUNCTIONS";
+/// Replaced by the cases of the custom widgets in the type switch of `fs_main`.
+const CASES_MARKER: &str = "// CUSTOM WIDGET CASES";
+/// The fragment shader the custom widgets are composed into.
+const FRAGMENT_SHADER: &str = include_str!("w__fragment.wgsl");
+/// Words WGSL doesn't allow as names.
+const WGSL_KEYWORDS: [&str; 26] = [
+    "alias", "break", "case", "const", "const_assert", "continue", "continuing", "default", "diagnostic", "discard", "else", "enable",
+    "false", "fn", "for", "if", "let", "loop", "override", "requires", "return", "struct", "switch", "true", "var", "while",
+];
+
+#[derive(Debug)]
+pub(super) enum CustomError {
+    /// The name isn't a WGSL identifier, or is taken by the fragment shader or another custom widget.
+    Name(String),
+    /// The parameters don't fit in `ty[1]` and `ty[2]`.
+    Params(String),
+    /// The fragment shader doesn't compile with the WGSL of the widget.
+    Shader(String),
+}
+
+impl fmt::Display for CustomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(e) => write!(f, "invalid custom widget name: {e}"),
+            Self::Params(e) => write!(f, "invalid custom widget parameters: {e}"),
+            Self::Shader(e) => write!(f, "invalid custom widget WGSL: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CustomError {}
+
+/// How a parameter of a custom widget is stored in `ty[1]` and `ty[2]`, and read by its WGSL function.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum ParamKind {
+    /// 1 bit, read as a `bool`.
+    Bool,
+    /// 8 bits, read as a `u32`.
+    U8,
+    /// 16 bits, read as a `u32`.
+    U16,
+    /// 8 bits, read as an `f32` from 0 to 1.
+    Unorm8,
+    /// 16 bits, read as an `f32` from 0 to 1.
+    Unorm16,
+    /// 32 bits, read as an `f32`.
+    F32,
+    /// 32 bits packed by `pack_rgba`, read as a `vec4<f32>`.
+    Rgba8,
+}
+
+impl ParamKind {
+    fn bits(&self) -> u32 {
+        match self {
+            Self::Bool => 1,
+            Self::U8 | Self::Unorm8 => 8,
+            Self::U16 | Self::Unorm16 => 16,
+            Self::F32 | Self::Rgba8 => 32,
+        }
+    }
+
+    fn wgsl_type(&self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::U8 | Self::U16 => "u32",
+            Self::Unorm8 | Self::Unorm16 | Self::F32 => "f32",
+            Self::Rgba8 => "vec4<f32>",
+        }
+    }
+
+    /// The WGSL expression reading the parameter from the u32 expression `word`, shifted so that it starts at bit 0.
+    fn wgsl_read(&self, word: &str) -> String {
+        match self {
+            Self::Bool => format!("({word} & 1u) != 0u"),
+            Self::U8 => format!("{word} & 0xffu"),
+            Self::U16 => format!("{word} & 0xffffu"),
+            Self::Unorm8 => format!("f32({word} & 0xffu) / 255.0"),
+            Self::Unorm16 => format!("f32({word} & 0xffffu) / 65535.0"),
+            Self::F32 => format!("bitcast<f32>({word})"),
+            Self::Rgba8 => format!("unpack4x8unorm({word})"),
+        }
+    }
+
+    /// The bits of `value`, which must be of a matching kind.
+    fn pack(&self, value: ParamValue) -> u32 {
+        let mask = if self.bits() == 32 { u32::MAX } else { (1 << self.bits()) - 1 };
+        match (self, value) {
+            (Self::Bool, ParamValue::Bool(b)) => b as u32,
+            (Self::U8 | Self::U16, ParamValue::Uint(n)) => n.min(mask),
+            (Self::Unorm8 | Self::Unorm16, ParamValue::Float(x)) => (x.clamp(0.0, 1.0) * mask as f32).round() as u32,
+            (Self::F32, ParamValue::Float(x)) => x.to_bits(),
+            (Self::Rgba8, ParamValue::Color(rgba)) => pack_rgba(rgba),
+            (kind, value) => panic!("A {kind:?} parameter can't hold {value:?}"),
+        }
+    }
+}
+
+/// The value of a parameter of a custom widget, for `ParamKind`s of the same type.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum ParamValue {
+    Bool(bool),
+    /// For `U8` and `U16`, clamped to their range.
+    Uint(u32),
+    /// For `Unorm8` and `Unorm16`, clamped from 0 to 1, and `F32`.
+    Float(f32),
+    /// RGBA from 0 to 1, for `Rgba8`.
+    Color([f32; 4]),
+}
+
+/// A widget drawn by a WGSL function of the application, composed into the fragment shader when `State::new` builds the pipeline.
+/// It is picked like the built-in widgets where its function doesn't discard, gets their hovered, pressed and disabled states,
+/// and reports `WidgetEvent::Clicked` when clicked.
+pub(super) trait CustomWidget {
+    /// Name of its WGSL function, a WGSL identifier unique among the custom widgets and not declared by the fragment shader.
+    fn name(&self) -> &str;
+
+    /// Names and kinds of its parameters, packed in order into `ty[1]` then `ty[2]`, a parameter not straddling them.
+    fn params(&self) -> &[(&str, ParamKind)];
+
+    /// WGSL source of `fn <name>(in: VertexOutput, params: <name>_params) -> vec4<f32>`, with any helpers it needs,
+    /// the struct `<name>_params` holding its parameters by name.
+    /// It returns the color of the pixel `in.position.xy` of the widget `in.id`, or discards it,
+    /// and can use the functions of `w__fragment.wgsl` such as `pixel_limits`, `widget_style` and `sdf_paint`.
+    fn wgsl(&self) -> &str;
+}
+
+/// A registered custom widget type, see `CustomWidgets::register`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct CustomType(u32);
+
+impl CustomType {
+    /// Its `ty[0]`.
+    pub(super) fn ty(&self) -> u32 {
+        self.0
+    }
+}
+
+/// A custom widget with where its parameters are, as their word in `ty[1..3]` and their shift.
+struct Registered {
+    widget: Box<dyn CustomWidget>,
+    offsets: Vec<(usize, u32)>,
+}
+
+/// The custom widgets of an application, given to `State::new`.
+#[derive(Default)]
+pub(super) struct CustomWidgets {
+    registered: Vec<Registered>,
+}
+
+impl CustomWidgets {
+    /// Add `widget`, returning its type for `Widget::custom`.
+    /// Fails if its name isn't a WGSL identifier or is taken, if its parameters don't fit in 64 bits,
+    /// or if the fragment shader doesn't compile with its WGSL, which is checked here rather than when `State::new` builds the pipeline.
+    pub(super) fn register(&mut self, widget: impl CustomWidget + 'static) -> Result<CustomType, CustomError> {
+        let name = widget.name();
+        let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && name != "_" && !name.starts_with("__");
+        if !is_identifier || WGSL_KEYWORDS.contains(&name) {
+            return Err(CustomError::Name(format!("{name:?} isn't a WGSL identifier")));
+        }
+        // Its function, parameters struct and loading function go next to the declarations of the shader
+        let generated = [name.to_string(), format!("{name}_params"), format!("{name}_load")];
+        if let Some(taken) = declared_names(FRAGMENT_SHADER).find(|d| generated.iter().any(|g| g == d)) {
+            return Err(CustomError::Name(format!("{taken} is declared by the fragment shader")));
+        }
+        if self.registered.iter().any(|r| r.widget.name() == name) {
+            return Err(CustomError::Name(format!("a custom widget is already named {name}")));
+        }
+
+        let mut offsets = vec![];
+        let (mut word, mut shift) = (0, 0);
+        for (param, kind) in widget.params() {
+            if shift + kind.bits() > 32 {
+                (word, shift) = (word + 1, 0);
+            }
+            if word >= 2 {
+                return Err(CustomError::Params(format!("the parameters of {name} don't fit in 64 bits, from {param}")));
+            }
+            offsets.push((word, shift));
+            shift += kind.bits();
+        }
+        self.registered.push(Registered { widget: Box::new(widget), offsets });
+        if let Err(e) = validate(&self.fragment_shader()) {
+            self.registered.pop();
+            return Err(CustomError::Shader(e));
+        }
+        Ok(CustomType(CUSTOM_TYPE_BASE + self.registered.len() as u32 - 1))
+    }
+
+    /// `ty[1]` and `ty[2]` of a widget of type `ty` with the parameters `values`, in the order of `CustomWidget::params`.
+    /// Missing parameters are 0, panics if one doesn't match its kind.
+    pub(super) fn pack(&self, ty: CustomType, values: &[ParamValue]) -> [u32; 2] {
+        let registered = &self.registered[(ty.0 - CUSTOM_TYPE_BASE) as usize];
+        let mut words = [0; 2];
+        for ((&(_, kind), &(word, shift)), &value) in registered.widget.params().iter().zip(&registered.offsets).zip(values) {
+            words[word] |= kind.pack(value) << shift;
+        }
+        words
+    }
+
+    /// The type of widgets of `ty[0]`, if it is a custom one.
+    pub(super) fn custom_type(&self, ty: u32) -> Option<CustomType> {
+        (ty >= CUSTOM_TYPE_BASE && ((ty - CUSTOM_TYPE_BASE) as usize) < self.registered.len()).then_some(CustomType(ty))
+    }
+
+    /// The fragment shader with the functions of the custom widgets, and their cases in its type switch.
+    pub(super) fn fragment_shader(&self) -> String {
+        let mut functions = String::new();
+        let mut cases = String::new();
+        for (i, Registered { widget, offsets }) in self.registered.iter().enumerate() {
+            let name = widget.name();
+            let fields: String = widget.params().iter().map(|(field, kind)| format!("    {field}: {},\n", kind.wgsl_type())).collect();
+            let reads: String = widget.params().iter().zip(offsets).map(|((field, kind), (word, shift))| {
+                format!("    p.{field} = {};\n", kind.wgsl_read(&format!("(ty[{}] >> {shift}u)", word + 1)))
+            }).collect();
+            // WGSL structs can't be empty
+            let fields = if fields.is_empty() { "    _unused: u32,\n".to_string() } else { fields };
+            functions += &format!(
+                "struct {name}_params {{\n{fields}}};\n\
+                fn {name}_load(id: u32) -> {name}_params {{\n    let ty = widget[id].ty;\n    var p: {name}_params;\n{reads}    return p;\n}}\n\
+                {}\n",
+                widget.wgsl(),
+            );
+            cases += &format!("        case {}u: {{\n            color = {name}(in, {name}_load(in.id));\n        }}\n", CUSTOM_TYPE_BASE + i as u32);
+        }
+        FRAGMENT_SHADER.replacen(FUNCTIONS_MARKER, &functions, 1).replacen(CASES_MARKER, &cases, 1)
+    }
+}
+
+/// The names of the functions, structs, constants and variables declared at the top level of `source`, not indented.
+fn declared_names(source: &str) -> impl Iterator<Item = &str> {
+    source.lines().filter_map(|line| {
+        let rest = ["fn ", "struct ", "const ", "alias ", "override ", "var "].iter().find_map(|keyword| line.strip_prefix(keyword))
+            .or_else(|| line.strip_prefix("var<").and_then(|r| r.split_once('>')).map(|(_, r)| r))?
+            .trim_start();
+        let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        Some(&rest[..end])
+    })
+}
+
+/// Parse and validate the WGSL `source` as the device would, returning the error message if it fails.
+fn validate(source: &str) -> Result<(), String> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::default())
+        .validate(&module)
+        .map_err(|e| e.emit_to_string(source))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A widget of `name` drawing its color, with `extra` WGSL after its function.
+    struct Fill {
+        name: &'static str,
+        wgsl: String,
+    }
+
+    impl Fill {
+        fn new(name: &'static str, extra: &str) -> Self {
+            let wgsl = format!("fn {name}(in: VertexOutput, params: {name}_params) -> vec4<f32> {{\n    return params.color;\n}}\n{extra}");
+            Self { name, wgsl }
+        }
+    }
+
+    impl CustomWidget for Fill {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn params(&self) -> &[(&str, ParamKind)] {
+            &[("color", ParamKind::Rgba8), ("on", ParamKind::Bool)]
+        }
+
+        fn wgsl(&self) -> &str {
+            &self.wgsl
+        }
+    }
+
+    #[test]
+    fn names() {
+        let mut custom = CustomWidgets::default();
+        let ty = custom.register(Fill::new("fill", "")).unwrap();
+        assert_eq!(ty.ty(), CUSTOM_TYPE_BASE);
+        assert!(custom.fragment_shader().contains("case 256u"));
+        for name in ["fill", "chart", "viewport", "text_input", "widget", "TAU", "Style", "2d", "a-b", "", "_", "__x", "loop"] {
+            assert!(matches!(custom.register(Fill::new(name, "")), Err(CustomError::Name(_))), "{name}");
+        }
+        // Its generated struct would be declared twice
+        assert!(matches!(custom.register(Fill::new("widget_style", "")), Err(CustomError::Name(_))));
+        assert_eq!(custom.register(Fill::new("fill2", "")).unwrap().ty(), CUSTOM_TYPE_BASE + 1);
+    }
+
+    #[test]
+    fn invalid_wgsl() {
+        let mut custom = CustomWidgets::default();
+        for extra in ["fn broken( {", "fn pixel_limits(id: u32) -> f32 { return 0.0; }", "fn typo() -> f32 { return 1u; }"] {
+            assert!(matches!(custom.register(Fill::new("fill", extra)), Err(CustomError::Shader(_))), "{extra}");
+        }
+        // Failed registrations are forgotten
+        assert_eq!(custom.register(Fill::new("fill", "")).unwrap().ty(), CUSTOM_TYPE_BASE);
+    }
+
+    #[test]
+    fn params() {
+        struct Wide;
+        impl CustomWidget for Wide {
+            fn name(&self) -> &str {
+                "wide"
+            }
+            fn params(&self) -> &[(&str, ParamKind)] {
+                &[("a", ParamKind::F32), ("b", ParamKind::Bool), ("c", ParamKind::F32)]
+            }
+            fn wgsl(&self) -> &str {
+                ""
+            }
+        }
+        let mut custom = CustomWidgets::default();
+        assert!(matches!(custom.register(Wide), Err(CustomError::Params(_))));
+        let ty = custom.register(Fill::new("fill", "")).unwrap();
+        assert_eq!(custom.pack(ty, &[ParamValue::Color([1.0, 0.0, 0.0, 1.0]), ParamValue::Bool(true)]), [pack_rgba([1.0, 0.0, 0.0, 1.0]), 1]);
+    }
+}
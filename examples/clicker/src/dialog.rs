@@ -0,0 +1,149 @@
+use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc, task::{Context, Poll, Waker}};
+
+use crate::state::{Widget, WidgetType};
+
+/// Space between the border of a stock dialog and its buttons and input, in NDC.
+const DIALOG_MARGIN: f32 = 0.02;
+
+/// The stock dialogs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum DialogKind {
+    /// An OK button.
+    Alert,
+    /// OK and cancel buttons.
+    Confirm,
+    /// A text input above OK and cancel buttons.
+    Prompt,
+}
+
+/// How a dialog was closed.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum DialogResult {
+    Ok,
+    /// With the cancel button or escape.
+    Cancel,
+    /// With the OK button of a prompt, with the text entered.
+    Text(String),
+}
+
+/// Called with the result of a dialog when it closes.
+pub(super) type DialogCallback = Box<dyn FnOnce(DialogResult)>;
+
+/// Where the parts of a stock dialog go, and their styles.
+/// Its buttons are in its bottom right corner, OK on the right. The rest of it is left to the application's widgets.
+#[derive(Clone, Debug)]
+pub(super) struct DialogLayout {
+    kind: DialogKind,
+    limits: [f32; 4],
+    /// Width and height of the buttons, and height of the text input, in NDC.
+    button_size: [f32; 2],
+    dialog_style: u32,
+    button_style: u32,
+    input_style: u32,
+}
+
+impl DialogLayout {
+    /// A dialog of `kind` at `limits`, with buttons of `button_size`, all in NDC.
+    pub(super) fn new(kind: DialogKind, limits: [f32; 4], button_size: [f32; 2]) -> Self {
+        Self { kind, limits, button_size, dialog_style: 0, button_style: 0, input_style: 0 }
+    }
+
+    /// Builder method setting the styles of the dialog, of its buttons and of its text input.
+    pub(super) fn with_styles(mut self, dialog: u32, button: u32, input: u32) -> Self {
+        self.dialog_style = dialog;
+        self.button_style = button;
+        self.input_style = input;
+        self
+    }
+
+    /// The indices of the buttons and input, for a dialog at `index`.
+    pub(super) fn parts(&self, index: usize) -> DialogParts {
+        DialogParts {
+            ok: index + 1,
+            cancel: (self.kind != DialogKind::Alert).then_some(index + 2),
+            input: (self.kind == DialogKind::Prompt).then_some(index + 3),
+        }
+    }
+
+    /// The widgets of the dialog, to be put at `index` in the widgets: the dialog, its OK and cancel buttons, then its text input.
+    /// The application's widgets in it follow them, put in it with `in_popup`.
+    pub(super) fn widgets(&self, index: usize) -> Vec<Widget> {
+        let [left, right, bottom, top] = self.limits;
+        let [width, height] = self.button_size;
+        let button = |n: f32| {
+            let right = right - DIALOG_MARGIN - n * (width + DIALOG_MARGIN);
+            Widget::new([right - width, right, bottom + DIALOG_MARGIN, bottom + DIALOG_MARGIN + height], WidgetType::Button)
+                .with_style(self.button_style)
+                .in_popup(index)
+        };
+        let mut widgets = vec![Widget::dialog(self.limits).with_style(self.dialog_style), button(0.0)];
+        if self.kind != DialogKind::Alert {
+            widgets.push(button(1.0));
+        }
+        if self.kind == DialogKind::Prompt {
+            let bottom = bottom + 2.0 * DIALOG_MARGIN + height;
+            let input = [left + DIALOG_MARGIN, right - DIALOG_MARGIN, bottom, (bottom + height).min(top - DIALOG_MARGIN)];
+            widgets.push(Widget::new(input, WidgetType::TextInput).with_style(self.input_style).in_popup(index));
+        }
+        widgets
+    }
+}
+
+/// The result of a dialog once it is closed, shared between its callback and `DialogFuture`.
+#[derive(Default)]
+struct DialogSlot {
+    result: Option<DialogResult>,
+    waker: Option<Waker>,
+}
+
+/// Resolves to the result of a dialog when it closes, see `State::ask`.
+pub(super) struct DialogFuture(Rc<RefCell<DialogSlot>>);
+
+impl DialogFuture {
+    /// A future, and the callback resolving it.
+    pub(super) fn new() -> (Self, DialogCallback) {
+        let slot = Rc::new(RefCell::new(DialogSlot::default()));
+        let callback = {
+            let slot = slot.clone();
+            Box::new(move |result| {
+                let mut slot = slot.borrow_mut();
+                slot.result = Some(result);
+                if let Some(waker) = slot.waker.take() {
+                    waker.wake();
+                }
+            })
+        };
+        (Self(slot), callback)
+    }
+}
+
+impl Future for DialogFuture {
+    type Output = DialogResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<DialogResult> {
+        let mut slot = self.0.borrow_mut();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A shown dialog.
+pub(super) struct Modal {
+    pub(super) dialog: usize,
+    pub(super) on_close: Option<DialogCallback>,
+    /// The widget focused before it was shown, focused again when it closes.
+    pub(super) restore_focus: Option<usize>,
+}
+
+/// The buttons and input of a dialog made from a `DialogLayout`.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct DialogParts {
+    pub(super) ok: usize,
+    pub(super) cancel: Option<usize>,
+    pub(super) input: Option<usize>,
+}
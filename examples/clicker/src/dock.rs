@@ -0,0 +1,631 @@
+use std::{fmt, mem, str::FromStr};
+
+use crate::{overlay::Side, slider::Orientation, state::{Widget, WidgetType}};
+
+/// Thickness of the dividers between the panels of a dock, in pixels.
+pub(super) const DIVIDER_WIDTH: f32 = 6.0;
+/// Set in `ty[1]` of the dividers between panels side by side, dragged horizontally.
+/// Must match `DIVIDER_VERTICAL` in `w__fragment.wgsl`.
+pub(super) const DIVIDER_VERTICAL: u32 = 1;
+/// Tab bars from this one up are those of the groups of tabs of docks, ORed with the index of the first tab of their group.
+pub(super) const DOCK_TAB_BARS: u32 = 1 << 23;
+/// Fraction of the width or height of a group of tabs along its edges where dropping a tab splits the group
+/// instead of joining it.
+const DROP_EDGE: f32 = 0.25;
+/// Deepest nesting of splits read by `DockNode::from_str`, bounding its recursion.
+const MAX_DEPTH: usize = 64;
+
+/// How the panels of a dock are arranged, numbered from 0 in the order of `DockLayout::widgets`.
+///
+/// It is written as text with `to_string` and read back with `parse`, to keep arrangements across sessions:
+/// `h(0.3,[0*,1],v(0.5,[2],[3]))` is panels 0 and 1 in tabs, 0 selected, left of panel 2 above panel 3.
+#[derive(Clone, Debug)]
+pub(super) enum DockNode {
+    /// Two nodes side by side with a `Horizontal` orientation or stacked with a `Vertical` one, `first` on the left or top.
+    /// `ratio` is the fraction of the space left by the divider going to `first`, 0 or 1 collapsing a side.
+    Split { orientation: Orientation, ratio: f32, first: Box<DockNode>, second: Box<DockNode> },
+    /// Panels sharing the same place with a tab each, only the `selected` one shown.
+    Tabs { panels: Vec<usize>, selected: usize },
+}
+
+impl DockNode {
+    /// The panels `panels` in tabs, the first one selected.
+    pub(super) fn tabs(panels: &[usize]) -> Self {
+        Self::Tabs { panels: panels.to_vec(), selected: panels[0] }
+    }
+
+    /// `first` and `second` side by side or stacked, `first` getting `ratio` of the space.
+    pub(super) fn split(orientation: Orientation, ratio: f32, first: DockNode, second: DockNode) -> Self {
+        Self::Split { orientation, ratio, first: Box::new(first), second: Box::new(second) }
+    }
+
+    /// Check that the node holds each of `count` panels once, with valid ratios and selected tabs.
+    pub(super) fn validate(&self, count: usize) -> Result<(), DockError> {
+        let mut seen = vec![false; count];
+        self.check(&mut seen)?;
+        match seen.iter().position(|&seen| !seen) {
+            Some(panel) => Err(DockError::Panels(format!("panel {panel} is missing"))),
+            None => Ok(()),
+        }
+    }
+
+    fn check(&self, seen: &mut [bool]) -> Result<(), DockError> {
+        match self {
+            Self::Split { ratio, first, second, .. } => {
+                if !(0.0..=1.0).contains(ratio) {
+                    return Err(DockError::Syntax(format!("ratio {ratio} isn't between 0 and 1")));
+                }
+                first.check(seen)?;
+                second.check(seen)
+            }
+            Self::Tabs { panels, selected } => {
+                if !panels.contains(selected) {
+                    return Err(DockError::Panels(format!("selected panel {selected} isn't in its tabs")));
+                }
+                for &panel in panels {
+                    match seen.get_mut(panel) {
+                        None => return Err(DockError::Panels(format!("there is no panel {panel}"))),
+                        Some(true) => return Err(DockError::Panels(format!("panel {panel} is in more than one tab"))),
+                        Some(seen) => *seen = true,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The number of panels in the node.
+    fn panel_count(&self) -> usize {
+        match self {
+            Self::Split { first, second, .. } => first.panel_count() + second.panel_count(),
+            Self::Tabs { panels, .. } => panels.len(),
+        }
+    }
+
+    /// The tabs holding `panel`.
+    fn tabs_of(&mut self, panel: usize) -> Option<&mut DockNode> {
+        match self {
+            Self::Split { first, second, .. } => match first.tabs_of(panel) {
+                Some(tabs) => Some(tabs),
+                None => second.tabs_of(panel),
+            },
+            Self::Tabs { panels, .. } if panels.contains(&panel) => Some(self),
+            Self::Tabs { .. } => None,
+        }
+    }
+
+    /// The split at `path` from this node, `true` going to the second node of each split.
+    fn node_at(&mut self, path: &[bool]) -> &mut DockNode {
+        match (self, path) {
+            (node, []) => node,
+            (Self::Split { first, second, .. }, [side, rest @ ..]) => if *side { second } else { first }.node_at(rest),
+            (Self::Tabs { .. }, _) => panic!("no split at {path:?}"),
+        }
+    }
+
+    /// Select the tab of `panel`.
+    pub(super) fn select(&mut self, panel: usize) {
+        if let Some(Self::Tabs { selected, .. }) = self.tabs_of(panel) {
+            *selected = panel;
+        }
+    }
+
+    /// The node without `panel`, `None` if it was its only panel. Splits left with one side are replaced by it.
+    fn without(self, panel: usize) -> Option<DockNode> {
+        match self {
+            Self::Split { orientation, ratio, first, second } => match (first.without(panel), second.without(panel)) {
+                (Some(first), Some(second)) => Some(Self::split(orientation, ratio, first, second)),
+                (first, second) => first.or(second),
+            },
+            Self::Tabs { mut panels, selected } => {
+                let position = panels.iter().position(|&p| p == panel);
+                if let Some(position) = position {
+                    panels.remove(position);
+                }
+                let selected = match position {
+                    // The tab after it gets selected, or the one before for the last tab
+                    Some(position) if selected == panel => *panels.get(position).or(panels.last())?,
+                    _ => selected,
+                };
+                (!panels.is_empty()).then_some(Self::Tabs { panels, selected })
+            }
+        }
+    }
+
+    /// Move `panel` to `target`: selected in the tabs of its panel, or beside them.
+    pub(super) fn move_panel(&mut self, panel: usize, target: DropTarget) {
+        let root = mem::replace(self, Self::tabs(&[panel]));
+        *self = root.without(panel).expect("the target panel stays in the dock");
+        let tabs = self.tabs_of(target.panel).expect("the target panel is in the dock");
+        match target.side {
+            None => {
+                if let Self::Tabs { panels, selected } = tabs {
+                    panels.push(panel);
+                    *selected = panel;
+                }
+            }
+            Some(side) => {
+                let old = mem::replace(tabs, Self::tabs(&[panel]));
+                let new = Self::tabs(&[panel]);
+                *tabs = match side {
+                    Side::Left => Self::split(Orientation::Horizontal, 0.5, new, old),
+                    Side::Right => Self::split(Orientation::Horizontal, 0.5, old, new),
+                    Side::Above => Self::split(Orientation::Vertical, 0.5, new, old),
+                    Side::Below => Self::split(Orientation::Vertical, 0.5, old, new),
+                };
+            }
+        }
+    }
+
+    /// The shortest the node can be along `orientation`, with groups of tabs at least `min` long and dividers `gap` thick,
+    /// collapsed sides taking no space.
+    fn min_length(&self, orientation: Orientation, min: f32, gap: f32) -> f32 {
+        let Self::Split { orientation: split, ratio, first, second } = self else { return min };
+        let first = if *ratio > 0.0 { first.min_length(orientation, min, gap) } else { 0.0 };
+        let second = if *ratio < 1.0 { second.min_length(orientation, min, gap) } else { 0.0 };
+        match (split, orientation) {
+            (Orientation::Horizontal, Orientation::Horizontal) | (Orientation::Vertical, Orientation::Vertical) => first + second + gap,
+            _ => first.max(second),
+        }
+    }
+
+    /// Where the groups of tabs and the dividers go in `limits`, with dividers `gap` wide and high, all in NDC.
+    fn place(&self, limits: [f32; 4], gap: [f32; 2], path: &mut Vec<bool>, placement: &mut Placement) {
+        let (orientation, ratio, first, second) = match self {
+            Self::Split { orientation, ratio, first, second } => (*orientation, *ratio, first, second),
+            Self::Tabs { panels, selected } => {
+                placement.groups.push(Group { panels: panels.clone(), selected: *selected, limits });
+                return;
+            }
+        };
+        let [left, right, bottom, top] = limits;
+        let (a, divider, b) = match orientation {
+            Orientation::Horizontal => {
+                let x = left + (right - left - gap[0]).max(0.0) * ratio;
+                let end = (x + gap[0]).min(right);
+                ([left, x, bottom, top], [x, end, bottom, top], [end, right, bottom, top])
+            }
+            Orientation::Vertical => {
+                let y = top - (top - bottom - gap[1]).max(0.0) * ratio;
+                let end = (y - gap[1]).max(bottom);
+                ([left, right, y, top], [left, right, end, y], [left, right, bottom, end])
+            }
+        };
+        placement.dividers.push(Divider { path: path.clone(), orientation, limits: divider, span: limits });
+        path.push(false);
+        first.place(a, gap, path, placement);
+        path.pop();
+        path.push(true);
+        second.place(b, gap, path, placement);
+        path.pop();
+    }
+}
+
+impl fmt::Display for DockNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Split { orientation, ratio, first, second } => {
+                let axis = match orientation {
+                    Orientation::Horizontal => 'h',
+                    Orientation::Vertical => 'v',
+                };
+                write!(f, "{axis}({ratio},{first},{second})")
+            }
+            Self::Tabs { panels, selected } => {
+                write!(f, "[")?;
+                for (i, panel) in panels.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    let mark = if panel == selected && panels.len() > 1 { "*" } else { "" };
+                    write!(f, "{separator}{panel}{mark}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl FromStr for DockNode {
+    type Err = DockError;
+
+    /// Read a node written by `to_string`, tabs without a panel marked with `*` selecting their first one.
+    /// Whether it holds the panels of a dock is checked by `State::set_dock_layout`.
+    fn from_str(s: &str) -> Result<Self, DockError> {
+        let mut rest = s;
+        let node = parse_node(&mut rest, 0)?;
+        match rest.trim() {
+            "" => Ok(node),
+            rest => Err(DockError::Syntax(format!("unexpected `{rest}` after the layout"))),
+        }
+    }
+}
+
+/// Read the node at the start of `s`, moving `s` past it, within `depth` splits.
+fn parse_node(s: &mut &str, depth: usize) -> Result<DockNode, DockError> {
+    let rest = s.trim_start();
+    if let Some(rest) = rest.strip_prefix('[') {
+        let close = rest.find(']').ok_or_else(|| DockError::Syntax("unclosed `[`".to_string()))?;
+        let mut panels = vec![];
+        let mut selected = None;
+        for tab in rest[..close].split(',').map(str::trim) {
+            let (number, marked) = match tab.strip_suffix('*') {
+                Some(number) => (number.trim_end(), true),
+                None => (tab, false),
+            };
+            let panel = number.parse().map_err(|_| DockError::Syntax(format!("invalid panel `{tab}`")))?;
+            if marked {
+                selected = Some(panel);
+            }
+            panels.push(panel);
+        }
+        *s = &rest[close + 1..];
+        return Ok(DockNode::Tabs { selected: selected.unwrap_or(panels[0]), panels });
+    }
+    let orientation = match rest.chars().next() {
+        Some('h') => Orientation::Horizontal,
+        Some('v') => Orientation::Vertical,
+        _ => return Err(DockError::Syntax(format!("expected `h(`, `v(` or `[` at `{rest}`"))),
+    };
+    if depth >= MAX_DEPTH {
+        return Err(DockError::Syntax(format!("splits nested deeper than {MAX_DEPTH}")));
+    }
+    let rest = rest[1..].trim_start().strip_prefix('(').ok_or_else(|| DockError::Syntax("expected `(` after the orientation".to_string()))?;
+    let comma = rest.find(',').ok_or_else(|| DockError::Syntax("expected `,` after the ratio".to_string()))?;
+    let ratio = rest[..comma].trim().parse().map_err(|_| DockError::Syntax(format!("invalid ratio `{}`", &rest[..comma])))?;
+    *s = &rest[comma + 1..];
+    let first = parse_node(s, depth + 1)?;
+    *s = s.trim_start().strip_prefix(',').ok_or_else(|| DockError::Syntax("expected `,` between the sides of a split".to_string()))?;
+    let second = parse_node(s, depth + 1)?;
+    *s = s.trim_start().strip_prefix(')').ok_or_else(|| DockError::Syntax("expected `)` after a split".to_string()))?;
+    Ok(DockNode::split(orientation, ratio, first, second))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum DockError {
+    /// The text isn't a dock layout.
+    Syntax(String),
+    /// The layout doesn't hold each panel of the dock once.
+    Panels(String),
+}
+
+impl fmt::Display for DockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(e) => write!(f, "invalid dock layout: {e}"),
+            Self::Panels(e) => write!(f, "dock layout doesn't match the panels: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DockError {}
+
+/// Where the parts of a dock go, and their styles.
+/// A dock is a background holding panels, scroll views in groups of tabs split by draggable dividers.
+/// Dragging a tab onto a group joins its tabs, or splits it near its edges.
+/// Without tabs, the panels are split panes, only resized by their dividers.
+#[derive(Clone, Debug)]
+pub(super) struct DockLayout {
+    limits: [f32; 4],
+    /// The arrangement the dock starts with.
+    root: DockNode,
+    /// Width and height of the tabs in NDC, 0 high for split panes.
+    tab_size: [f32; 2],
+    /// Smallest width and height of a group of tabs, in pixels.
+    min_size: [f32; 2],
+    /// Whether dragging a divider past half the minimum size of a side collapses it.
+    collapsible: bool,
+    dock_style: u32,
+    tab_style: u32,
+    panel_style: u32,
+    divider_style: u32,
+    indicator_style: u32,
+}
+
+impl DockLayout {
+    /// A dock at `limits` in NDC arranged as `root`, numbering its panels.
+    /// Fails if `root` doesn't hold the panels from 0 to its largest one once each.
+    pub(super) fn new(limits: [f32; 4], root: DockNode) -> Result<Self, DockError> {
+        root.validate(root.panel_count())?;
+        Ok(Self {
+            limits,
+            root,
+            tab_size: [0.0; 2],
+            min_size: [48.0; 2],
+            collapsible: false,
+            dock_style: 0,
+            tab_style: 0,
+            panel_style: 0,
+            divider_style: 0,
+            indicator_style: 0,
+        })
+    }
+
+    /// Builder method giving the panels tabs of `size` in NDC, which can be dragged to rearrange them.
+    pub(super) fn with_tabs(mut self, size: [f32; 2]) -> Self {
+        self.tab_size = size;
+        self
+    }
+
+    /// Builder method setting the smallest width and height of a group of tabs, in pixels, 48 by default.
+    pub(super) fn with_min_size(mut self, size: [f32; 2]) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Builder method letting dividers collapse a side dragged past half its minimum size, until dragged back.
+    pub(super) fn collapsible(mut self) -> Self {
+        self.collapsible = true;
+        self
+    }
+
+    /// Builder method setting the styles of the background, of the tabs, of the panels, of the dividers,
+    /// and of the indicator showing where a dragged tab would go.
+    pub(super) fn with_styles(mut self, dock: u32, tab: u32, panel: u32, divider: u32, indicator: u32) -> Self {
+        self.dock_style = dock;
+        self.tab_style = tab;
+        self.panel_style = panel;
+        self.divider_style = divider;
+        self.indicator_style = indicator;
+        self
+    }
+
+    pub(super) fn panel_count(&self) -> usize {
+        self.root.panel_count()
+    }
+
+    /// The index of the tab of `panel`, for a dock at `index`.
+    pub(super) fn tab(&self, index: usize, panel: usize) -> usize {
+        index + 1 + 2 * panel
+    }
+
+    /// The index of the scroll view of `panel`, for a dock at `index`. The widgets of the panel are put in it with `in_scroll_view`.
+    pub(super) fn panel(&self, index: usize, panel: usize) -> usize {
+        index + 2 + 2 * panel
+    }
+
+    /// The index of the divider `divider`, for a dock at `index`.
+    pub(super) fn divider(&self, index: usize, divider: usize) -> usize {
+        index + 1 + 2 * self.panel_count() + divider
+    }
+
+    /// The index of the drop indicator, for a dock at `index`.
+    pub(super) fn indicator(&self, index: usize) -> usize {
+        index + 3 * self.panel_count()
+    }
+
+    /// The limits of the panels in the widgets, below the tabs.
+    /// The widgets in a panel are placed in them, and moved with it, keeping their size.
+    pub(super) fn panel_limits(&self) -> [f32; 4] {
+        let [left, right, bottom, top] = self.limits;
+        [left, right, bottom, top - self.tab_size[1]]
+    }
+
+    /// Move the dock by `offset` in NDC, after its widgets were moved.
+    pub(super) fn translate(&mut self, offset: [f32; 2]) {
+        let [left, right, bottom, top] = self.limits;
+        self.limits = [left + offset[0], right + offset[0], bottom + offset[1], top + offset[1]];
+    }
+
+    /// The widgets of the dock, to be put at `index` in the widgets: its background, the tab then the scroll view of each panel,
+    /// the dividers, then the drop indicator, an overlay. They are shown by `State::set_dock`.
+    pub(super) fn widgets(&self, index: usize) -> Vec<Widget> {
+        let count = self.panel_count();
+        let [left, _, _, top] = self.limits;
+        let mut widgets = vec![Widget::new(self.limits, WidgetType::Dock).with_style(self.dock_style)];
+        for _ in 0..count {
+            widgets.push(Widget::tab([left, left + self.tab_size[0], top - self.tab_size[1], top], DOCK_TAB_BARS, false)
+                .with_style(self.tab_style)
+                .hidden()
+                .in_scroll_view(index));
+            widgets.push(Widget::scroll_view(self.panel_limits()).with_style(self.panel_style).hidden().in_scroll_view(index));
+        }
+        widgets.extend((1..count).map(|_| {
+            Widget::new(self.limits, WidgetType::Divider).with_style(self.divider_style).hidden().in_scroll_view(index)
+        }));
+        widgets.push(Widget::overlay(self.limits).with_style(self.indicator_style));
+        widgets
+    }
+}
+
+/// A group of tabs placed by `Placement`.
+#[derive(Clone, Debug)]
+pub(super) struct Group {
+    pub(super) panels: Vec<usize>,
+    pub(super) selected: usize,
+    /// With the tabs, empty if collapsed.
+    pub(super) limits: [f32; 4],
+}
+
+/// A divider placed by `Placement`.
+#[derive(Clone, Debug)]
+pub(super) struct Divider {
+    /// The path to its split from the root, `true` going to the second node of each split.
+    path: Vec<bool>,
+    pub(super) orientation: Orientation,
+    pub(super) limits: [f32; 4],
+    /// The limits of its split.
+    span: [f32; 4],
+}
+
+/// Where the groups of tabs and the dividers of a dock are, in NDC.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Placement {
+    pub(super) groups: Vec<Group>,
+    /// The dividers in the order of their widgets.
+    pub(super) dividers: Vec<Divider>,
+}
+
+impl Placement {
+    /// Where dropping the tab of `panel` with the pointer at `cursor` in NDC puts it, with the limits of the drop indicator:
+    /// in the tabs of the group under the pointer, or beside it near its edges.
+    fn drop_target(&self, panel: usize, cursor: [f32; 2], tab_height: f32) -> Option<(DropTarget, [f32; 4])> {
+        let [x, y] = cursor;
+        let group = self.groups.iter().find(|g| !is_empty(g.limits) && x >= g.limits[0] && x < g.limits[1] && y >= g.limits[2] && y < g.limits[3])?;
+        let [left, right, bottom, top] = group.limits;
+        let [fx, fy] = [(x - left) / (right - left), (top - y) / (top - bottom)];
+        let side = if y >= top - tab_height {
+            None
+        } else {
+            [(fx, Side::Left), (1.0 - fx, Side::Right), (fy, Side::Above), (1.0 - fy, Side::Below)].into_iter()
+                .filter(|&(distance, _)| distance < DROP_EDGE)
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, side)| side)
+        };
+        // Dropping a tab in its own group changes nothing, as does splitting a group from its only tab
+        if group.panels.contains(&panel) && (side.is_none() || group.panels.len() == 1) {
+            return None;
+        }
+        let target = DropTarget { panel: *group.panels.iter().find(|&&p| p != panel)?, side };
+        let [center_x, center_y] = [(left + right) * 0.5, (bottom + top) * 0.5];
+        let indicator = match side {
+            None => group.limits,
+            Some(Side::Left) => [left, center_x, bottom, top],
+            Some(Side::Right) => [center_x, right, bottom, top],
+            Some(Side::Above) => [left, right, center_y, top],
+            Some(Side::Below) => [left, right, bottom, center_y],
+        };
+        Some((target, indicator))
+    }
+}
+
+/// Whether `limits` cover no pixel, collapsed.
+pub(super) fn is_empty(limits: [f32; 4]) -> bool {
+    limits[1] - limits[0] <= 1e-6 || limits[3] - limits[2] <= 1e-6
+}
+
+/// Where a dragged tab goes: in the tabs of `panel`, or on `side` of them, splitting their place.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct DropTarget {
+    panel: usize,
+    side: Option<Side>,
+}
+
+/// A dock, by the widget index of its background.
+pub(super) struct Dock {
+    pub(super) layout: DockLayout,
+    pub(super) root: DockNode,
+    /// Where the groups and dividers were last placed.
+    pub(super) placement: Placement,
+}
+
+impl Dock {
+    pub(super) fn new(layout: DockLayout) -> Self {
+        let root = layout.root.clone();
+        Self { layout, root, placement: Placement::default() }
+    }
+
+    /// Place the groups of tabs and the dividers, with pixels of `pixel` size in NDC.
+    pub(super) fn place(&mut self, pixel: [f32; 2]) {
+        self.placement = Placement::default();
+        self.root.place(self.layout.limits, [DIVIDER_WIDTH * pixel[0], DIVIDER_WIDTH * pixel[1]], &mut vec![], &mut self.placement);
+    }
+
+    /// The limits of the tab of the `i`th panel of `group`, and of the panels of the group, below the tabs.
+    pub(super) fn tab_and_panel(&self, group: &Group, i: usize) -> ([f32; 4], [f32; 4]) {
+        let [left, right, bottom, top] = group.limits;
+        let [width, height] = self.layout.tab_size;
+        let width = width.min((right - left) / group.panels.len() as f32);
+        let x = left + i as f32 * width;
+        ([x, x + width, top - height, top], [left, right, bottom.min(top - height), top - height])
+    }
+
+    /// The grab offset of the divider `divider` pressed with the pointer at `cursor` in NDC: how far the pointer is
+    /// from its left edge, or below its top edge.
+    pub(super) fn grab(&self, divider: usize, cursor: [f32; 2]) -> f32 {
+        let d = &self.placement.dividers[divider];
+        match d.orientation {
+            Orientation::Horizontal => cursor[0] - d.limits[0],
+            Orientation::Vertical => d.limits[3] - cursor[1],
+        }
+    }
+
+    /// Move the divider `divider` grabbed `grab` away, see `grab`, to the pointer at `cursor`, with pixels of `pixel` size in NDC.
+    /// Returns whether its ratio changed.
+    pub(super) fn drag_divider(&mut self, divider: usize, grab: f32, cursor: [f32; 2], pixel: [f32; 2]) -> bool {
+        let d = &self.placement.dividers[divider];
+        let [left, right, bottom, top] = d.span;
+        let (position, space, axis) = match d.orientation {
+            Orientation::Horizontal => (cursor[0] - grab - left, right - left - DIVIDER_WIDTH * pixel[0], 0),
+            Orientation::Vertical => (top - cursor[1] - grab, top - bottom - DIVIDER_WIDTH * pixel[1], 1),
+        };
+        let (min, gap, collapsible) = (self.layout.min_size[axis] * pixel[axis], DIVIDER_WIDTH * pixel[axis], self.layout.collapsible);
+        let DockNode::Split { orientation, ratio, first, second } = self.root.node_at(&d.path) else { unreachable!() };
+        if space <= 0.0 {
+            return false;
+        }
+        let [min_first, min_second] = [first, second].map(|side| side.min_length(*orientation, min, gap));
+        let length = if collapsible && position < min_first * 0.5 {
+            0.0
+        } else if collapsible && space - position < min_second * 0.5 {
+            space
+        } else {
+            position.min(space - min_second).max(min_first).min(space)
+        };
+        let new = length / space;
+        let changed = new != *ratio;
+        *ratio = new;
+        changed
+    }
+
+    /// Where dropping the tab of `panel` with the pointer at `cursor` in NDC puts it, with the limits of the drop indicator.
+    pub(super) fn drop_target(&self, panel: usize, cursor: [f32; 2]) -> Option<(DropTarget, [f32; 4])> {
+        self.placement.drop_target(panel, cursor, self.layout.tab_size[1])
+    }
+
+    /// The panel of the tab widget `index`, for a dock at `dock`.
+    pub(super) fn tab_panel(&self, dock: usize, index: usize) -> Option<usize> {
+        let offset = index.checked_sub(dock + 1)?;
+        (offset % 2 == 0 && offset / 2 < self.layout.panel_count()).then_some(offset / 2)
+    }
+
+    /// The divider of the widget `index` in use, for a dock at `dock`.
+    pub(super) fn divider_of(&self, dock: usize, index: usize) -> Option<usize> {
+        let divider = index.checked_sub(self.layout.divider(dock, 0))?;
+        (divider < self.placement.dividers.len()).then_some(divider)
+    }
+}
+
+/// The pointer dragging a divider or a tab of a dock.
+pub(super) enum DockDrag {
+    /// Dragging the divider `divider` of the dock `dock`, grabbed `grab` away from it, see `Dock::grab`.
+    Divider { dock: usize, divider: usize, grab: f32 },
+    /// Dragging the tab of `panel` of the dock `dock`, pressed at `start` in pixels,
+    /// `moving` once past `TOUCH_SLOP`, with where it would be dropped.
+    Tab { dock: usize, panel: usize, start: [f64; 2], moving: bool, target: Option<DropTarget> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for text in ["[0]", "[0*,1]", "[0,1*,2]", "h(0.3,[0*,1],v(0.5,[2],[3]))", "v(0,h(1,[1],[0]),[2])"] {
+            let node: DockNode = text.parse().unwrap();
+            assert_eq!(node.to_string(), text);
+        }
+        let node = DockNode::split(Orientation::Vertical, 0.25, DockNode::tabs(&[2, 0]), DockNode::tabs(&[1]));
+        let parsed: DockNode = node.to_string().parse().unwrap();
+        assert_eq!(parsed.to_string(), node.to_string());
+        // Spaces are allowed between the parts, and the first tab is selected by default
+        let spaced: DockNode = " h( 0.5 , [ 0 , 1 ] , [2 *] ) ".parse().unwrap();
+        assert_eq!(spaced.to_string(), "h(0.5,[0*,1],[2])");
+    }
+
+    #[test]
+    fn bad_input() {
+        for text in ["", "[", "[]", "[a]", "[0", "x(0.5,[0],[1])", "h[0]", "h(a,[0],[1])", "h(0.5,[0])", "h(0.5,[0],[1]", "[0] [1]"] {
+            assert!(matches!(text.parse::<DockNode>(), Err(DockError::Syntax(_))), "{text:?} parsed");
+        }
+        let deep = "h(0.5,".repeat(100_000) + "[0]";
+        assert!(matches!(deep.parse::<DockNode>(), Err(DockError::Syntax(_))));
+    }
+
+    #[test]
+    fn invalid_layouts() {
+        let limits = [-1.0, 1.0, -1.0, 1.0];
+        for text in ["[0,2]", "[1]", "h(0.5,[0],[0])", "h(1.5,[0],[1])"] {
+            assert!(DockLayout::new(limits, text.parse().unwrap()).is_err(), "{text:?} accepted");
+        }
+        assert!(DockLayout::new(limits, "h(0.5,[1],[0,2*])".parse().unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{toggle::CheckState, tree::{DropPosition, NodeId}};
+
+/// Something the user did to a widget, collected by `State::take_events`.
+/// Changes made by the application through `State` don't produce events.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum WidgetEvent {
+    /// The user changed the state of the checkbox, radio button or toggle `index`.
+    /// Checking a radio button also produces an event for the one of its group it unchecked.
+    CheckChanged { index: usize, state: CheckState },
+    /// The user moved the thumb of the slider `index`.
+    SliderMoved { index: usize, value: f32 },
+    /// The user moved a thumb of the range slider `index`.
+    RangeMoved { index: usize, low: f32, high: f32 },
+    /// The user scrolled the scroll view `index` to `offset`, in NDC, by wheel, dragging or inertia.
+    Scrolled { index: usize, offset: [f64; 2] },
+    /// The user selected the data row `row` of the table `index` by pressing one of its cells.
+    RowSelected { index: usize, row: usize },
+    /// The user expanded or collapsed `node` of the tree view `index`.
+    Expanded { index: usize, node: NodeId, expanded: bool },
+    /// The user changed the selection of the tree view `index`, see `State::selected_nodes`.
+    SelectionChanged { index: usize },
+    /// The user dragged the selected nodes of the tree view `index` to `position` relative to `target`.
+    /// Reordering the nodes is up to the application, followed by `State::reload_tree`.
+    Dropped { index: usize, target: NodeId, position: DropPosition },
+    /// The user selected the tab `index`, showing its page.
+    TabSelected { index: usize },
+    /// The user activated the menu item `index`, by clicking it, with the keyboard or with its accelerator.
+    MenuActivated { index: usize },
+    /// The user chose the option `option` of the combo box `index`.
+    OptionSelected { index: usize, option: usize },
+    /// The user clicked the button or custom widget `index`, other than the OK and cancel buttons of stock dialogs.
+    Clicked { index: usize },
+    /// The dialog `index` closed, after calling its callback.
+    DialogClosed { index: usize },
+    /// The user changed the color of the color picker `index`, as RGBA from 0 to 1.
+    ColorChanged { index: usize, color: [f32; 4] },
+    /// The pointer moved onto a point of the chart `index`, given as the index of its series and its index in it,
+    /// or off its points with `None`.
+    PointHovered { index: usize, point: Option<(usize, usize)> },
+    /// The pointer moved onto the hit region `region` of the canvas `index`, or off its hit regions with `None`.
+    RegionHovered { index: usize, region: Option<u32> },
+    /// The user clicked the canvas `index`, on its hit region `region` if any.
+    CanvasClicked { index: usize, region: Option<u32> },
+    /// The user rearranged the dock `index` by dragging a divider or a tab, see `State::dock_layout`.
+    /// Selecting a tab of a dock produces `TabSelected` instead.
+    DockChanged { index: usize },
+}
@@ -0,0 +1,345 @@
+use crate::slider::Orientation;
+
+/// Where the children of a flex container go across it.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum Align {
+    Start,
+    Center,
+    End,
+    /// As long as the container across it, for children without a size across it.
+    Stretch,
+}
+
+/// How the space left along a flex container is shared around its children.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum Justify {
+    Start,
+    Center,
+    End,
+    /// Between the children, none before the first and after the last.
+    SpaceBetween,
+    /// Around each child, half as much before the first and after the last.
+    SpaceAround,
+    /// Before, between and after the children.
+    SpaceEvenly,
+}
+
+/// A box of a flexible layout, placing a widget or only its children, in a row or a column.
+///
+/// Sizes, gaps, paddings and margins are in pixels, so that the layout follows the window when laid out again.
+/// Paddings and margins are left, top, right, bottom.
+/// Children without a size get the size of their own children, and share the space left along their container
+/// by their grow factors, or give up space it lacks by their shrink factors weighted by their size.
+#[derive(Clone, Debug)]
+pub(super) struct FlexNode {
+    /// The widget placed in the box, with its padding but not its margins.
+    widget: Option<usize>,
+    direction: Orientation,
+    size: [Option<f32>; 2],
+    grow: f32,
+    shrink: f32,
+    align_items: Align,
+    align_self: Option<Align>,
+    justify: Justify,
+    gap: f32,
+    padding: [f32; 4],
+    margin: [f32; 4],
+    children: Vec<FlexNode>,
+}
+
+impl FlexNode {
+    fn new(direction: Orientation) -> Self {
+        Self {
+            widget: None,
+            direction,
+            size: [None; 2],
+            grow: 0.0,
+            shrink: 1.0,
+            align_items: Align::Stretch,
+            align_self: None,
+            justify: Justify::Start,
+            gap: 0.0,
+            padding: [0.0; 4],
+            margin: [0.0; 4],
+            children: vec![],
+        }
+    }
+
+    /// A box laying out its children from left to right.
+    pub(super) fn row() -> Self {
+        Self::new(Orientation::Horizontal)
+    }
+
+    /// A box laying out its children from top to bottom.
+    pub(super) fn column() -> Self {
+        Self::new(Orientation::Vertical)
+    }
+
+    /// A box placing the widget `index`, laying out its children in a row.
+    pub(super) fn widget(index: usize) -> Self {
+        Self::row().with_widget(index)
+    }
+
+    /// Builder method placing the widget `index` in the box.
+    pub(super) fn with_widget(mut self, index: usize) -> Self {
+        self.widget = Some(index);
+        self
+    }
+
+    /// Builder method setting the width of the box, instead of the width of its children.
+    pub(super) fn with_width(mut self, width: f32) -> Self {
+        self.size[0] = Some(width);
+        self
+    }
+
+    /// Builder method setting the height of the box, instead of the height of its children.
+    pub(super) fn with_height(mut self, height: f32) -> Self {
+        self.size[1] = Some(height);
+        self
+    }
+
+    /// Builder method setting how much of the space left along its container the box takes, relative to the other children, 0 by default.
+    pub(super) fn with_grow(mut self, grow: f32) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    /// Builder method setting how much of the space lacking along its container the box gives up,
+    /// relative to the other children and weighted by its size, 1 by default.
+    #[allow(unused)]
+    pub(super) fn with_shrink(mut self, shrink: f32) -> Self {
+        self.shrink = shrink;
+        self
+    }
+
+    /// Builder method setting where the children go across the box, `Stretch` by default.
+    pub(super) fn with_align_items(mut self, align: Align) -> Self {
+        self.align_items = align;
+        self
+    }
+
+    /// Builder method setting where the box goes across its container, instead of its container's `align_items`.
+    #[allow(unused)]
+    pub(super) fn with_align_self(mut self, align: Align) -> Self {
+        self.align_self = Some(align);
+        self
+    }
+
+    /// Builder method setting how the space left along the box is shared around its children, `Start` by default.
+    #[allow(unused)]
+    pub(super) fn with_justify(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    /// Builder method setting the space between the children.
+    pub(super) fn with_gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Builder method setting the space between the edges of the box and its children.
+    pub(super) fn with_padding(mut self, padding: [f32; 4]) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Builder method setting the space around the box in its container.
+    #[allow(unused)]
+    pub(super) fn with_margin(mut self, margin: [f32; 4]) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Builder method adding `children` after the children of the box.
+    pub(super) fn with_children(mut self, children: impl IntoIterator<Item = FlexNode>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    /// The axis the children are laid out along, 0 for rows and 1 for columns.
+    fn axis(&self) -> usize {
+        match self.direction {
+            Orientation::Horizontal => 0,
+            Orientation::Vertical => 1,
+        }
+    }
+
+    /// The width and height of the box, from its size or from its children and padding.
+    fn measure(&self) -> [f32; 2] {
+        let axis = self.axis();
+        let mut content = [0.0; 2];
+        for (i, child) in self.children.iter().enumerate() {
+            let outer = child.outer_size();
+            content[axis] += outer[axis] + if i > 0 { self.gap } else { 0.0 };
+            content[1 - axis] = f32::max(content[1 - axis], outer[1 - axis]);
+        }
+        let [left, top, right, bottom] = self.padding;
+        let natural = [content[0] + left + right, content[1] + top + bottom];
+        [0, 1].map(|a| self.size[a].unwrap_or(natural[a]))
+    }
+
+    /// The width and height of the box with its margins.
+    fn outer_size(&self) -> [f32; 2] {
+        let [width, height] = self.measure();
+        let [left, top, right, bottom] = self.margin;
+        [width + left + right, height + top + bottom]
+    }
+
+    /// The limits of the widgets of the tree in a window of `size`, as (index, [left, top, right, bottom]) in pixels,
+    /// containers before their children.
+    pub(super) fn layout(&self, size: [f32; 2]) -> Vec<(usize, [f32; 4])> {
+        let [left, top, right, bottom] = self.margin;
+        let mut placed = vec![];
+        self.place([left, top, size[0] - right, size[1] - bottom], &mut placed);
+        placed
+    }
+
+    /// Place the box at `rect`, left, top, right, bottom in pixels, then its children in it.
+    fn place(&self, rect: [f32; 4], placed: &mut Vec<(usize, [f32; 4])>) {
+        if let Some(index) = self.widget {
+            placed.push((index, rect));
+        }
+        if self.children.is_empty() {
+            return;
+        }
+        let (axis, cross) = (self.axis(), 1 - self.axis());
+        let [left, top, right, bottom] = self.padding;
+        let content = [rect[0] + left, rect[1] + top, rect[2] - right, rect[3] - bottom];
+        let length = [content[2] - content[0], content[3] - content[1]];
+
+        // Sizes along the box, from the size of the children then grown or shrunk to fill it
+        let bases: Vec<f32> = self.children.iter().map(|c| c.measure()[axis]).collect();
+        let margins: f32 = self.children.iter().map(|c| c.margin[axis] + c.margin[axis + 2]).sum();
+        let gaps = self.gap * (self.children.len() - 1) as f32;
+        let free = length[axis] - bases.iter().sum::<f32>() - margins - gaps;
+        let grow: f32 = self.children.iter().map(|c| c.grow).sum();
+        let shrink: f32 = self.children.iter().zip(&bases).map(|(c, base)| c.shrink * base).sum();
+        let sizes: Vec<f32> = self.children.iter().zip(&bases).map(|(c, &base)| {
+            if free > 0.0 && grow > 0.0 {
+                base + free * c.grow / grow
+            } else if free < 0.0 && shrink > 0.0 {
+                (base + free * c.shrink * base / shrink).max(0.0)
+            } else {
+                base
+            }
+        }).collect();
+
+        // The space still left is shared around the children
+        let left_over = (length[axis] - sizes.iter().sum::<f32>() - margins - gaps).max(0.0);
+        let n = self.children.len() as f32;
+        let (offset, spacing) = match self.justify {
+            Justify::Start => (0.0, 0.0),
+            Justify::Center => (left_over * 0.5, 0.0),
+            Justify::End => (left_over, 0.0),
+            Justify::SpaceBetween if n > 1.0 => (0.0, left_over / (n - 1.0)),
+            Justify::SpaceBetween => (0.0, 0.0),
+            Justify::SpaceAround => (left_over / n * 0.5, left_over / n),
+            Justify::SpaceEvenly => (left_over / (n + 1.0), left_over / (n + 1.0)),
+        };
+
+        let mut position = content[axis] + offset;
+        for (child, size) in self.children.iter().zip(sizes) {
+            position += child.margin[axis];
+            let mut rect = [0.0; 4];
+            rect[axis] = position;
+            rect[axis + 2] = position + size;
+            position += size + child.margin[axis + 2] + self.gap + spacing;
+
+            let available = length[cross] - child.margin[cross] - child.margin[cross + 2];
+            let align = child.align_self.unwrap_or(self.align_items);
+            let size = match child.size[cross] {
+                None if align == Align::Stretch => available.max(0.0),
+                _ => child.measure()[cross],
+            };
+            let start = content[cross] + child.margin[cross] + match align {
+                Align::Start | Align::Stretch => 0.0,
+                Align::Center => (available - size) * 0.5,
+                Align::End => available - size,
+            };
+            rect[cross] = start;
+            rect[cross + 2] = start + size;
+            child.place(rect, placed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The rects of the widgets placed by `node` in a window of `size`, by widget index.
+    fn rects(node: FlexNode, size: [f32; 2]) -> Vec<[f32; 4]> {
+        let mut placed = node.layout(size);
+        placed.sort_by_key(|&(index, _)| index);
+        placed.into_iter().map(|(_, rect)| rect).collect()
+    }
+
+    fn assert_rects(actual: &[[f32; 4]], expected: &[[f32; 4]]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(a.iter().zip(e).all(|(a, e)| (a - e).abs() < 1e-3), "{actual:?} isn't {expected:?}");
+        }
+    }
+
+    #[test]
+    fn grow() {
+        let row = FlexNode::row().with_children([
+            FlexNode::widget(0).with_width(50.0).with_height(10.0).with_grow(1.0),
+            FlexNode::widget(1).with_width(50.0).with_height(10.0).with_grow(2.0),
+            FlexNode::widget(2).with_width(20.0).with_height(10.0),
+        ]);
+        // 180 left, a third to the first and two to the second, the last keeping its width
+        assert_rects(&rects(row, [300.0, 10.0]), &[[0.0, 0.0, 110.0, 10.0], [110.0, 0.0, 280.0, 10.0], [280.0, 0.0, 300.0, 10.0]]);
+    }
+
+    #[test]
+    fn shrink() {
+        let row = FlexNode::row().with_children([
+            FlexNode::widget(0).with_width(100.0),
+            FlexNode::widget(1).with_width(50.0),
+            FlexNode::widget(2).with_width(50.0).with_shrink(0.0),
+        ]);
+        // 100 lacking, given up by the first two in proportion to their widths
+        assert_rects(&rects(row, [100.0, 10.0]), &[[0.0, 0.0, 33.333, 10.0], [33.333, 0.0, 50.0, 10.0], [50.0, 0.0, 100.0, 10.0]]);
+    }
+
+    #[test]
+    fn justify() {
+        let cases = [
+            (Justify::Start, [0.0, 20.0]),
+            (Justify::Center, [35.0, 55.0]),
+            (Justify::End, [70.0, 90.0]),
+            (Justify::SpaceBetween, [0.0, 90.0]),
+            (Justify::SpaceAround, [17.5, 72.5]),
+            (Justify::SpaceEvenly, [23.333, 66.667]),
+        ];
+        for (justify, [first, second]) in cases {
+            let row = FlexNode::row().with_justify(justify).with_gap(10.0).with_children([
+                FlexNode::widget(0).with_width(10.0),
+                FlexNode::widget(1).with_width(10.0),
+            ]);
+            // 70 left after the gap
+            assert_rects(&rects(row, [100.0, 10.0]), &[[first, 0.0, first + 10.0, 10.0], [second, 0.0, second + 10.0, 10.0]]);
+        }
+    }
+
+    #[test]
+    fn align() {
+        let column = FlexNode::column().with_padding([5.0, 0.0, 5.0, 0.0]).with_children([
+            FlexNode::widget(0).with_height(10.0),
+            FlexNode::widget(1).with_width(20.0).with_height(10.0),
+            FlexNode::widget(2).with_height(10.0).with_align_self(Align::Center).with_children([FlexNode::row().with_width(30.0)]),
+            FlexNode::widget(3).with_width(20.0).with_height(10.0).with_align_self(Align::End).with_margin([0.0, 0.0, 10.0, 0.0]),
+        ]);
+        // Stretched across the padded column unless they have a width, centered by their children's width
+        assert_rects(&rects(column, [100.0, 100.0]), &[
+            [5.0, 0.0, 95.0, 10.0],
+            [5.0, 10.0, 25.0, 20.0],
+            [35.0, 20.0, 65.0, 30.0],
+            [65.0, 30.0, 85.0, 40.0],
+        ]);
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::HashMap, fmt};
+
+use ab_glyph::{Font as _, FontVec, GlyphId, InvalidFont, ScaleFont as _};
+
+use crate::atlas::Atlas;
+
+#[derive(Debug)]
+pub(super) enum FontError {
+    /// The bytes aren't a TrueType or OpenType font.
+    Invalid(InvalidFont),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "could not parse font: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// A character rasterized at a size, placed from the pen position on the baseline.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct Glyph {
+    /// Index of its coverage in the atlas, white with the coverage as alpha, if it has any pixels.
+    pub(super) image: Option<u32>,
+    /// Left, top, right, bottom of the image in pixels from the pen position.
+    pub(super) bounds: [f32; 4],
+    /// Horizontal advance in pixels.
+    pub(super) advance: f32,
+}
+
+/// The fonts text inputs are drawn with, each character taken from the first one that has it,
+/// and the glyphs they were rasterized into the atlas as, by character and size in pixels.
+#[derive(Default)]
+pub(super) struct Fonts {
+    fonts: Vec<FontVec>,
+    glyphs: HashMap<(char, u32), Glyph>,
+}
+
+impl Fonts {
+    /// Parse a TrueType or OpenType font, and fall back on it for the characters the fonts added before don't have.
+    pub(super) fn add(&mut self, bytes: Vec<u8>) -> Result<(), FontError> {
+        let font = FontVec::try_from_vec(bytes).map_err(FontError::Invalid)?;
+        // Characters drawn as missing may be in this one
+        let fonts = &self.fonts;
+        self.glyphs.retain(|&(c, _), _| fonts.iter().any(|font| font.glyph_id(c) != GlyphId(0)));
+        self.fonts.push(font);
+        Ok(())
+    }
+
+    /// Ascent and descent (negative) of the first font at `size` pixels, placing the baseline.
+    pub(super) fn v_metrics(&self, size: u32) -> [f32; 2] {
+        self.fonts.first().map_or([size as f32, 0.0], |font| {
+            let scaled = font.as_scaled(size as f32);
+            [scaled.ascent(), scaled.descent()]
+        })
+    }
+
+    /// The glyph of `c` at `size` pixels, rasterized into `atlas` the first time.
+    /// Characters none of the fonts have are drawn as the missing glyph of the first one.
+    /// Returns `None` without fonts, or when the atlas is full.
+    pub(super) fn glyph(&mut self, atlas: &mut Atlas, c: char, size: u32) -> Option<Glyph> {
+        if let Some(glyph) = self.glyphs.get(&(c, size)) {
+            return Some(*glyph);
+        }
+        let font = self.fonts.iter().find(|font| font.glyph_id(c) != GlyphId(0)).or(self.fonts.first())?;
+        let id = font.glyph_id(c);
+        let advance = font.as_scaled(size as f32).h_advance(id);
+        let glyph = match font.outline_glyph(id.with_scale(size as f32)) {
+            Some(outline) => {
+                let bounds = outline.px_bounds();
+                let mut image = image::RgbaImage::new(bounds.width() as u32, bounds.height() as u32);
+                outline.draw(|x, y, coverage| image.put_pixel(x, y, image::Rgba([255, 255, 255, (coverage * 255.0).round() as u8])));
+                let image = match atlas.add_rgba(image) {
+                    Ok(image) => image,
+                    Err(e) => {
+                        log::warn!("Glyph of {c:?} not drawn: {e}");
+                        return None;
+                    }
+                };
+                Glyph { image: Some(image), bounds: [bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y], advance }
+            }
+            None => Glyph { image: None, bounds: [0.0; 4], advance },
+        };
+        self.glyphs.insert((c, size), glyph);
+        Some(glyph)
+    }
+}
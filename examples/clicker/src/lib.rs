@@ -1,11 +1,57 @@
+mod atlas;
+mod backdrop;
+mod canvas;
+mod chart;
+mod clipboard;
+mod color;
+mod custom;
+mod dialog;
+mod dock;
+mod event;
+mod flex;
+mod font;
 mod init;
+mod list;
+mod menu;
+mod overlay;
+mod path;
+mod scroll;
+mod slider;
 mod state;
+mod style;
+mod svg;
+mod tab;
+mod table;
+mod text;
+mod toggle;
+mod tree;
+mod viewport;
+use std::cmp::Ordering;
+
 use state::State;
 use winit::{
-    event::*, event_loop::{EventLoop, EventLoopWindowTarget}, keyboard::{Key, NamedKey}
+    event::*, event_loop::{EventLoop, EventLoopWindowTarget}, keyboard::{Key, ModifiersState, NamedKey}
 };
 
+use atlas::{Atlas, ImageFilter, ImageFit};
+use canvas::Canvas;
+use chart::{ChartKind, Series};
+use color::{ColorPickerLayout, PickerShape};
+use custom::{CustomWidget, CustomWidgets, ParamKind, ParamValue};
+use dialog::{DialogKind, DialogLayout};
+use dock::{DockLayout, DockNode};
+use flex::{Align, FlexNode};
+use list::{DataSource, ItemLayout};
+use menu::Accelerator;
+use overlay::Side;
+use path::{FillRule, LineCap, LineJoin, Path, Stroke};
+use slider::Orientation;
 use state::{Widget, WidgetType};
+use style::{Gradient, Skin, Style, StyleSet};
+use table::{Column, TableLayout};
+use tree::{NodeId, TreeSource};
+use toggle::CheckState;
+use viewport::{Viewport, ViewportEvent};
 
 #[cfg(target_arch = "wasm32")]
 #[allow(unused)]
@@ -16,14 +62,347 @@ pub fn run() {
     init::init_logger();
     let (event_loop, window) = init::init_window();
 
-    let widgets: Vec<Widget> = (0..10000).map(|n| {
-        Widget::new([(n/100) as f32/50.0-1.0, (n/100) as f32/50.0-0.98, (n%100) as f32/50.0-1.0, (n%100) as f32/50.0-0.98], WidgetType::EllipticButton)
-    }).collect();
+    let mut atlas = Atlas::new(2048);
+    let checker = atlas.add_rgba(image::RgbaImage::from_fn(16, 16, |x, y| {
+        if (x / 4 + y / 4) % 2 == 0 { image::Rgba([255, 255, 255, 255]) } else { image::Rgba([40, 40, 40, 255]) }
+    })).unwrap();
+    let frame = atlas.add_rgba(image::RgbaImage::from_fn(24, 24, |x, y| {
+        if x < 8 || y < 8 || x >= 16 || y >= 16 { image::Rgba([200, 150, 50, 255]) } else { image::Rgba([60, 40, 20, 200]) }
+    })).unwrap();
+    atlas.set_insets(frame, [8.0; 4]);
+    // Bevelled button skin, lit from the top left, or from the bottom right when pressed
+    let bevel = |light: [u8; 3], dark: [u8; 3], face: [u8; 3]| image::RgbaImage::from_fn(12, 12, move |x, y| {
+        let c = if x < 4 && y < 12 - x || y < 4 && x < 12 - y { light } else if x >= 8 || y >= 8 { dark } else { face };
+        image::Rgba([c[0], c[1], c[2], 255])
+    });
+    let skin = Skin::new(atlas.add_rgba(bevel([220, 220, 220], [80, 80, 80], [160, 160, 160])).unwrap())
+        .with_hovered(atlas.add_rgba(bevel([240, 240, 240], [90, 90, 90], [185, 185, 185])).unwrap())
+        .with_pressed(atlas.add_rgba(bevel([80, 80, 80], [220, 220, 220], [140, 140, 140])).unwrap())
+        .with_disabled(atlas.add_rgba(bevel([130, 130, 130], [110, 110, 110], [120, 120, 120])).unwrap())
+        .with_insets([4.0; 4]);
+
+    // A star filled with the even-odd rule, leaving a hole in the middle, and a heart
+    let star = (0..5).fold(Path::new([-1.0, -1.0, 2.0, 2.0]), |path, i| {
+        let angle = (i * 2 % 5) as f32 * std::f32::consts::TAU / 5.0 - std::f32::consts::FRAC_PI_2;
+        let to = [0.9 * angle.cos(), 0.9 * angle.sin()];
+        if i == 0 { path.move_to(to) } else { path.line_to(to) }
+    }).close().with_fill(Some(FillRule::EvenOdd)).with_stroke(Some(Stroke::new(0.06).with_join(LineJoin::Round)));
+    let heart = Path::new([0.0, 0.0, 24.0, 24.0])
+        .move_to([12.0, 21.0])
+        .cubic_to([4.0, 15.0], [1.0, 11.0], [3.0, 6.5])
+        .arc_to([4.5, 4.5], 0.0, false, true, [12.0, 6.0])
+        .arc_to([4.5, 4.5], 0.0, false, true, [21.0, 6.5])
+        .quad_to([23.0, 13.0], [12.0, 21.0])
+        .close();
+    let check = Path::new([0.0, 0.0, 24.0, 24.0])
+        .move_to([4.0, 12.0]).line_to([9.0, 17.0]).line_to([20.0, 6.0])
+        .with_fill(None).with_stroke(Some(Stroke::new(3.0).with_cap(LineCap::Square).with_join(LineJoin::Bevel)));
+    let mut paths = vec![star, heart, check];
+    // Feather's "settings" icon
+    let icon = svg::parse(r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round"><circle cx="12" cy="12" r="3"></circle><path d="M19.4 15a1.65 1.65 0 0 0 .33 1.82l.06.06a2 2 0 0 1 0 2.83 2 2 0 0 1-2.83 0l-.06-.06a1.65 1.65 0 0 0-1.82-.33 1.65 1.65 0 0 0-1 1.51V21a2 2 0 0 1-2 2 2 2 0 0 1-2-2v-.09A1.65 1.65 0 0 0 9 19.4a1.65 1.65 0 0 0-1.82.33l-.06.06a2 2 0 0 1-2.83 0 2 2 0 0 1 0-2.83l.06-.06a1.65 1.65 0 0 0 .33-1.82 1.65 1.65 0 0 0-1.51-1H3a2 2 0 0 1-2-2 2 2 0 0 1 2-2h.09A1.65 1.65 0 0 0 4.6 9a1.65 1.65 0 0 0-.33-1.82l-.06-.06a2 2 0 0 1 0-2.83 2 2 0 0 1 2.83 0l.06.06a1.65 1.65 0 0 0 1.82.33H9a1.65 1.65 0 0 0 1-1.51V3a2 2 0 0 1 2-2 2 2 0 0 1 2 2v.09a1.65 1.65 0 0 0 1 1.51 1.65 1.65 0 0 0 1.82-.33l.06-.06a2 2 0 0 1 2.83 0 2 2 0 0 1 0 2.83l-.06.06a1.65 1.65 0 0 0-.33 1.82V9a1.65 1.65 0 0 0 1.51 1H21a2 2 0 0 1 2 2 2 2 0 0 1-2 2h-.09a1.65 1.65 0 0 0-1.51 1z"></path></svg>"#).unwrap();
+    let icon_paths = paths.len() as u32..(paths.len() + icon.len()) as u32;
+    paths.extend(icon);
+
+    // Placed by the flexible layout below, in 100 columns of 100 from the bottom up
+    let mut widgets: Vec<Widget> = (0..10000).map(|_| Widget::new([0.0; 4], WidgetType::EllipticButton)).collect();
+    widgets.push(Widget::new([-0.9, 0.9, 0.8, 0.95], WidgetType::TextInput));
+    widgets.push(Widget::new([-0.9, -0.1, -0.9, -0.5], WidgetType::Rect).with_style(1));
+    widgets.push(Widget::new([0.1, 0.9, -0.9, -0.5], WidgetType::RoundedRect).with_style(2));
+    widgets.push(Widget::new([0.1, 0.9, -0.4, -0.2], WidgetType::RoundedRect).with_style(2).disabled());
+    widgets.push(Widget::image([-0.9, -0.6, 0.3, 0.7], checker, ImageFit::Cover, ImageFilter::Nearest).with_style(3));
+    widgets.push(Widget::image([-0.9, -0.6, -0.1, 0.2], checker, ImageFit::Contain, ImageFilter::Linear));
+    widgets.push(Widget::image([0.6, 0.9, 0.3, 0.7], checker, ImageFit::Fill, ImageFilter::Linear));
+    widgets.push(Widget::image([0.6, 0.9, -0.1, 0.2], frame, ImageFit::NineSlice, ImageFilter::Nearest));
+    widgets.push(Widget::new([-0.4, 0.4, 0.55, 0.75], WidgetType::Rect).with_style(4));
+    widgets.push(Widget::path([-0.55, -0.42, 0.5, 0.75], 0).with_style(5));
+    widgets.push(Widget::path([0.42, 0.55, 0.5, 0.75], 1).with_style(5));
+    widgets.push(Widget::path([0.42, 0.55, 0.25, 0.45], 2).with_style(5));
+    // The shapes of an SVG icon are drawn on top of each other
+    widgets.extend(icon_paths.map(|i| Widget::path([-0.55, -0.42, 0.25, 0.45], i).with_style(5)));
+    widgets.push(Widget::checkbox([-0.35, -0.3, -0.45, -0.38], CheckState::Unchecked).with_style(6));
+    widgets.push(Widget::checkbox([-0.28, -0.23, -0.45, -0.38], CheckState::Checked).with_style(6));
+    widgets.push(Widget::checkbox([-0.21, -0.16, -0.45, -0.38], CheckState::Indeterminate).with_style(6));
+    widgets.extend((0..3).map(|i| {
+        let x = -0.1 + i as f32 * 0.07;
+        Widget::radio([x, x + 0.05, -0.45, -0.38], 0, i == 0).with_style(6)
+    }));
+    widgets.push(Widget::toggle([0.15, 0.25, -0.45, -0.38], false).with_style(6));
+    let sliders = widgets.len();
+    widgets.push(Widget::slider([0.3, 0.6, -0.47, -0.41], Orientation::Horizontal).with_ticks(11).with_style(6));
+    widgets.push(Widget::range_slider([0.3, 0.6, -0.39, -0.33], Orientation::Horizontal).with_style(6));
+    widgets.push(Widget::slider([0.63, 0.66, -0.95, -0.55], Orientation::Vertical).with_style(6));
+    let progress = widgets.len();
+    widgets.push(Widget::progress_bar([0.3, 0.6, -0.53, -0.5], Some(0.0)).with_style(6));
+    widgets.push(Widget::progress_bar([0.3, 0.6, -0.58, -0.55], None).with_style(6));
+    widgets.push(Widget::spinner([0.68, 0.74, -0.6, -0.5]).with_style(6));
+    let glass = widgets.len();
+    widgets.push(Widget::new([-0.5, 0.5, -0.3, 0.5], WidgetType::RoundedRect).with_style(3));
+    // Rows wider and taller than their scroll view, the last one being a scroll view of checkboxes
+    let scroll = widgets.len();
+    widgets.push(Widget::scroll_view([0.72, 0.98, -0.95, -0.62]).with_style(6));
+    widgets.extend((0..8).map(|i| {
+        let top = -0.63 - i as f32 * 0.12;
+        Widget::new([0.73, 1.1, top - 0.1, top], WidgetType::RoundedRect).with_style(2).in_scroll_view(scroll)
+    }));
+    let nested = widgets.len();
+    widgets.push(Widget::scroll_view([0.73, 0.95, -1.71, -1.59]).with_style(6).in_scroll_view(scroll));
+    widgets.extend((0..8).map(|i| {
+        let x = 0.74 + i as f32 * 0.07;
+        Widget::checkbox([x, x + 0.05, -1.66, -1.6], CheckState::Unchecked).with_style(6).in_scroll_view(nested)
+    }));
+    let last = widgets.len() - 1;
+    let log = ItemLayout::list([0.72, 0.98, -0.55, -0.12], 0.04);
+    let log_view = widgets.len();
+    widgets.extend(log.widgets(log_view));
+    let swatches = ItemLayout::grid([-0.98, -0.62, -0.95, -0.55], [0.06, 0.06], 8);
+    let swatches_view = widgets.len();
+    widgets.extend(swatches.widgets(swatches_view));
+    // Wider than it is, scrolling horizontally
+    let table_layout = TableLayout::new([-0.55, 0.05, -0.28, 0.2], 0.06, 0.05, &[0.25, 0.12, 0.35]).with_styles(6, 7, 8);
+    let table = widgets.len();
+    widgets.extend(table_layout.widgets(table));
+    let tree_layout = ItemLayout::tree([0.08, 0.4, -0.28, 0.2], 0.05);
+    let tree = widgets.len();
+    widgets.extend(tree_layout.widgets(tree));
+    // A menu bar along the top, a combo box and tabs showing one of four pages in the glass rectangle
+    let menu_bar = widgets.len();
+    widgets.push(Widget::new([-1.0, 1.0, 0.955, 1.0], WidgetType::Rect).with_style(9));
+    let file = widgets.len();
+    widgets.push(Widget::menu_item([-0.99, -0.87, 0.96, 0.995]).with_style(10));
+    widgets.push(Widget::menu_item([-0.86, -0.74, 0.96, 0.995]).with_style(10));
+    let combo = widgets.len();
+    widgets.push(Widget::combo_box([0.05, 0.3, 0.42, 0.48]).with_style(6));
+    // Showing a popover below it
+    let more = widgets.len();
+    widgets.push(Widget::new([0.33, 0.45, 0.42, 0.48], WidgetType::RoundedRect).with_style(2));
+    // Asking to confirm in a dialog
+    let confirm_button = widgets.len();
+    widgets.push(Widget::new([0.48, 0.6, 0.42, 0.48], WidgetType::Button).with_style(13));
+    // A color well, picking its color in a popover
+    let well = widgets.len();
+    widgets.push(Widget::swatch([0.63, 0.7, 0.42, 0.48], [1.0; 4]).with_style(14));
+    // A custom widget, following the first slider
+    let mut custom = CustomWidgets::default();
+    let gauge_type = custom.register(Gauge).unwrap();
+    let gauge = widgets.len();
+    widgets.push(Widget::custom([0.91, 0.99, 0.55, 0.67], &custom, gauge_type, &[ParamValue::Float(0.3), ParamValue::Color([0.3, 0.8, 0.4, 1.0])]).with_style(18));
+    // Showing a chart in a popover
+    let plot_button = widgets.len();
+    widgets.push(Widget::new([0.73, 0.85, 0.42, 0.48], WidgetType::RoundedRect).with_style(2));
+    // A canvas drawing bars that light up under the pointer
+    let canvas = widgets.len();
+    widgets.push(Widget::canvas([-0.98, -0.56, -0.48, -0.13]).with_style(19));
+    // A dock of four panels, two of them in tabs, the last one holding more than fits
+    let arrangement = DockNode::split(Orientation::Horizontal, 0.5, DockNode::tabs(&[0, 1]),
+        DockNode::split(Orientation::Vertical, 0.5, DockNode::tabs(&[2]), DockNode::tabs(&[3])));
+    let dock_layout = DockLayout::new([-0.6, 0.27, -0.98, -0.49], arrangement).unwrap()
+        .with_tabs([0.1, 0.05])
+        .with_min_size([40.0, 32.0])
+        .collapsible()
+        .with_styles(19, 11, 7, 20, 21);
+    let dock = widgets.len();
+    widgets.extend(dock_layout.widgets(dock));
+    let [left, _, _, top] = dock_layout.panel_limits();
+    let panel = |i: usize| dock_layout.panel(dock, i);
+    widgets.push(Widget::checkbox([left + 0.02, left + 0.07, top - 0.09, top - 0.02], CheckState::Checked).with_style(6).in_scroll_view(panel(0)));
+    widgets.push(Widget::toggle([left + 0.1, left + 0.2, top - 0.09, top - 0.02], true).with_style(6).in_scroll_view(panel(0)));
+    widgets.push(Widget::spinner([left + 0.02, left + 0.08, top - 0.12, top - 0.02]).with_style(6).in_scroll_view(panel(1)));
+    widgets.push(Widget::progress_bar([left + 0.02, left + 0.3, top - 0.06, top - 0.03], Some(0.7)).with_style(6).in_scroll_view(panel(2)));
+    widgets.extend((0..6).map(|i| {
+        let y = top - 0.02 - i as f32 * 0.07;
+        Widget::new([left + 0.02, left + 0.25, y - 0.05, y], WidgetType::RoundedRect).with_style(1 + i as u32 % 2).in_scroll_view(panel(3))
+    }));
+    let tabs = widgets.len();
+    widgets.extend((0..4).map(|i| {
+        let x = -0.45 + i as f32 * 0.12;
+        Widget::tab([x, x + 0.11, 0.42, 0.48], 0, i == 0).with_style(11)
+    }));
+    let pages = widgets.len();
+    for i in 0..4 {
+        let page = widgets.len();
+        widgets.push(Widget::scroll_view([-0.45, -0.09, 0.25, 0.42]));
+        widgets.push(match i {
+            0 => Widget::new([-0.4, -0.14, 0.29, 0.38], WidgetType::RoundedRect).with_style(1),
+            1 => Widget::toggle([-0.32, -0.22, 0.3, 0.37], true).with_style(6),
+            2 => Widget::spinner([-0.3, -0.24, 0.29, 0.38]).with_style(6),
+            _ => Widget::viewport([-0.44, -0.1, 0.26, 0.41]).with_style(15),
+        }.in_scroll_view(page));
+    }
+    // Popups come last, submenus after the menu they are in
+    let menu_item = |popup: usize, row: usize| Widget::menu_item([-0.99, -0.69, 0.955 - (row + 1) as f32 * 0.06, 0.955 - row as f32 * 0.06]).in_popup(popup).with_style(10);
+    let file_menu = widgets.len();
+    widgets.push(Widget::popup([-1.0, -0.68, 0.685, 0.955]).with_style(9));
+    let new_item = widgets.len();
+    widgets.push(menu_item(file_menu, 0));
+    widgets.push(menu_item(file_menu, 1));
+    widgets.push(Widget::menu_separator([-0.99, -0.69, 0.815, 0.835]).in_popup(file_menu).with_style(10));
+    let recent = widgets.len();
+    widgets.push(Widget::menu_item([-0.99, -0.69, 0.755, 0.815]).in_popup(file_menu).with_style(10));
+    widgets.push(Widget::menu_item([-0.99, -0.69, 0.695, 0.755]).in_popup(file_menu).with_style(10).disabled());
+    let recent_menu = widgets.len();
+    widgets.push(Widget::popup([-1.0, -0.68, 0.765, 0.955]).with_style(9));
+    widgets.extend((0..3).map(|row| menu_item(recent_menu, row)));
+    let edit_menu = widgets.len();
+    widgets.push(Widget::popup([-1.0, -0.68, 0.825, 0.955]).with_style(9));
+    widgets.extend((0..2).map(|row| menu_item(edit_menu, row)));
+    let options = widgets.len();
+    widgets.push(Widget::popup([-1.0, -0.68, 0.765, 0.955]).with_style(9));
+    widgets.extend((0..3).map(|row| menu_item(options, row)));
+    let context_menu = widgets.len();
+    widgets.push(Widget::popup([-1.0, -0.68, 0.765, 0.955]).with_style(9));
+    widgets.extend((0..3).map(|row| menu_item(context_menu, row)));
+    // Dialogs with a heart, shown by the button, the new item of the file menu and the first item of the edit menu
+    let confirm = widgets.len();
+    let confirm_layout = DialogLayout::new(DialogKind::Confirm, [-0.4, 0.4, -0.2, 0.2], [0.15, 0.07]).with_styles(9, 13, 6);
+    widgets.extend(confirm_layout.widgets(confirm));
+    widgets.push(Widget::path([-0.36, -0.24, 0.0, 0.16], 1).with_style(5).in_popup(confirm));
+    let alert = widgets.len();
+    let alert_layout = DialogLayout::new(DialogKind::Alert, [-0.3, 0.3, -0.15, 0.15], [0.15, 0.07]).with_styles(9, 13, 6);
+    widgets.extend(alert_layout.widgets(alert));
+    let prompt = widgets.len();
+    let prompt_layout = DialogLayout::new(DialogKind::Prompt, [-0.4, 0.4, -0.2, 0.2], [0.15, 0.07]).with_styles(9, 13, 6);
+    widgets.extend(prompt_layout.widgets(prompt));
+    // Overlays come after the popups
+    let tooltip = widgets.len();
+    widgets.push(Widget::overlay([-1.0, -0.84, 0.9, 0.96]).with_style(12));
+    widgets.push(Widget::path([-0.99, -0.95, 0.91, 0.95], 1).with_style(5).in_popup(tooltip));
+    let popover = widgets.len();
+    widgets.push(Widget::overlay([-1.0, -0.7, 0.8, 0.96]).with_style(9));
+    widgets.push(Widget::checkbox([-0.98, -0.93, 0.89, 0.94], CheckState::Checked).with_style(6).in_popup(popover));
+    widgets.push(Widget::progress_bar([-0.98, -0.72, 0.83, 0.86], Some(0.6)).with_style(6).in_popup(popover));
+    let picker_popover = widgets.len();
+    widgets.push(Widget::overlay([-1.0, -0.5, 0.2, 0.96]).with_style(16));
+    let picker = widgets.len();
+    let picker_layout = ColorPickerLayout::new([-1.0, -0.5, 0.2, 0.96], PickerShape::Square, 0.05)
+        .with_swatches(&[[0.9, 0.2, 0.3, 1.0], [0.9, 0.6, 0.1, 1.0], [0.2, 0.7, 0.3, 1.0], [0.2, 0.45, 0.9, 1.0], [0.5, 0.3, 0.8, 0.5], [1.0, 1.0, 1.0, 0.0]])
+        .with_styles(9, 15, 6, 14);
+    widgets.extend(picker_layout.widgets().into_iter().map(|w| w.in_popup(picker_popover)));
+    let chart_popover = widgets.len();
+    widgets.push(Widget::overlay([-0.2, 0.9, -0.4, 0.4]).with_style(16));
+    let chart = widgets.len();
+    widgets.push(Widget::chart([-0.2, 0.9, -0.4, 0.4]).with_style(17).in_popup(chart_popover));
+    let blue = Style::new([0.2, 0.5, 0.9, 1.0]).with_border(2.0, [1.0, 1.0, 1.0, 1.0]).with_shadow([4.0, 6.0], 12.0, 0.0, [0.0, 0.0, 0.0, 0.6])
+        .with_gradient(Gradient::Linear { angle: 0.0 }, &[(0.0, [0.1, 0.2, 0.6, 1.0]), (1.0, [0.3, 0.7, 1.0, 1.0])]);
+    let orange = Style::new([0.9, 0.4, 0.2, 0.8]).with_border(3.0, [0.1, 0.1, 0.1, 1.0]).with_radii([4.0, 16.0, 32.0, 0.0]);
+    let styles = vec![
+        StyleSet::default(),
+        StyleSet::new(blue)
+            .with_hovered(blue.with_gradient(Gradient::Radial { center: [0.5, 0.5], radius: 0.7 }, &[(0.0, [0.3, 0.7, 1.0, 1.0]), (1.0, [0.1, 0.2, 0.6, 1.0])])),
+        StyleSet::new(orange)
+            .with_hovered(orange.with_fill([0.9, 0.4, 0.2, 1.0]))
+            .with_pressed(orange.with_gradient(Gradient::Conic { center: [0.5, 0.5], angle: 0.0 }, &[(0.0, [0.9, 0.4, 0.2, 1.0]), (0.5, [0.9, 0.9, 0.2, 1.0]), (1.0, [0.9, 0.4, 0.2, 1.0])]))
+            .with_disabled(orange.with_opacity(0.3)),
+        StyleSet::new(Style::new([1.0, 1.0, 1.0, 0.3]).with_radii([12.0; 4]).with_backdrop_blur(16.0)
+            .with_border(1.0, [1.0, 1.0, 1.0, 0.5]).with_shadow([0.0, 8.0], 24.0, -4.0, [0.0, 0.0, 0.0, 0.5])),
+        StyleSet::new(Style::new([0.0; 4])).with_skin(&skin),
+        StyleSet::new(Style::new([0.9, 0.2, 0.3, 1.0]).with_border(0.0, [1.0, 1.0, 1.0, 1.0]))
+            .with_hovered(Style::new([1.0, 0.4, 0.5, 1.0]).with_border(0.0, [1.0, 1.0, 0.6, 1.0])),
+        StyleSet::new(Style::new([0.95, 0.95, 0.95, 1.0]).with_border(1.5, [0.2, 0.45, 0.9, 1.0]).with_radii([3.0; 4]))
+            .with_hovered(Style::new([1.0, 1.0, 1.0, 1.0]).with_border(1.5, [0.3, 0.55, 1.0, 1.0]).with_radii([3.0; 4])),
+        // Their border colors are those of the arrows and guides of tree views
+        StyleSet::new(Style::new([0.15, 0.15, 0.18, 0.9]).with_border(0.0, [0.8, 0.8, 0.85, 1.0])),
+        StyleSet::new(Style::new([0.2, 0.45, 0.9, 0.8]).with_border(0.0, [1.0, 1.0, 1.0, 1.0])),
+        // Menu bar and popups, then menu items, with arrows and separators of their border color
+        StyleSet::new(Style::new([0.18, 0.18, 0.2, 0.97]).with_radii([4.0; 4]).with_border(1.0, [0.4, 0.4, 0.45, 1.0])
+            .with_shadow([0.0, 4.0], 12.0, 0.0, [0.0, 0.0, 0.0, 0.5])),
+        StyleSet::new(Style::new([0.0; 4]).with_border(0.0, [0.8, 0.8, 0.85, 1.0]))
+            .with_hovered(Style::new([0.2, 0.45, 0.9, 1.0]).with_border(0.0, [1.0, 1.0, 1.0, 1.0]).with_radii([3.0; 4]))
+            .with_disabled(Style::new([0.0; 4]).with_border(0.0, [0.8, 0.8, 0.85, 0.3])),
+        // Tabs, underlined with their border color when selected
+        StyleSet::new(Style::new([0.25, 0.25, 0.3, 1.0]).with_border(0.0, [0.3, 0.6, 1.0, 1.0]).with_radii([6.0; 4]))
+            .with_hovered(Style::new([0.32, 0.32, 0.38, 1.0]).with_border(0.0, [0.3, 0.6, 1.0, 1.0]).with_radii([6.0; 4])),
+        StyleSet::new(Style::new([0.1, 0.1, 0.12, 0.95]).with_radii([4.0; 4]).with_shadow([0.0, 2.0], 6.0, 0.0, [0.0, 0.0, 0.0, 0.4])),
+        // Buttons
+        StyleSet::new(Style::new([0.2, 0.45, 0.9, 1.0]).with_radii([4.0; 4]))
+            .with_hovered(Style::new([0.3, 0.55, 1.0, 1.0]).with_radii([4.0; 4]))
+            .with_pressed(Style::new([0.15, 0.35, 0.75, 1.0]).with_radii([4.0; 4])),
+        // Swatches, then color areas and sliders, then the popover of the color picker, which draws its own panel
+        StyleSet::new(Style::new([0.0; 4]).with_radii([3.0; 4]).with_border(1.0, [0.4, 0.4, 0.45, 1.0]))
+            .with_hovered(Style::new([0.0; 4]).with_radii([3.0; 4]).with_border(1.5, [1.0, 1.0, 1.0, 1.0])),
+        StyleSet::new(Style::new([0.0; 4]).with_radii([4.0; 4])),
+        StyleSet::new(Style::new([0.0; 4])),
+        // Charts, their border coloring the grid, axes and values
+        StyleSet::new(Style::new([0.12, 0.12, 0.14, 0.97]).with_radii([6.0; 4]).with_border(1.0, [0.75, 0.75, 0.8, 1.0])
+            .with_shadow([0.0, 4.0], 12.0, 0.0, [0.0, 0.0, 0.0, 0.5])),
+        // Gauges, their border coloring the track
+        StyleSet::new(Style::new([0.0; 4]).with_border(0.0, [0.3, 0.3, 0.35, 1.0]))
+            .with_hovered(Style::new([0.0; 4]).with_border(0.0, [0.45, 0.45, 0.5, 1.0])),
+        // Canvases
+        StyleSet::new(Style::new([0.12, 0.12, 0.14, 0.97]).with_radii([6.0; 4])),
+        // Dividers of docks, their border coloring the grip, then the indicator of where a dragged tab would go
+        StyleSet::new(Style::new([0.2, 0.2, 0.24, 1.0]).with_border(0.0, [0.5, 0.5, 0.55, 1.0]))
+            .with_hovered(Style::new([0.3, 0.45, 0.8, 1.0]).with_border(0.0, [1.0, 1.0, 1.0, 1.0])),
+        StyleSet::new(Style::new([0.3, 0.6, 1.0, 0.3]).with_radii([4.0; 4]).with_border(2.0, [0.3, 0.6, 1.0, 0.9])),
+    ];
     // widgets.iter().for_each(|w| {
     //     println!("{}", w.limits[2]);
     // });
 
-    let mut state = pollster::block_on(State::new(window, widgets));
+    let mut state = pollster::block_on(State::new(window, widgets, styles, atlas, &paths, custom, 4));
+    // The text input, accented, Cyrillic and Greek characters included
+    state.add_font(include_bytes!("../assets/DejaVuSansMono.ttf").to_vec()).unwrap();
+    // The menu bar along the top and the buttons filling the window below it, following its size
+    let bar = FlexNode::widget(menu_bar).with_height(24.0).with_padding([4.0, 0.0, 4.0, 0.0]).with_gap(4.0).with_align_items(Align::Center)
+        .with_children((file..file + 2).map(|item| FlexNode::widget(item).with_width(64.0).with_height(20.0)));
+    let buttons = FlexNode::row().with_grow(1.0).with_children((0..100).map(|column| {
+        FlexNode::column().with_grow(1.0).with_children((0..100).rev().map(|row| FlexNode::widget(column * 100 + row).with_grow(1.0)))
+    }));
+    state.set_flex(FlexNode::column().with_children([bar, buttons]));
+    state.set_slider_bounds(sliders, 0.0, 100.0, 10.0);
+    state.set_slider_value(sliders, 0, 30.0);
+    state.set_slider_bounds(sliders + 1, 0.0, 1000.0, 50.0);
+    state.set_slider_value(sliders + 1, 0, 200.0);
+    state.set_slider_value(sliders + 1, 1, 750.0);
+    state.set_slider_bounds(sliders + 2, -1.0, 1.0, 0.0);
+    state.set_slider_value(sliders + 2, 0, 0.25);
+    state.set_progress(progress, Some(0.3));
+    state.scroll_to(last);
+    state.set_data_source(log_view, log, LogRows { len: 1_000_000 });
+    state.set_data_source(swatches_view, swatches, Swatches { paths: 3 });
+    state.set_table(table, table_layout, 100_000, vec![Box::new(Levels), Box::new(Flags), Box::new(Kinds)]);
+    state.select_row(table, Some(42));
+    state.scroll_to_row(table, 42);
+    state.set_tree(tree, tree_layout, Folders, 7, 8);
+    state.set_expanded(tree, 1, true);
+    state.set_submenu(file, file_menu);
+    state.set_submenu(file + 1, edit_menu);
+    state.set_submenu(recent, recent_menu);
+    state.set_accelerator(new_item, Accelerator::new(Key::Character("n".into()), ModifiersState::CONTROL));
+    state.set_accelerator(new_item + 1, Accelerator::new(Key::Character("o".into()), ModifiersState::CONTROL));
+    state.set_combo_options(combo, options);
+    state.set_context_menu(glass, context_menu);
+    for i in 0..4 {
+        state.set_tab_page(tabs + i, pages + i * 2);
+    }
+    state.set_viewport(pages + 7, Scene { texture: None, size: [1, 1], pointer: [0.0; 2], pressed: false, blue: 0.5 });
+    state.set_tooltip(combo, tooltip);
+    state.set_tooltip(tabs + 1, tooltip);
+    state.set_popover(more, popover, Side::Below);
+    state.set_dialog(confirm, &confirm_layout);
+    state.set_dialog(alert, &alert_layout);
+    state.set_dialog(prompt, &prompt_layout);
+    state.set_popover(well, picker_popover, Side::Below);
+    state.set_color_picker(picker, &picker_layout);
+    state.set_color(picker, [0.2, 0.45, 0.9, 1.0]);
+    state.set_swatch(well, [0.2, 0.45, 0.9, 1.0]);
+    state.set_popover(plot_button, chart_popover, Side::Below);
+    state.set_series(chart, vec![
+        Series::new(ChartKind::Bar, [0.9, 0.6, 0.1, 0.8], (0..20).map(|i| [i as f32 * 10.0 + 5.0, (i * 7 % 11) as f32 / 10.0 + 0.2]).collect()),
+        Series::new(ChartKind::Area, [0.2, 0.7, 0.3, 1.0], (0..=400).map(|i| [i as f32 * 0.5, (i as f32 * 0.025).cos() * 0.5]).collect()),
+        Series::new(ChartKind::Line, [0.3, 0.6, 1.0, 1.0], (0..=4000).map(|i| {
+            let x = i as f32 * 0.05;
+            [x, (x * 0.1).sin() + (x * 3.0).sin() * 0.1]
+        }).collect()),
+        Series::new(ChartKind::Scatter, [0.9, 0.2, 0.3, 1.0], (0..60).map(|i| [(i * 37 % 200) as f32, (i * 53 % 100) as f32 / 50.0 - 1.0]).collect()),
+    ]);
+    let mut hovered_bar = None;
+    state.set_canvas(canvas, sketch(state.canvas_size(canvas), hovered_bar, checker));
+    state.set_dock(dock, &dock_layout);
+    // Restoring the arrangement of a previous session, logged whenever it changes
+    let saved = "v(0.6,h(0.4,[0,1*],[2]),[3])";
+    if let Err(e) = saved.parse().and_then(|root| state.set_dock_layout(dock, root)) {
+        log::warn!("{e}");
+    }
 
     // Run loop
     cfg_if::cfg_if! {
@@ -47,7 +426,7 @@ pub fn run() {
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == state.window.id() => if !state.input(event) {
+            } if window_id == state.window.id() && !state.input(event) => {
                 match event {
                     WindowEvent::KeyboardInput {
                         event:
@@ -63,13 +442,14 @@ pub fn run() {
                     
                         let slice: &[u8] = &mut mapped_id_buffer.slice(..).get_mapped_range(); // DEBUG
                         let mut vec: Vec<u8> = Vec::with_capacity(slice.len() / 4); // DEBUG
-                        slice.into_iter().step_by(4).for_each(|&val| vec.push(val)); // DEBUG
+                        slice.iter().step_by(4).for_each(|&val| vec.push(val)); // DEBUG
                         image::save_buffer("./id_buffer.png", vec.as_slice(), width, height, image::ColorType::L8).unwrap(); // DEBUG
 
                         target.exit();
                     }
                     WindowEvent::Resized(physical_size) => {
                         state.resize(*physical_size);
+                        state.set_canvas(canvas, sketch(state.canvas_size(canvas), hovered_bar, checker));
 
                         state.window.request_redraw();
                     }
@@ -92,5 +472,310 @@ pub fn run() {
             },
             _ => {}
         }
+        for event in state.take_events() {
+            log::info!("{:?}", event);
+            if let event::WidgetEvent::SelectionChanged { index } = event {
+                log::info!("selected {:?}", state.selected_nodes(index));
+            }
+            if let event::WidgetEvent::CheckChanged { index, .. } = event {
+                if let Some((view, item)) = state.slot_item(index) {
+                    log::info!("item {item} of list {view}");
+                }
+            }
+            if let event::WidgetEvent::OptionSelected { index, option } = event {
+                if index == combo {
+                    state.set_progress(progress, Some(option as f32 / 2.0));
+                }
+            }
+            if let event::WidgetEvent::Clicked { index } = event {
+                if index == confirm_button {
+                    state.show_dialog(confirm, |result| log::info!("confirm: {result:?}"));
+                }
+            }
+            if let event::WidgetEvent::MenuActivated { index } = event {
+                if index == new_item {
+                    state.show_dialog(alert, |result| log::info!("alert: {result:?}"));
+                } else if index == edit_menu + 1 {
+                    state.show_dialog(prompt, |result| log::info!("prompt: {result:?}"));
+                }
+            }
+            if let event::WidgetEvent::ColorChanged { index, color } = event {
+                if index == picker {
+                    state.set_swatch(well, color);
+                }
+            }
+            if let event::WidgetEvent::RegionHovered { index, region } = event {
+                if index == canvas {
+                    hovered_bar = region;
+                    state.set_canvas(canvas, sketch(state.canvas_size(canvas), hovered_bar, checker));
+                }
+            }
+            if let event::WidgetEvent::DockChanged { index } = event {
+                log::info!("dock layout: {}", state.dock_layout(index));
+            }
+            if let event::WidgetEvent::SliderMoved { index, value } = event {
+                if index == sliders {
+                    state.set_progress(progress, Some(value / 100.0));
+                    state.set_params(gauge, &[ParamValue::Float(value / 100.0), ParamValue::Color([0.3, 0.8, 0.4, 1.0])]);
+                }
+            }
+        }
     });
-}
\ No newline at end of file
+}
+/// Bars of `size` pixels with a hit region each, the `highlighted` one lighter, joined by a line and named below,
+/// with a title along the left, the first path and the image `checker` clipped to a circle's corner.
+fn sketch(size: [f32; 2], highlighted: Option<u32>, checker: u32) -> Canvas {
+    const VALUES: [f32; 5] = [0.4, 0.9, 0.6, 0.75, 0.3];
+    let [width, height] = size;
+    let mut canvas = Canvas::new();
+    let left = 24.0;
+    let bar = (width - left - 8.0) / VALUES.len() as f32;
+    let bottom = height - 18.0;
+    let mut tops = vec![];
+    for (i, value) in VALUES.iter().enumerate() {
+        let x = left + i as f32 * bar;
+        let top = 32.0 + (bottom - 32.0) * (1.0 - value);
+        canvas.set_hit_region(Some(i as u32));
+        canvas.set_fill_color(if highlighted == Some(i as u32) { [0.5, 0.75, 1.0, 1.0] } else { [0.2, 0.45, 0.9, 1.0] });
+        canvas.fill_rounded_rect(x + 4.0, top, bar - 8.0, bottom - top, 3.0);
+        canvas.set_hit_region(None);
+        let name = ((b'A' + i as u8) as char).to_string();
+        canvas.set_fill_color([0.8, 0.8, 0.85, 1.0]);
+        canvas.fill_text(&name, [x + (bar - Canvas::measure_text(&name, 10.0)) * 0.5, bottom + 4.0], 10.0);
+        tops.push([x + bar * 0.5, top]);
+    }
+    canvas.set_stroke_color([0.9, 0.6, 0.1, 1.0]);
+    canvas.set_line_width(2.0);
+    canvas.polyline(&tops);
+    for &top in &tops {
+        canvas.fill_circle(top, 3.0);
+    }
+    canvas.save();
+    canvas.translate(12.0, height * 0.5);
+    canvas.rotate(-std::f32::consts::FRAC_PI_2);
+    let title = "Sales";
+    canvas.fill_text(title, [-Canvas::measure_text(title, 10.0) * 0.5, -5.0], 10.0);
+    canvas.restore();
+    canvas.save();
+    canvas.clip_rect(width - 32.0, 4.0, 14.0, 14.0);
+    canvas.draw_image(checker, width - 32.0, 4.0, 28.0, 28.0);
+    canvas.restore();
+    canvas.set_stroke_color([1.0, 1.0, 1.0, 0.8]);
+    canvas.set_line_width(1.5);
+    canvas.stroke_circle([width - 18.0, 18.0], 12.0);
+    canvas.set_fill_color([0.9, 0.2, 0.3, 1.0]);
+    canvas.draw_path(0, left, 4.0, 24.0, 24.0);
+    canvas
+}
+
+/// A ring open at the bottom, filled clockwise up to `value` in `color`, over a track in the border color of its style.
+struct Gauge;
+
+impl CustomWidget for Gauge {
+    fn name(&self) -> &str {
+        "gauge"
+    }
+
+    fn params(&self) -> &[(&str, ParamKind)] {
+        &[("value", ParamKind::Unorm16), ("color", ParamKind::Rgba8)]
+    }
+
+    fn wgsl(&self) -> &str {
+        r#"
+fn gauge(in: VertexOutput, params: gauge_params) -> vec4<f32> {
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+    let p = in.position.xy - (r.xy + r.zw) * 0.5;
+    let radius = min(r.z - r.x, r.w - r.y) * 0.5 - 1.0;
+    let width = radius * 0.3;
+    let d = abs(length(p) - radius + width * 0.5) - width * 0.5;
+    // From 0 at the bottom clockwise, leaving a quarter open there
+    let t = (fract(atan2(-p.x, p.y) / TAU + 1.0) - 0.125) / 0.75;
+    let coverage = clamp(0.5 - d, 0.0, 1.0) * f32(t >= 0.0 && t <= 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    let color = select(style.border_color, params.color, t <= params.value);
+    return vec4<f32>(color.rgb, color.a * coverage * style.opacity);
+}
+"#
+    }
+}
+
+/// A scene cleared to a color following the pointer, darker while the left button is held,
+/// its blue changed by the wheel and the up and down keys.
+struct Scene {
+    texture: Option<wgpu::Texture>,
+    size: [u32; 2],
+    pointer: [f32; 2],
+    pressed: bool,
+    blue: f32,
+}
+
+impl Viewport for Scene {
+    fn resize(&mut self, device: &wgpu::Device, size: [u32; 2]) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Scene Texture"),
+            size: wgpu::Extent3d { width: size[0], height: size[1], depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.texture = Some(texture);
+        self.size = size;
+        view
+    }
+
+    fn draw(&mut self, _device: &wgpu::Device, _queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder) {
+        let Some(texture) = &self.texture else { return };
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let dim = if self.pressed { 0.5 } else { 1.0 };
+        let [r, g] = [self.pointer[0] / self.size[0] as f32, self.pointer[1] / self.size[1] as f32].map(|c| (c.clamp(0.0, 1.0) * dim) as f64);
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Scene Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b: (self.blue * dim) as f64, a: 1.0 }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+
+    fn input(&mut self, event: &ViewportEvent) -> bool {
+        match event {
+            ViewportEvent::PointerMoved { position } => self.pointer = *position,
+            ViewportEvent::Button { button: MouseButton::Left, pressed, .. } => self.pressed = *pressed,
+            ViewportEvent::Wheel { delta, .. } => self.blue = (self.blue + delta[1] * 0.002).clamp(0.0, 1.0),
+            ViewportEvent::Key(KeyEvent { logical_key: Key::Named(NamedKey::ArrowUp), state: ElementState::Pressed, .. }) => {
+                self.blue = (self.blue + 0.1).min(1.0);
+            }
+            ViewportEvent::Key(KeyEvent { logical_key: Key::Named(NamedKey::ArrowDown), state: ElementState::Pressed, .. }) => {
+                self.blue = (self.blue - 0.1).max(0.0);
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// A million rows standing for the lines of a log, as progress bars of varying length.
+struct LogRows {
+    len: usize,
+}
+
+impl DataSource for LogRows {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn widget(&mut self, item: usize, limits: [f32; 4]) -> Widget {
+        let level = (item * 7919 % 1000) as f32 / 1000.0;
+        let [left, right, bottom, top] = limits;
+        Widget::progress_bar([left + 0.01, right - 0.03, bottom + 0.01, top - 0.01], Some(level)).with_style(6)
+    }
+}
+
+/// A grid of the first `paths` paths, every fifth item being a checkbox.
+struct Swatches {
+    paths: u32,
+}
+
+impl DataSource for Swatches {
+    fn len(&self) -> usize {
+        10_000
+    }
+
+    fn widget(&mut self, item: usize, limits: [f32; 4]) -> Widget {
+        let [left, right, bottom, top] = limits;
+        let limits = [left + 0.005, right - 0.005, bottom + 0.005, top - 0.005];
+        if item % 5 == 4 {
+            Widget::checkbox(limits, CheckState::Unchecked).with_style(6)
+        } else {
+            Widget::path(limits, item as u32 % self.paths).with_style(5)
+        }
+    }
+}
+
+/// The level of a log row, from 0 to 1.
+fn level(row: usize) -> f32 {
+    (row * 7919 % 1000) as f32 / 1000.0
+}
+
+/// Table column showing the level of rows as progress bars.
+struct Levels;
+
+impl Column for Levels {
+    fn cell(&mut self, row: usize, limits: [f32; 4]) -> Widget {
+        let [left, right, bottom, top] = limits;
+        Widget::progress_bar([left + 0.01, right - 0.01, bottom + 0.015, top - 0.015], Some(level(row))).with_style(6)
+    }
+
+    fn sortable(&self) -> bool {
+        true
+    }
+
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        level(a).total_cmp(&level(b))
+    }
+}
+
+/// Table column checking every third row.
+struct Flags;
+
+impl Column for Flags {
+    fn cell(&mut self, row: usize, limits: [f32; 4]) -> Widget {
+        let [left, _, bottom, top] = limits;
+        let size = top - bottom - 0.02;
+        let state = if row.is_multiple_of(3) { CheckState::Checked } else { CheckState::Unchecked };
+        Widget::checkbox([left + 0.01, left + 0.01 + size * 0.6, bottom + 0.01, top - 0.01], state).with_style(6)
+    }
+
+    fn sortable(&self) -> bool {
+        true
+    }
+
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        a.is_multiple_of(3).cmp(&b.is_multiple_of(3)).reverse()
+    }
+}
+
+/// Table column showing the kind of rows as the blue or orange style, not sortable.
+struct Kinds;
+
+impl Column for Kinds {
+    fn cell(&mut self, row: usize, limits: [f32; 4]) -> Widget {
+        let [left, right, bottom, top] = limits;
+        Widget::new([left + 0.01, right - 0.01, bottom + 0.01, top - 0.01], WidgetType::RoundedRect).with_style(1 + row as u32 % 2)
+    }
+}
+
+/// Folders four levels deep, each holding three folders or files. Nodes are numbered like `1 -> 11, 12, 13`.
+struct Folders;
+
+impl TreeSource for Folders {
+    fn children(&mut self, node: Option<NodeId>) -> Vec<NodeId> {
+        let first = node.map_or(1, |n| n * 10 + 1);
+        (first..first + 3).collect()
+    }
+
+    fn has_children(&self, node: NodeId) -> bool {
+        node < 1000
+    }
+
+    fn widget(&mut self, node: NodeId, limits: [f32; 4]) -> Widget {
+        let [left, _, bottom, top] = limits;
+        let width = if self.has_children(node) { 0.12 } else { 0.08 };
+        let style = if self.has_children(node) { 2 } else { 1 };
+        Widget::new([left + 0.005, left + width, bottom + 0.01, top - 0.01], WidgetType::RoundedRect).with_style(style)
+    }
+}
@@ -0,0 +1,268 @@
+use std::iter;
+
+use crate::state::{Widget, WidgetType};
+
+/// The items shown by a list or grid view, asked for as they are scrolled into view.
+pub(super) trait DataSource {
+    /// The number of items.
+    fn len(&self) -> usize;
+
+    /// The widget showing `item` at `limits`, in the slot of a list or grid view.
+    /// Its state must be in its `ty`: sliders, text inputs and scroll views can't be items.
+    fn widget(&mut self, item: usize, limits: [f32; 4]) -> Widget;
+}
+
+/// How a list or grid view lays out its items: rows of `row_height`, split in columns, from its top left.
+/// All sizes are in NDC.
+#[derive(Clone, Debug)]
+pub(super) struct ItemLayout {
+    limits: [f32; 4],
+    row_height: f32,
+    /// Left edges of the columns from the left of the view, then the right edge of the last one.
+    edges: Vec<f32>,
+    /// Whether each row starts with an item spanning all columns, drawn behind the others.
+    backgrounds: bool,
+}
+
+impl ItemLayout {
+    /// A list view at `limits`, its items being rows as wide as it.
+    pub(super) fn list(limits: [f32; 4], item_height: f32) -> Self {
+        Self::grid(limits, [limits[1] - limits[0], item_height], 1)
+    }
+
+    /// A grid view at `limits`, with `columns` items of `item_size` per row.
+    pub(super) fn grid(limits: [f32; 4], item_size: [f32; 2], columns: usize) -> Self {
+        assert!(item_size[1] > 0.0, "Items of list and grid views must have a height, not {}", item_size[1]);
+        let edges = (0..=columns.max(1)).map(|c| c as f32 * item_size[0]).collect();
+        Self { limits, row_height: item_size[1], edges, backgrounds: false }
+    }
+
+    /// The body of a table at `limits`, with columns of `widths`, each row starting with its background.
+    pub(super) fn table(limits: [f32; 4], row_height: f32, widths: &[f32]) -> Self {
+        assert!(row_height > 0.0, "Rows of tables and trees must have a height, not {row_height}");
+        let edges = iter::once(0.0).chain(widths.iter().scan(0.0, |right, width| {
+            *right += width;
+            Some(*right)
+        })).collect();
+        Self { limits, row_height, edges, backgrounds: true }
+    }
+
+    /// The list view of a tree at `limits`, each row being its tree row widget then its node.
+    pub(super) fn tree(limits: [f32; 4], row_height: f32) -> Self {
+        Self::table(limits, row_height, &[limits[1] - limits[0]])
+    }
+
+    /// The number of items per row, including the background.
+    pub(super) fn items_per_row(&self) -> usize {
+        self.edges.len() - 1 + self.backgrounds as usize
+    }
+
+    /// The number of slots covering the view at any scroll offset, one more row than fits in it.
+    pub(super) fn slot_count(&self) -> usize {
+        let rows = ((self.limits[3] - self.limits[2]) / self.row_height).ceil() as usize + 1;
+        rows * self.items_per_row()
+    }
+
+    /// The scroll view of the list or grid view, to be put at `index` in the widgets, followed by its empty slots.
+    pub(super) fn widgets(&self, index: usize) -> Vec<Widget> {
+        let empty = Widget::new(self.hidden(), WidgetType::Rect).in_scroll_view(index);
+        let mut widgets = vec![Widget::scroll_view(self.limits)];
+        widgets.resize(self.slot_count() + 1, empty);
+        widgets
+    }
+
+    /// The largest scroll offsets with `len` items.
+    pub(super) fn scroll_max(&self, len: usize) -> [f64; 2] {
+        let rows = len.div_ceil(self.items_per_row());
+        [
+            f64::from((self.edges[self.edges.len() - 1] - (self.limits[1] - self.limits[0])).max(0.0)),
+            (self.rows_height(rows) - f64::from(self.limits[3] - self.limits[2])).max(0.0),
+        ]
+    }
+
+    /// The first row in view when scrolled down by `offset`.
+    pub(super) fn first_row(&self, offset: f64) -> usize {
+        (offset / f64::from(self.row_height)).max(0.0) as usize
+    }
+
+    /// The height of `rows` rows, as `f64` like scroll offsets.
+    pub(super) fn rows_height(&self, rows: usize) -> f64 {
+        rows as f64 * f64::from(self.row_height)
+    }
+
+    /// The vertical offset the view scrolled down by `offset` is drawn with, its items being placed from the row `base_row`.
+    pub(super) fn drawn_offset(&self, offset: f64, base_row: usize) -> f32 {
+        (offset - self.rows_height(base_row)) as f32
+    }
+
+    /// The limits of `item` when the row `base_row` is at the top of the view.
+    pub(super) fn item_limits(&self, item: usize, base_row: usize) -> [f32; 4] {
+        let per_row = self.items_per_row();
+        let (left, right) = match (self.backgrounds, item % per_row) {
+            (true, 0) => (self.edges[0], self.edges[self.edges.len() - 1]),
+            (true, column) => (self.edges[column - 1], self.edges[column]),
+            (false, column) => (self.edges[column], self.edges[column + 1]),
+        };
+        let top = self.limits[3] - self.rows_height(item / per_row - base_row) as f32;
+        [self.limits[0] + left, self.limits[0] + right, top - self.row_height, top]
+    }
+
+    /// Move the view by `offset` in NDC, after its widgets were moved.
+    pub(super) fn translate(&mut self, offset: [f32; 2]) {
+        let [left, right, bottom, top] = self.limits;
+        self.limits = [left + offset[0], right + offset[0], bottom + offset[1], top + offset[1]];
+    }
+
+    /// Zero sized limits, hiding a slot without an item.
+    pub(super) fn hidden(&self) -> [f32; 4] {
+        let [left, _, _, top] = self.limits;
+        [left, left, top, top]
+    }
+}
+
+/// A list or grid view, its slots showing the items in view.
+pub(super) struct VirtualView {
+    pub(super) layout: ItemLayout,
+    pub(super) source: Box<dyn DataSource>,
+    /// Index of the first slot in the widgets, the others following it.
+    pub(super) first_slot: usize,
+    /// The item in each slot.
+    pub(super) items: Vec<Option<usize>>,
+    /// The row the items are placed from, the first one in view.
+    /// The scroll view is drawn scrolled by the `f64` offset less the height of the rows above it, less than a row,
+    /// so that the items and the offset given to the shaders stay small enough for `f32` with millions of rows.
+    pub(super) base_row: usize,
+}
+
+impl VirtualView {
+    pub(super) fn new(layout: ItemLayout, source: Box<dyn DataSource>, first_slot: usize) -> Self {
+        let items = vec![None; layout.slot_count()];
+        Self { layout, source, first_slot, items, base_row: 0 }
+    }
+
+    /// The item of the slot widget `index`, if it is one of this view's slots and shows an item.
+    pub(super) fn item(&self, index: usize) -> Option<usize> {
+        let slot = index.checked_sub(self.first_slot)?;
+        self.items.get(slot).copied().flatten()
+    }
+
+    /// The item that should be in each slot when scrolled down to `base_row`, items staying in the same slot while in view.
+    pub(super) fn wanted(&self, base_row: usize) -> impl Iterator<Item = Option<usize>> + '_ {
+        let count = self.items.len();
+        let first = base_row * self.layout.items_per_row();
+        let len = self.source.len();
+        (0..count).map(move |slot| {
+            let item = first + (slot + count - first % count) % count;
+            (item < len).then_some(item)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scroll::ScrollModel;
+
+    /// `len` rectangles.
+    struct Items(usize);
+
+    impl DataSource for Items {
+        fn len(&self) -> usize {
+            self.0
+        }
+
+        fn widget(&mut self, _item: usize, limits: [f32; 4]) -> Widget {
+            Widget::new(limits, WidgetType::Rect)
+        }
+    }
+
+    fn view(layout: ItemLayout, len: usize) -> VirtualView {
+        VirtualView::new(layout, Box::new(Items(len)), 1)
+    }
+
+    #[test]
+    fn items_keep_their_slot() {
+        // 4 rows in view, so 5 slots
+        let view = view(ItemLayout::list([-1.0, 1.0, -1.0, 1.0], 0.5), 100);
+        assert_eq!(view.items.len(), 5);
+        assert_eq!(view.wanted(0).collect::<Vec<_>>(), [Some(0), Some(1), Some(2), Some(3), Some(4)]);
+        // Scrolled down by two rows, the items still in view stay where they were
+        assert_eq!(view.wanted(2).collect::<Vec<_>>(), [Some(5), Some(6), Some(2), Some(3), Some(4)]);
+        for base_row in 0..96 {
+            for (slot, item) in view.wanted(base_row).enumerate() {
+                let item = item.unwrap();
+                assert!((base_row..base_row + 5).contains(&item));
+                assert_eq!(item % 5, slot);
+            }
+        }
+        assert_eq!(view.layout.item_limits(3, 2), [-1.0, 1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn last_row_not_full() {
+        // Rows of 3 items, 10 of them
+        let view = view(ItemLayout::grid([-1.0, 1.0, -1.0, 1.0], [0.5, 0.5], 3), 10);
+        assert_eq!(view.items.len(), 15);
+        let wanted: Vec<_> = view.wanted(0).collect();
+        assert_eq!(wanted[..10], (0..10).map(Some).collect::<Vec<_>>());
+        assert!(wanted[10..].iter().all(Option::is_none));
+        // Only the last row and the one before it are left
+        let wanted: Vec<_> = view.wanted(2).collect();
+        assert_eq!(wanted.iter().flatten().copied().collect::<Vec<_>>(), [6, 7, 8, 9]);
+        assert_eq!(wanted[6..10], [Some(6), Some(7), Some(8), Some(9)]);
+        assert_eq!(view.layout.item_limits(9, 2), [-1.0, -0.5, 0.0, 0.5]);
+        assert_eq!(view.layout.item_limits(8, 2), [0.0, 0.5, 0.5, 1.0]);
+        // 4 rows fit, a 5th one scrolls
+        assert_eq!(view.layout.scroll_max(12), [0.0, 0.0]);
+        assert_eq!(view.layout.scroll_max(13), [0.0, 0.5]);
+    }
+
+    #[test]
+    fn table_backgrounds() {
+        // Each row is its background then 2 cells
+        let view = view(ItemLayout::table([-1.0, 1.0, -1.0, 1.0], 0.5, &[0.5, 1.0]), 21);
+        assert_eq!(view.layout.items_per_row(), 3);
+        assert_eq!(view.items.len(), 15);
+        assert_eq!(view.layout.item_limits(3, 0), [-1.0, 0.5, 0.0, 0.5]);
+        assert_eq!(view.layout.item_limits(4, 0), [-1.0, -0.5, 0.0, 0.5]);
+        assert_eq!(view.layout.item_limits(5, 0), [-0.5, 0.5, 0.0, 0.5]);
+        assert_eq!(view.layout.item_limits(5, 1), [-0.5, 0.5, 0.5, 1.0]);
+        // Backgrounds stay in the slots of backgrounds, and cells in the slots of their column
+        for base_row in 0..3 {
+            let wanted: Vec<_> = view.wanted(base_row).collect();
+            assert_eq!(wanted.iter().flatten().count(), 15.min(21 - base_row * 3));
+            for (slot, item) in wanted.into_iter().enumerate() {
+                if let Some(item) = item {
+                    assert_eq!(item % 3, slot % 3);
+                    assert!(item >= base_row * 3);
+                }
+            }
+        }
+        assert_eq!(view.wanted(1).nth(3), Some(Some(3)));
+    }
+
+    #[test]
+    fn pixel_scroll_at_the_end_of_millions_of_rows() {
+        // 5 million rows of 0.05 NDC in a view 800 pixels tall
+        let layout = ItemLayout::list([-1.0, 1.0, -1.0, 1.0], 0.05);
+        let pixel = 2.0 / 800.0;
+        let mut scroll = ScrollModel::new(layout.scroll_max(5_000_000));
+        let max = scroll.max()[1];
+        assert!((max - 249_998.0).abs() < 0.01, "Scrolls down to {max}");
+        scroll.set_offset([0.0, max - 0.5]);
+        let base_row = layout.first_row(scroll.offset()[1]);
+        let drawn = layout.drawn_offset(scroll.offset()[1], base_row);
+
+        for i in 1..=4 {
+            assert!(scroll.scroll_by([0.0, pixel]));
+            assert_eq!(layout.first_row(scroll.offset()[1]), base_row);
+            let moved = layout.drawn_offset(scroll.offset()[1], base_row) - drawn;
+            assert!((moved - i as f32 * pixel).abs() < 1e-5, "Moved by {moved} after {i} pixels");
+        }
+        // Up to the last pixel
+        scroll.set_offset([0.0, max - f64::from(pixel) * 0.5]);
+        assert!(scroll.scroll_by([0.0, pixel]));
+        assert_eq!(scroll.offset()[1], max);
+        assert!(!scroll.scroll_by([0.0, pixel]));
+    }
+}
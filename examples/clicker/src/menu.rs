@@ -0,0 +1,57 @@
+use winit::keyboard::{Key, ModifiersState};
+
+/// Set in `ty[1]` of menu items opening a submenu to their right, with an arrow.
+/// Must match `MENU_SUBMENU` in `w__fragment.wgsl`.
+pub(super) const MENU_SUBMENU: u32 = 1;
+/// Set in `ty[1]` of menu items only separating others, drawn as a line.
+/// Must match `MENU_SEPARATOR` in `w__fragment.wgsl`.
+pub(super) const MENU_SEPARATOR: u32 = 1 << 1;
+/// Set in `ty[2]` of combo boxes while their options are shown, flipping their arrow.
+/// Must match `COMBO_OPEN` in `w__fragment.wgsl`.
+pub(super) const COMBO_OPEN: u32 = 1;
+
+/// Where a popup opens relative to the widget opening it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum PopupAnchor {
+    /// Its top left corner at the bottom left one of the widget, for menu bars and combo boxes.
+    Below,
+    /// Its top left corner at the top right one of the widget, for submenus.
+    Right,
+}
+
+/// A key with modifiers activating a menu item, even when its menu is closed.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Accelerator {
+    key: Key,
+    modifiers: ModifiersState,
+}
+
+impl Accelerator {
+    pub(super) fn new(key: Key, modifiers: ModifiersState) -> Self {
+        Self { key, modifiers }
+    }
+
+    /// Whether `key` pressed with `modifiers` triggers the accelerator. Letters match in either case.
+    pub(super) fn matches(&self, key: &Key, modifiers: ModifiersState) -> bool {
+        let same_key = match (&self.key, key) {
+            (Key::Character(a), Key::Character(b)) => a.to_lowercase() == b.to_lowercase(),
+            (a, b) => a == b,
+        };
+        same_key && self.modifiers == modifiers
+    }
+
+    /// Whether the accelerator is also typed as text, being a character with no modifiers besides Shift.
+    pub(super) fn types_text(&self) -> bool {
+        matches!(self.key, Key::Character(_)) && !self.modifiers.intersects(ModifiersState::CONTROL | ModifiersState::ALT | ModifiersState::SUPER)
+    }
+}
+
+/// The offset moving a popup at `limits` so that its top left corner is at `at`, in NDC.
+/// Popups that would leave the screen are moved back in, on the other side of `at` if they fit there.
+pub(super) fn popup_offset(limits: [f32; 4], at: [f32; 2]) -> [f32; 2] {
+    let [width, height] = [limits[1] - limits[0], limits[3] - limits[2]];
+    let left = if at[0] + width <= 1.0 { at[0] } else { (at[0] - width).max(-1.0) };
+    let top = if at[1] - height >= -1.0 { at[1] } else { (at[1] + height).min(1.0) };
+    // +X scrolls content to the left and +Y up, like scroll views
+    [limits[0] - left, top - limits[3]]
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+/// How long the pointer must rest on a widget before its tooltip shows.
+pub(super) const TOOLTIP_DELAY: Duration = Duration::from_millis(500);
+/// How far tooltips are from the pointer, in pixels, right and below it unless it doesn't fit.
+pub(super) const TOOLTIP_OFFSET: [f32; 2] = [12.0, 18.0];
+/// Space between popovers and their anchor, in pixels.
+pub(super) const POPOVER_GAP: f32 = 6.0;
+
+/// The side of its anchor a popover shows on.
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum Side {
+    Above,
+    Below,
+    Left,
+    Right,
+}
+
+/// The offset moving a tooltip at `limits` to the pointer at `cursor`, `offset` away from it, all in NDC.
+/// It goes right of and below the pointer, or left of or above it where it doesn't fit.
+pub(super) fn tooltip_offset(limits: [f32; 4], cursor: [f32; 2], offset: [f32; 2]) -> [f32; 2] {
+    let [width, height] = [limits[1] - limits[0], limits[3] - limits[2]];
+    let left = beside(cursor[0] - offset[0], cursor[0] + offset[0], width, true);
+    let bottom = beside(cursor[1] - offset[1], cursor[1] + offset[1], height, false);
+    [limits[0] - left, bottom + height - limits[3]]
+}
+
+/// The offset moving a popover at `limits` on `side` of `anchor`, `gap` away from it, all in NDC.
+/// It is centered along the anchor, goes on the opposite side if it doesn't fit on `side`, and stays on screen.
+pub(super) fn popover_offset(limits: [f32; 4], anchor: [f32; 4], side: Side, gap: [f32; 2]) -> [f32; 2] {
+    let [width, height] = [limits[1] - limits[0], limits[3] - limits[2]];
+    let (left, bottom) = match side {
+        Side::Above | Side::Below => (
+            centered(anchor[0], anchor[1], width),
+            beside(anchor[2] - gap[1], anchor[3] + gap[1], height, side == Side::Above),
+        ),
+        Side::Left | Side::Right => (
+            beside(anchor[0] - gap[0], anchor[1] + gap[0], width, side == Side::Right),
+            centered(anchor[2], anchor[3], height),
+        ),
+    };
+    // +X scrolls content to the left and +Y up, like scroll views
+    [limits[0] - left, bottom + height - limits[3]]
+}
+
+/// Where something of `size` starts along an axis to be past `high`, or to end before `low`,
+/// on the preferred side if it fits on screen there, else on the other one, else pushed back on screen.
+fn beside(low: f32, high: f32, size: f32, prefer_high: bool) -> f32 {
+    let fits_high = high + size <= 1.0;
+    let fits_low = low - size >= -1.0;
+    if fits_high && (prefer_high || !fits_low) {
+        high
+    } else if fits_low {
+        low - size
+    } else if prefer_high {
+        (1.0 - size).max(-1.0)
+    } else {
+        -1.0
+    }
+}
+
+/// Where something of `size` starts along an axis to be centered between `min` and `max`, kept on screen.
+fn centered(min: f32, max: f32, size: f32) -> f32 {
+    ((min + max - size) * 0.5).min(1.0 - size).max(-1.0)
+}
@@ -0,0 +1,430 @@
+use std::f32::consts::PI;
+
+use bytemuck::{Pod, Zeroable};
+
+/// Maximum distance between a curve and the segments approximating it, in path units.
+const FLATTEN_TOLERANCE: f32 = 0.02;
+/// Set in the fill count of a path header for the even-odd fill rule.
+/// Must match `PATH_EVEN_ODD` in `w__fragment.wgsl`.
+const PATH_EVEN_ODD: u32 = 0x8000_0000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum PathCommand {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    /// Quadratic bézier curve, with a control point.
+    QuadTo([f32; 2], [f32; 2]),
+    /// Cubic bézier curve, with two control points.
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    /// Elliptical arc, as in SVG: radii, rotation of the X axis in radians, large arc and sweep flags, end point.
+    ArcTo { radii: [f32; 2], rotation: f32, large_arc: bool, sweep: bool, to: [f32; 2] },
+    Close,
+}
+
+/// Which points are inside a filled path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum LineJoin {
+    /// Falls back to `Bevel` when the miter is longer than the miter limit times the width.
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(super) struct Stroke {
+    /// Width in path units.
+    pub(super) width: f32,
+    pub(super) cap: LineCap,
+    pub(super) join: LineJoin,
+    pub(super) miter_limit: f32,
+}
+
+impl Stroke {
+    /// A stroke of `width` path units, with butt caps and miter joins, like SVG's defaults.
+    pub(super) fn new(width: f32) -> Self {
+        Self {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+
+    pub(super) fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub(super) fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+}
+
+/// A vector shape shown by path widgets, filled with the widget's fill and stroked with its border color.
+///
+/// The shape is scaled uniformly to fit the widget, keeping `viewbox` centered.
+#[derive(Clone, Debug)]
+pub(super) struct Path {
+    /// (min x, min y, width, height) of the area shown, in path units, +Y being down.
+    pub(super) viewbox: [f32; 4],
+    pub(super) commands: Vec<PathCommand>,
+    /// How to fill the path, `None` to only stroke it.
+    pub(super) fill: Option<FillRule>,
+    pub(super) stroke: Option<Stroke>,
+}
+
+/// An element of the paths storage buffer. Depending on where it is, it is:
+/// - a header: `a` is the viewbox, `b` is (start, fill edge count | `PATH_EVEN_ODD`, circle count, quad count) as `u32`s.
+/// - a fill edge: `a` is (x0, y0, x1, y1).
+/// - a stroke circle: `a` is (center x, center y, radius, _).
+/// - a stroke quad: `a` and `b` are the four corners of a convex quadrilateral.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub(super) struct PathPrimitive {
+    a: [f32; 4],
+    b: [f32; 4],
+}
+
+impl Path {
+    /// An empty path showing `viewbox`, filled with the non-zero rule and without stroke.
+    pub(super) fn new(viewbox: [f32; 4]) -> Self {
+        Self {
+            viewbox,
+            commands: vec![],
+            fill: Some(FillRule::NonZero),
+            stroke: None,
+        }
+    }
+
+    pub(super) fn move_to(mut self, to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::MoveTo(to));
+        self
+    }
+
+    pub(super) fn line_to(mut self, to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::LineTo(to));
+        self
+    }
+
+    pub(super) fn quad_to(mut self, ctrl: [f32; 2], to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::QuadTo(ctrl, to));
+        self
+    }
+
+    pub(super) fn cubic_to(mut self, ctrl1: [f32; 2], ctrl2: [f32; 2], to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::CubicTo(ctrl1, ctrl2, to));
+        self
+    }
+
+    pub(super) fn arc_to(mut self, radii: [f32; 2], rotation: f32, large_arc: bool, sweep: bool, to: [f32; 2]) -> Self {
+        self.commands.push(PathCommand::ArcTo { radii, rotation, large_arc, sweep, to });
+        self
+    }
+
+    pub(super) fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    pub(super) fn with_fill(mut self, fill: Option<FillRule>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub(super) fn with_stroke(mut self, stroke: Option<Stroke>) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    /// The subpaths as polylines, with whether they are closed.
+    pub(super) fn flatten(&self) -> Vec<(Vec<[f32; 2]>, bool)> {
+        let mut subpaths = vec![];
+        let mut points: Vec<[f32; 2]> = vec![];
+        let mut current = [0.0, 0.0];
+        let mut start = [0.0, 0.0];
+        let mut end_subpath = |points: &mut Vec<[f32; 2]>, closed: bool| {
+            if points.len() > 1 {
+                subpaths.push((std::mem::take(points), closed));
+            }
+            points.clear();
+        };
+
+        for command in &self.commands {
+            if points.is_empty() && !matches!(command, PathCommand::MoveTo(_)) {
+                points.push(current);
+            }
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    end_subpath(&mut points, false);
+                    points.push(to);
+                    start = to;
+                    current = to;
+                }
+                PathCommand::LineTo(to) => {
+                    points.push(to);
+                    current = to;
+                }
+                PathCommand::QuadTo(c, to) => {
+                    let n = segment_count(distance(current, c) + distance(c, to));
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let u = 1.0 - t;
+                        points.push(combine(&[(current, u * u), (c, 2.0 * u * t), (to, t * t)]));
+                    }
+                    current = to;
+                }
+                PathCommand::CubicTo(c1, c2, to) => {
+                    let n = segment_count(distance(current, c1) + distance(c1, c2) + distance(c2, to));
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let u = 1.0 - t;
+                        points.push(combine(&[(current, u * u * u), (c1, 3.0 * u * u * t), (c2, 3.0 * u * t * t), (to, t * t * t)]));
+                    }
+                    current = to;
+                }
+                PathCommand::ArcTo { radii, rotation, large_arc, sweep, to } => {
+                    flatten_arc(&mut points, current, radii, rotation, large_arc, sweep, to);
+                    current = to;
+                }
+                PathCommand::Close => {
+                    end_subpath(&mut points, true);
+                    current = start;
+                }
+            }
+        }
+        end_subpath(&mut points, false);
+        subpaths
+    }
+
+    /// Encode the path as its header and its primitives, which start at index `start` of the paths buffer.
+    pub(super) fn encode(&self, start: u32) -> (PathPrimitive, Vec<PathPrimitive>) {
+        let subpaths = self.flatten();
+
+        let mut edges = vec![];
+        if self.fill.is_some() {
+            for (points, _) in &subpaths {
+                // Filled subpaths are always closed
+                for (i, &p0) in points.iter().enumerate() {
+                    let p1 = points[(i + 1) % points.len()];
+                    edges.push(PathPrimitive { a: [p0[0], p0[1], p1[0], p1[1]], b: [0.0; 4] });
+                }
+            }
+        }
+        let (circles, quads) = match &self.stroke {
+            Some(stroke) => stroke_primitives(&subpaths, stroke),
+            None => (vec![], vec![]),
+        };
+
+        let even_odd = if self.fill == Some(FillRule::EvenOdd) { PATH_EVEN_ODD } else { 0 };
+        let header = PathPrimitive {
+            a: self.viewbox,
+            b: [
+                f32::from_bits(start),
+                f32::from_bits(edges.len() as u32 | even_odd),
+                f32::from_bits(circles.len() as u32),
+                f32::from_bits(quads.len() as u32),
+            ],
+        };
+        let mut primitives = edges;
+        primitives.extend(circles);
+        primitives.extend(quads);
+        (header, primitives)
+    }
+}
+
+/// Encode `paths` for the paths storage buffer: one header per path, indexed by `ty[1]` of path widgets, then their data.
+pub(super) fn encode_paths(paths: &[Path]) -> Vec<PathPrimitive> {
+    let mut headers = vec![];
+    let mut data = vec![];
+    for path in paths {
+        let (header, primitives) = path.encode((paths.len() + data.len()) as u32);
+        headers.push(header);
+        data.extend(primitives);
+    }
+    headers.extend(data);
+    if headers.is_empty() {
+        // Bindings can't be empty
+        headers.push(PathPrimitive::zeroed());
+    }
+    headers
+}
+
+/// The circles and convex quads whose union is the stroke of `subpaths`.
+fn stroke_primitives(subpaths: &[(Vec<[f32; 2]>, bool)], stroke: &Stroke) -> (Vec<PathPrimitive>, Vec<PathPrimitive>) {
+    let hw = stroke.width * 0.5;
+    let mut circles = vec![];
+    let mut quads = vec![];
+    let circle = |p: [f32; 2]| PathPrimitive { a: [p[0], p[1], hw, 0.0], b: [0.0; 4] };
+    let quad = |p: [[f32; 2]; 4]| PathPrimitive { a: [p[0][0], p[0][1], p[1][0], p[1][1]], b: [p[2][0], p[2][1], p[3][0], p[3][1]] };
+
+    for (points, closed) in subpaths {
+        let mut points = points.clone();
+        points.dedup();
+        if *closed && points.len() > 2 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 2 {
+            continue;
+        }
+        let n = points.len();
+        let segments = if *closed { n } else { n - 1 };
+        let dir = |i: usize| normalize(sub(points[(i + 1) % n], points[i]));
+
+        for i in 0..segments {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            let o = scale(perp(dir(i)), hw);
+            quads.push(quad([add(a, o), add(b, o), sub(b, o), sub(a, o)]));
+        }
+
+        // Joins between consecutive segments
+        let joins = if *closed { 0..n } else { 1..n - 1 };
+        for i in joins {
+            let p = points[i];
+            let (d0, d1) = (dir((i + n - 1) % n), dir(i));
+            let turn = cross(d0, d1);
+            if turn.abs() < 1e-6 && dot(d0, d1) > 0.0 {
+                continue;
+            }
+            match stroke.join {
+                LineJoin::Round => circles.push(circle(p)),
+                LineJoin::Miter | LineJoin::Bevel => {
+                    // Offsets to the outer side of the turn
+                    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+                    let o0 = scale(perp(d0), side * hw);
+                    let o1 = scale(perp(d1), side * hw);
+                    let bisector = normalize(add(o0, o1));
+                    let cos_half = dot(bisector, scale(o0, 1.0 / hw));
+                    if stroke.join == LineJoin::Miter && cos_half > 1e-6 && 1.0 / cos_half <= stroke.miter_limit {
+                        let miter = add(p, scale(bisector, hw / cos_half));
+                        quads.push(quad([p, add(p, o0), miter, add(p, o1)]));
+                    }
+                    else {
+                        quads.push(quad([p, add(p, o0), add(p, o1), add(p, o1)]));
+                    }
+                }
+            }
+        }
+
+        if !*closed {
+            for (p, d) in [(points[0], scale(dir(0), -1.0)), (points[n - 1], dir(n - 2))] {
+                match stroke.cap {
+                    LineCap::Butt => {}
+                    LineCap::Round => circles.push(circle(p)),
+                    LineCap::Square => {
+                        let o = scale(perp(d), hw);
+                        let e = scale(d, hw);
+                        quads.push(quad([add(p, o), add(add(p, o), e), add(sub(p, o), e), sub(p, o)]));
+                    }
+                }
+            }
+        }
+    }
+    (circles, quads)
+}
+
+/// Flatten an SVG endpoint-parameterized arc, see https://www.w3.org/TR/SVG/implnote.html#ArcConversionEndpointToCenter
+fn flatten_arc(points: &mut Vec<[f32; 2]>, from: [f32; 2], radii: [f32; 2], rotation: f32, large_arc: bool, sweep: bool, to: [f32; 2]) {
+    let (mut rx, mut ry) = (radii[0].abs(), radii[1].abs());
+    if rx == 0.0 || ry == 0.0 || from == to {
+        points.push(to);
+        return;
+    }
+    let (sin, cos) = rotation.sin_cos();
+    let dx = (from[0] - to[0]) * 0.5;
+    let dy = (from[1] - to[1]) * 0.5;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Scale the radii up if they can't reach the end point
+    let lambda = x1 * x1 / (rx * rx) + y1 * y1 / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut k = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        k = -k;
+    }
+    let cx1 = k * rx * y1 / ry;
+    let cy1 = -k * ry * x1 / rx;
+    let center = [
+        cos * cx1 - sin * cy1 + (from[0] + to[0]) * 0.5,
+        sin * cx1 + cos * cy1 + (from[1] + to[1]) * 0.5,
+    ];
+
+    let angle = |u: [f32; 2]| u[1].atan2(u[0]);
+    let start = angle([(x1 - cx1) / rx, (y1 - cy1) / ry]);
+    let mut delta = angle([(-x1 - cx1) / rx, (-y1 - cy1) / ry]) - start;
+    if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    } else if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    }
+
+    let n = segment_count(rx.max(ry) * delta.abs());
+    for i in 1..=n {
+        let theta = start + delta * i as f32 / n as f32;
+        let (x, y) = (rx * theta.cos(), ry * theta.sin());
+        points.push([center[0] + cos * x - sin * y, center[1] + sin * x + cos * y]);
+    }
+    // Avoid accumulating error on the end point
+    *points.last_mut().unwrap() = to;
+}
+
+/// Number of segments to flatten a curve of approximate length `length` within the tolerance.
+fn segment_count(length: f32) -> usize {
+    ((length / FLATTEN_TOLERANCE).sqrt().ceil() as usize).clamp(1, 64)
+}
+
+fn combine(terms: &[([f32; 2], f32)]) -> [f32; 2] {
+    terms.iter().fold([0.0, 0.0], |acc, &(p, w)| [acc[0] + p[0] * w, acc[1] + p[1] * w])
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn scale(a: [f32; 2], k: f32) -> [f32; 2] {
+    [a[0] * k, a[1] * k]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn cross(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn perp(a: [f32; 2]) -> [f32; 2] {
+    [-a[1], a[0]]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    dot(sub(b, a), sub(b, a)).sqrt()
+}
+
+fn normalize(a: [f32; 2]) -> [f32; 2] {
+    let length = dot(a, a).sqrt();
+    if length > 0.0 { scale(a, 1.0 / length) } else { a }
+}
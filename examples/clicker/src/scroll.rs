@@ -0,0 +1,204 @@
+use web_time::Instant;
+
+/// Width in pixels of the scrollbars, along the right and bottom edges of scroll views.
+/// Must match `SCROLLBAR_WIDTH` in `w__fragment.wgsl`.
+pub(super) const SCROLLBAR_WIDTH: f32 = 8.0;
+/// Maximum depth of nested scroll views. Must match `MAX_NESTING` in the shaders.
+pub(super) const MAX_NESTING: usize = 8;
+/// Pixels scrolled per line of mouse wheel.
+pub(super) const WHEEL_LINE: f32 = 40.0;
+/// Distance in pixels a touch has to move before it scrolls instead of pressing.
+pub(super) const TOUCH_SLOP: f64 = 8.0;
+/// Rate at which the velocity of a flung scroll view decays, per second.
+const FRICTION: f32 = 4.0;
+/// Speed in NDC per second under which a flung scroll view stops.
+const MIN_VELOCITY: f32 = 0.01;
+
+/// Scroll offset of a scroll view, with the inertia left after it was flung.
+/// Offsets are in NDC, +X scrolling the content to the left and +Y scrolling it up.
+/// They are `f64`, the content of list views of millions of rows being too tall for a pixel to add up in `f32`.
+#[derive(Clone, Debug, Default)]
+pub(super) struct ScrollModel {
+    offset: [f64; 2],
+    /// The largest offsets, how far the content extends past the right and bottom of the view.
+    max: [f64; 2],
+    velocity: [f32; 2],
+    /// When the inertia was last applied, `None` when not moving.
+    last_step: Option<Instant>,
+}
+
+impl ScrollModel {
+    pub(super) fn new(max: [f64; 2]) -> Self {
+        Self { max, ..Default::default() }
+    }
+
+    pub(super) fn offset(&self) -> [f64; 2] {
+        self.offset
+    }
+
+    pub(super) fn max(&self) -> [f64; 2] {
+        self.max
+    }
+
+    /// Change the largest offsets, moving the offset into them.
+    pub(super) fn set_max(&mut self, max: [f64; 2]) {
+        self.max = max;
+        self.set_offset(self.offset);
+    }
+
+    /// Scroll to `offset`, clamped to the content. Returns whether the offset changed.
+    pub(super) fn set_offset(&mut self, offset: [f64; 2]) -> bool {
+        let offset = [offset[0].clamp(0.0, self.max[0]), offset[1].clamp(0.0, self.max[1])];
+        let changed = offset != self.offset;
+        self.offset = offset;
+        changed
+    }
+
+    /// Scroll by `delta`, stopping any inertia. Returns whether the offset changed.
+    pub(super) fn scroll_by(&mut self, delta: [f32; 2]) -> bool {
+        self.stop();
+        self.set_offset([self.offset[0] + f64::from(delta[0]), self.offset[1] + f64::from(delta[1])])
+    }
+
+    /// Keep scrolling at `velocity`, in NDC per second, slowing down until stopped.
+    pub(super) fn fling(&mut self, velocity: [f32; 2]) {
+        self.velocity = velocity;
+        self.last_step = Some(Instant::now());
+    }
+
+    pub(super) fn stop(&mut self) {
+        self.velocity = [0.0; 2];
+        self.last_step = None;
+    }
+
+    pub(super) fn is_moving(&self) -> bool {
+        self.last_step.is_some()
+    }
+
+    /// Apply the inertia until `now`. Returns whether the offset changed.
+    pub(super) fn step(&mut self, now: Instant) -> bool {
+        let Some(last) = self.last_step else { return false };
+        let dt = now.saturating_duration_since(last).as_secs_f32();
+        let offset = [0, 1].map(|i| self.offset[i] + f64::from(self.velocity[i] * dt));
+        let decay = (-FRICTION * dt).exp();
+        self.velocity = self.velocity.map(|v| v * decay);
+        let changed = self.set_offset(offset);
+        // Stop when slow enough, or against the end of the content
+        if !changed || self.velocity[0].hypot(self.velocity[1]) < MIN_VELOCITY {
+            self.stop();
+        } else {
+            self.last_step = Some(now);
+        }
+        changed
+    }
+
+    /// Where the scrollbar thumb of `axis` is along its track of `track` pixels, showing `view` of the content.
+    /// Returns its start and length in pixels, `None` if the content fits.
+    pub(super) fn thumb(&self, axis: usize, view: f32, track: f32) -> Option<(f32, f32)> {
+        let max = self.max[axis];
+        if max <= 0.0 {
+            return None;
+        }
+        let length = ((f64::from(track) * f64::from(view) / (f64::from(view) + max)) as f32).max(SCROLLBAR_WIDTH * 2.0).min(track);
+        Some(((f64::from(track - length) * self.offset[axis] / max) as f32, length))
+    }
+}
+
+/// What the pointer pressed in a scroll view drags.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum DragKind {
+    /// A touch that hasn't moved past `TOUCH_SLOP` yet, and may still be a press.
+    Pending,
+    /// The content, following the pointer.
+    Content,
+    /// The scrollbar thumb of an axis, 0 for horizontal and 1 for vertical.
+    Thumb(usize),
+}
+
+/// A pointer dragging in the scroll view `index`.
+pub(super) struct ScrollDrag {
+    pub(super) index: usize,
+    pub(super) kind: DragKind,
+    /// Where the pointer was pressed, in pixels.
+    pub(super) start: [f64; 2],
+    /// Where the pointer was last, in pixels.
+    pub(super) last: [f64; 2],
+    pub(super) last_time: Instant,
+    /// Smoothed speed of the content, in NDC per second, to fling it when released.
+    pub(super) velocity: [f32; 2],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn assert_offset(model: &ScrollModel, expected: [f64; 2]) {
+        let offset = model.offset();
+        assert!(offset.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-3), "{offset:?} isn't {expected:?}");
+    }
+
+    #[test]
+    fn clamping() {
+        let mut model = ScrollModel::new([1.0, 2.0]);
+        assert!(model.set_offset([-1.0, 5.0]));
+        assert_eq!(model.offset(), [0.0, 2.0]);
+        assert!(!model.set_offset([0.0, 3.0]));
+        // Shrinking the content moves the offset back into it
+        model.set_max([0.5, 1.0]);
+        assert_eq!(model.offset(), [0.0, 1.0]);
+        assert!(model.scroll_by([0.25, -0.5]));
+        assert_eq!(model.offset(), [0.25, 0.5]);
+        assert!(model.scroll_by([-1.0, 0.0]));
+        assert_eq!(model.offset(), [0.0, 0.5]);
+    }
+
+    #[test]
+    fn fling() {
+        let mut model = ScrollModel::new([0.0, 10.0]);
+        let now = Instant::now();
+        model.fling([0.0, 1.0]);
+        assert!(model.is_moving());
+        // Scrolled by the velocity, which decays by e^-FRICTION per second
+        assert!(model.step(now + Duration::from_secs(1)));
+        assert_offset(&model, [0.0, 1.0]);
+        assert!(model.is_moving());
+        assert!(model.step(now + Duration::from_secs(2)));
+        assert_offset(&model, [0.0, 1.0 + (-FRICTION as f64).exp()]);
+        // Then slower than MIN_VELOCITY
+        assert!(!model.is_moving());
+        assert!(!model.step(now + Duration::from_secs(3)));
+
+        // Scrolling stops the inertia
+        model.fling([0.0, 1.0]);
+        model.scroll_by([0.0, 0.1]);
+        assert!(!model.is_moving());
+    }
+
+    #[test]
+    fn fling_to_the_end() {
+        let mut model = ScrollModel::new([0.0, 0.5]);
+        let now = Instant::now();
+        model.fling([0.0, 10.0]);
+        assert!(model.step(now + Duration::from_millis(100)));
+        assert_eq!(model.offset(), [0.0, 0.5]);
+        // Stopped against the end of the content, however fast
+        assert!(!model.step(now + Duration::from_millis(200)));
+        assert!(!model.is_moving());
+    }
+
+    #[test]
+    fn thumb() {
+        let mut model = ScrollModel::new([0.0, 1.0]);
+        assert_eq!(model.thumb(0, 1.0, 100.0), None);
+        // Half of the content in view
+        assert_eq!(model.thumb(1, 1.0, 100.0), Some((0.0, 50.0)));
+        model.set_offset([0.0, 1.0]);
+        assert_eq!(model.thumb(1, 1.0, 100.0), Some((50.0, 50.0)));
+        // Never shorter than two scrollbar widths, or longer than its track
+        model.set_max([0.0, 1000.0]);
+        model.set_offset([0.0, 100.0]);
+        assert_eq!(model.thumb(1, 1.0, 100.0), Some((8.4, SCROLLBAR_WIDTH * 2.0)));
+        assert_eq!(model.thumb(1, 1.0, 10.0).map(|(_, length)| length), Some(10.0));
+    }
+}
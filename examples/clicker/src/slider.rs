@@ -0,0 +1,118 @@
+/// Set in `ty[2]` of a slider laid out vertically, its minimum being at the bottom.
+/// Must match `SLIDER_VERTICAL` in `w__fragment.wgsl`.
+pub(super) const SLIDER_VERTICAL: u32 = 1;
+/// Set in `ty[2]` of a range slider when its high thumb is the one moved by the keyboard.
+/// Must match `SLIDER_SECOND_THUMB` in `w__fragment.wgsl`.
+pub(super) const SLIDER_SECOND_THUMB: u32 = 1 << 1;
+/// Shift of the number of tick marks in `ty[2]` of a slider.
+pub(super) const SLIDER_TICKS_SHIFT: u32 = 8;
+
+#[derive(Copy, Clone, Debug)]
+pub(super) enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// Value of a slider or of the two thumbs of a range slider, with its bounds.
+#[derive(Clone, Debug)]
+pub(super) struct SliderModel {
+    min: f32,
+    max: f32,
+    /// Values snap to multiples of `step` from `min`, 0 for continuous values.
+    step: f32,
+    /// The value of a slider is `values[0]`. The values of a range slider are low then high.
+    values: [f32; 2],
+    thumbs: usize,
+    /// The thumb moved by the keyboard, the last one dragged.
+    active: usize,
+}
+
+impl SliderModel {
+    /// A continuous slider from 0 to 1, its thumbs at the ends.
+    pub(super) fn new(range: bool) -> Self {
+        Self {
+            min: 0.0,
+            max: 1.0,
+            step: 0.0,
+            values: [0.0, 1.0],
+            thumbs: if range { 2 } else { 1 },
+            active: 0,
+        }
+    }
+
+    /// The values of the thumbs.
+    pub(super) fn values(&self) -> &[f32] {
+        &self.values[..self.thumbs]
+    }
+
+    pub(super) fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Change the bounds and step, moving the values into them.
+    pub(super) fn set_bounds(&mut self, min: f32, max: f32, step: f32) {
+        self.min = min;
+        self.max = max.max(min);
+        self.step = step.max(0.0);
+        for thumb in 0..self.thumbs {
+            self.set(thumb, self.values[thumb]);
+        }
+    }
+
+    /// Move `thumb` to `value`, snapped to the step and kept in order with the other thumb.
+    /// Returns whether the value changed.
+    pub(super) fn set(&mut self, thumb: usize, value: f32) -> bool {
+        let mut value = value.clamp(self.min, self.max);
+        if self.step > 0.0 {
+            value = (self.min + ((value - self.min) / self.step).round() * self.step).min(self.max);
+        }
+        if self.thumbs == 2 {
+            value = if thumb == 0 { value.min(self.values[1]) } else { value.max(self.values[0]) };
+        }
+        let changed = self.values[thumb] != value;
+        self.values[thumb] = value;
+        self.active = thumb;
+        changed
+    }
+
+    /// Move the active thumb by `steps` steps, or by hundredths of the range for continuous sliders.
+    pub(super) fn step_by(&mut self, steps: f32) -> bool {
+        let step = if self.step > 0.0 { self.step } else { (self.max - self.min) / 100.0 };
+        self.set(self.active, self.values[self.active] + steps * step)
+    }
+
+    /// Move the active thumb to the minimum or the maximum.
+    pub(super) fn set_to_end(&mut self, max: bool) -> bool {
+        self.set(self.active, if max { self.max } else { self.min })
+    }
+
+    /// The value at `fraction` of the track, from 0 at the minimum to 1 at the maximum.
+    pub(super) fn value_at(&self, fraction: f32) -> f32 {
+        self.min + fraction.clamp(0.0, 1.0) * (self.max - self.min)
+    }
+
+    /// The thumb closest to `value`. Between equal thumbs, the one that can move toward it.
+    pub(super) fn nearest_thumb(&self, value: f32) -> usize {
+        if self.thumbs == 1 {
+            return 0;
+        }
+        let [low, high] = self.values;
+        if low == high {
+            return if value > high { 1 } else { 0 };
+        }
+        if (value - low).abs() <= (value - high).abs() { 0 } else { 1 }
+    }
+
+    /// The filled part of the track as fractions packed as `ty[1]`: `start | end << 16` in 1/65535ths.
+    pub(super) fn ty(&self) -> u32 {
+        let fraction = |value: f32| {
+            let range = self.max - self.min;
+            let f = if range > 0.0 { (value - self.min) / range } else { 0.0 };
+            (f.clamp(0.0, 1.0) * 65535.0).round() as u32
+        };
+        match self.thumbs {
+            1 => fraction(self.values[0]) << 16,
+            _ => fraction(self.values[0]) | fraction(self.values[1]) << 16,
+        }
+    }
+}
@@ -1,8 +1,33 @@
-use std::{cell::OnceCell, iter, mem, sync::Arc};
+use std::{cell::{OnceCell, RefCell}, collections::{HashMap, HashSet}, iter, mem, ops::Range, rc::Rc, slice, sync::Arc};
 
 use bytemuck::{Pod, Zeroable};
 use wgpu::util::DeviceExt;
-use winit::{dpi::PhysicalPosition, event::{ElementState, MouseButton, Touch, TouchPhase, WindowEvent}, window::Window};
+use winit::{dpi::{PhysicalPosition, PhysicalSize}, event::{ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent}, keyboard::{Key, ModifiersState, NamedKey}, window::{CursorIcon, Window}};
+
+use crate::{atlas::{Atlas, AtlasError, ImageFilter, ImageFit, ImageRegion, MAX_IMAGES}, backdrop::{self, Backdrop}, canvas::{Canvas, CanvasModel, CanvasPrimitive, HIT_REGION_SHIFT, INITIAL_PRIMITIVES}, chart::{ChartDrag, ChartHeader, ChartModel, Plot, Series, CHART_HOVER_RADIUS, CHART_ZOOM_SPEED, INITIAL_POINTS, MAX_CHART_SERIES}, clipboard::{self, Clipboard}, color::{fraction, pack_rgba, parse_hex, to_hex, unpack_rgba, ColorChannel, ColorPicker, ColorPickerLayout, Hsva, PickerShape, COLOR_CHANNEL_SHIFT, COLOR_VERTICAL, COLOR_WHEEL}, custom::{CustomType, CustomWidgets, ParamValue}, dialog::{DialogFuture, DialogLayout, DialogParts, DialogResult, Modal}, dock::{is_empty, Dock, DockDrag, DockError, DockLayout, DockNode, DIVIDER_VERTICAL, DOCK_TAB_BARS}, event::WidgetEvent, flex::FlexNode, font::{FontError, Fonts}, list::{DataSource, ItemLayout, VirtualView}, menu::{popup_offset, Accelerator, PopupAnchor, COMBO_OPEN, MENU_SEPARATOR, MENU_SUBMENU}, overlay::{popover_offset, tooltip_offset, Side, POPOVER_GAP, TOOLTIP_DELAY, TOOLTIP_OFFSET}, path::{self, Path}, scroll::{DragKind, ScrollDrag, ScrollModel, MAX_NESTING, SCROLLBAR_WIDTH, TOUCH_SLOP, WHEEL_LINE}, slider::{Orientation, SliderModel, SLIDER_SECOND_THUMB, SLIDER_TICKS_SHIFT, SLIDER_VERTICAL}, table::{Column, ColumnDrag, SortOrder, Table, TableLayout, TableRows, TableSource, MIN_COLUMN_WIDTH, RESIZE_GRIP}, style::{StyleSet, STATE_DISABLED, STATE_FOCUSED, STATE_HIDDEN, STATE_HOVERED, STATE_PRESSED, STYLE_INDEX_MASK}, tab::{TAB_BAR_SHIFT, TAB_SELECTED}, text::{TextEdit, TEXT_CELL_ASPECT, TEXT_FOCUSED, TEXT_PADDING}, toggle::{CheckState, Transition, CHECK_STATE_MASK, RADIO_GROUP_SHIFT}, tree::{DropPosition, NodeId, SelectMode, TreeDrag, TreeModel, TreeRows, TreeSource, TREE_INDENT}, viewport::{Viewport, ViewportEvent, ViewportModel}};
+
+/// Set in the id of the vertices of a shadow quad, drawn just before the quad of its widget.
+/// Must match `SHADOW_BIT` in the shaders.
+const SHADOW_BIT: u32 = 0x8000_0000;
+/// Set in the id of the vertices of the quad drawing the scrollbars of a scroll view, after its content.
+/// Must match `SCROLLBAR_BIT` in the shaders.
+const SCROLLBAR_BIT: u32 = 0x4000_0000;
+/// Set in the id of the vertices of the full screen quad dimming the widgets below a dialog, drawn just before it.
+/// Must match `SCRIM_BIT` in the shaders.
+const SCRIM_BIT: u32 = 0x2000_0000;
+/// Set in the id of the vertices of the quads drawing the series of a chart, after it, instanced over their points.
+/// Their position holds the slot of their series instead, the last slot drawing its legend and readout.
+/// Must match `SERIES_BIT` in the shaders.
+const SERIES_BIT: u32 = 0x1000_0000;
+/// Set in the id of the vertices of the quad drawing the primitives of a canvas, after it, instanced over them.
+/// Must match `PRIMITIVE_BIT` in the shaders.
+const PRIMITIVE_BIT: u32 = 0x0800_0000;
+/// Set in `ty[2]` of a progress bar whose progress is unknown, animating it instead.
+/// Must match `PROGRESS_INDETERMINATE` in `w__fragment.wgsl`.
+const PROGRESS_INDETERMINATE: u32 = 1;
+/// Set in `ty[2]` of an image widget to sample it with the nearest filter.
+/// Must match `IMAGE_NEAREST` in `w__fragment.wgsl`.
+const IMAGE_NEAREST: u32 = 1 << 8;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -24,11 +49,63 @@ impl Vertex {
 }
 
 
+/// A quad of the index buffer drawn on its own.
+#[derive(Copy, Clone, Debug)]
+enum SplitQuad {
+    /// The quad of the series `slot` of the chart `chart`, instanced over its points.
+    Series { chart: usize, slot: usize },
+    /// The quad of the viewport widget `index`, with its texture in place of the atlas.
+    Viewport(usize),
+    /// The quad of the canvas widget `index`, instanced over its primitives, or of the text input `index`, over its glyphs.
+    Canvas(usize),
+}
+
+/// Where the data buffer holds the chart headers, the points of their series and the canvas primitives,
+/// read by both shaders as `vec4`s. Must match `packed` in the shaders.
+#[derive(Copy, Clone, Debug)]
+struct DataLayout {
+    /// Chart headers, at least one.
+    charts: usize,
+    /// Points it can hold, an even number.
+    points_capacity: usize,
+    /// Primitives it can hold.
+    canvas_capacity: usize,
+}
+
+impl DataLayout {
+    /// The header, the first `vec4` of the buffer: where the points then the primitives start, in `vec4`s.
+    const HEADER_SIZE: usize = mem::size_of::<[u32; 4]>();
+
+    fn chart_offset(&self, chart: usize) -> wgpu::BufferAddress {
+        (Self::HEADER_SIZE + chart * mem::size_of::<ChartHeader>()) as wgpu::BufferAddress
+    }
+
+    fn points_offset(&self) -> wgpu::BufferAddress {
+        self.chart_offset(self.charts)
+    }
+
+    fn canvas_offset(&self) -> wgpu::BufferAddress {
+        self.points_offset() + (self.points_capacity * mem::size_of::<[f32; 2]>()) as wgpu::BufferAddress
+    }
+
+    fn size(&self) -> wgpu::BufferAddress {
+        self.canvas_offset() + (self.canvas_capacity * mem::size_of::<CanvasPrimitive>()) as wgpu::BufferAddress
+    }
+
+    fn header(&self) -> [u32; 4] {
+        let vec4 = Self::HEADER_SIZE as wgpu::BufferAddress;
+        [(self.points_offset() / vec4) as u32, (self.canvas_offset() / vec4) as u32, 0, 0]
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct General {
     resolution: [u32; 2],
     resized: [u32; 2],
+    /// Seconds since the state was created, animating widgets in the shaders.
+    time: f32,
+    _padding: [f32; 3],
 }
 
 #[repr(C)]
@@ -38,7 +115,15 @@ pub(super) struct Widget {
     pub(super) limits: [f32; 4],
     /// The type of widget, with parameters.
     /// Interpreted in the shaders.
+    /// `ty[3]` is the index of the widget's `StyleSet`, ORed with the `STATE_*` flags.
     pub(super) ty: [u32; 4],
+    /// Index + 1 of the scroll view or popup clipping and moving the widget, 0 if it isn't in one.
+    parent: u32,
+    /// For list and grid views, how far down their items are placed from, in NDC.
+    /// The offset in `ty[2]` doesn't include it.
+    scroll_base: f32,
+    /// For scroll views, how far their content extends past their right and bottom, in NDC.
+    scroll_max: [f32; 2],
     // /// How resizing should be handled in the compute shader.
     // /// Interpreted in the shaders.
     // resize_type: u32,
@@ -52,20 +137,412 @@ impl Widget {
     /// - `limits` are (Xmin, Xmax ,Ymin, Ymax), are both the quad limits and the values used to draw in the fragment shader.
     /// - `wt` is the type of widget.
     pub(super) fn new(limits: [f32; 4], wt: WidgetType) -> Self  {
+        Self::from_ty(limits, [wt.ty(), 0, 0, 0])
+    }
+
+    fn from_ty(limits: [f32; 4], ty: [u32; 4]) -> Self {
         Self {
             limits,
-            ty: [wt.ty(), 0, 0, 0],
+            ty,
+            parent: 0,
+            scroll_base: 0.0,
+            scroll_max: [0.0; 2],
+        }
+    }
+
+    /// A scroll view, clipping and scrolling the widgets put in it with `in_scroll_view`.
+    pub(super) fn scroll_view(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::ScrollView)
+    }
+
+    /// Put the widget in the scroll view at `index`, which must come before it in the widgets.
+    /// Its limits are those it has when the scroll view isn't scrolled.
+    pub(super) fn in_scroll_view(mut self, index: usize) -> Self {
+        self.parent = index as u32 + 1;
+        self
+    }
+
+    /// The index of the scroll view or popup the widget is in, if any.
+    pub(super) fn parent(&self) -> Option<usize> {
+        self.parent.checked_sub(1).map(|p| p as usize)
+    }
+
+    /// A popup, hidden until opened, drawn and picked above the widgets that aren't in popups.
+    /// `limits` are where it is before being moved to where it opens, see `State::open_popup`.
+    /// Popups can't be in other widgets, and those opened from another popup must come after it.
+    pub(super) fn popup(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Popup).hidden()
+    }
+
+    /// A tooltip or popover, hidden until shown, drawn and picked above all the other widgets, popups included.
+    /// `limits` are where it is before being moved next to the pointer or its anchor, see `State::set_tooltip` and `State::show_popover`.
+    /// Widgets are put in it with `in_popup`. Overlays can't be in other widgets.
+    pub(super) fn overlay(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Overlay).hidden()
+    }
+
+    /// A modal dialog, hidden until shown with `State::show_dialog`, see `DialogLayout` for stock dialogs.
+    /// Widgets are put in it with `in_popup`. Dialogs can't be in other widgets.
+    pub(super) fn dialog(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Dialog).hidden()
+    }
+
+    /// Put the widget in the popup, overlay or dialog at `index`, which must come before it in the widgets.
+    /// Its limits are those it has before the popup is moved.
+    pub(super) fn in_popup(self, index: usize) -> Self {
+        self.in_scroll_view(index)
+    }
+
+    /// A tab of the tab bar `bar`, see `State::set_tab_page`. Selecting it deselects the others of its bar.
+    pub(super) fn tab(limits: [f32; 4], bar: u32, selected: bool) -> Self {
+        let selected = if selected { TAB_SELECTED } else { 0 };
+        Self::from_ty(limits, [WidgetType::Tab.ty(), selected | bar << TAB_BAR_SHIFT, 0, 0])
+    }
+
+    /// An item of a menu bar or of a menu, see `State::set_submenu` and `State::set_accelerator`.
+    pub(super) fn menu_item(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::MenuItem)
+    }
+
+    /// A line between the items of a menu, skipped by the keyboard.
+    pub(super) fn menu_separator(limits: [f32; 4]) -> Self {
+        Self::from_ty(limits, [WidgetType::MenuItem.ty(), MENU_SEPARATOR, 0, 0])
+    }
+
+    /// A combo box with its first option selected, see `State::set_combo_options`.
+    pub(super) fn combo_box(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::ComboBox)
+    }
+
+    /// A widget showing the image `image` of the atlas.
+    /// Its style's radii clip the corners, and its opacity applies to the image.
+    pub(super) fn image(limits: [f32; 4], image: u32, fit: ImageFit, filter: ImageFilter) -> Self {
+        let nearest = match filter {
+            ImageFilter::Nearest => IMAGE_NEAREST,
+            ImageFilter::Linear => 0,
+        };
+        Self::from_ty(limits, [WidgetType::Image.ty(), image, fit.ty() | nearest, 0])
+    }
+
+    /// A widget showing the path at index `path` of the paths given to `State::new`.
+    /// The path is filled with the fill of its style and stroked with its border color, its border width being ignored.
+    pub(super) fn path(limits: [f32; 4], path: u32) -> Self {
+        Self::from_ty(limits, [WidgetType::Path.ty(), path, 0, 0])
+    }
+
+    /// A checkbox in the state `state`.
+    pub(super) fn checkbox(limits: [f32; 4], state: CheckState) -> Self {
+        Self::from_ty(limits, [WidgetType::Checkbox.ty(), state.ty(), state.progress().to_bits(), 0])
+    }
+
+    /// A radio button of the group `group`, in which at most one radio button is checked.
+    pub(super) fn radio(limits: [f32; 4], group: u32, checked: bool) -> Self {
+        let state = if checked { CheckState::Checked } else { CheckState::Unchecked };
+        Self::from_ty(limits, [WidgetType::RadioButton.ty(), state.ty() | group << RADIO_GROUP_SHIFT, state.progress().to_bits(), 0])
+    }
+
+    /// A toggle switch, on if `on`.
+    pub(super) fn toggle(limits: [f32; 4], on: bool) -> Self {
+        let state = if on { CheckState::Checked } else { CheckState::Unchecked };
+        Self::from_ty(limits, [WidgetType::Toggle.ty(), state.ty(), state.progress().to_bits(), 0])
+    }
+
+    /// A slider with one thumb, from 0 to 1 until changed with `State::set_slider_bounds`.
+    pub(super) fn slider(limits: [f32; 4], orientation: Orientation) -> Self {
+        Self::new(limits, WidgetType::Slider).with_orientation(orientation)
+    }
+
+    /// A slider with a low and a high thumb, from 0 to 1 until changed with `State::set_slider_bounds`.
+    pub(super) fn range_slider(limits: [f32; 4], orientation: Orientation) -> Self {
+        Self::new(limits, WidgetType::RangeSlider).with_orientation(orientation)
+    }
+
+    fn with_orientation(mut self, orientation: Orientation) -> Self {
+        if let Orientation::Vertical = orientation {
+            self.ty[2] |= SLIDER_VERTICAL;
+        }
+        self
+    }
+
+    /// Draw `count` evenly spaced tick marks along the track of a slider, including both ends.
+    pub(super) fn with_ticks(mut self, count: u32) -> Self {
+        self.ty[2] = (self.ty[2] & !(0xffff << SLIDER_TICKS_SHIFT)) | (count.min(0xffff) << SLIDER_TICKS_SHIFT);
+        self
+    }
+
+    /// A progress bar filled up to `progress`, from 0 to 1, or animated if the progress is unknown.
+    pub(super) fn progress_bar(limits: [f32; 4], progress: Option<f32>) -> Self {
+        let mut widget = Self::new(limits, WidgetType::ProgressBar);
+        widget.set_progress(progress);
+        widget
+    }
+
+    /// An activity indicator, animated while shown.
+    pub(super) fn spinner(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Spinner)
+    }
+
+    /// The color area of a color picker, see `ColorPickerLayout`.
+    pub(super) fn color_area(limits: [f32; 4], shape: PickerShape) -> Self {
+        let wheel = if shape == PickerShape::Wheel { COLOR_WHEEL } else { 0 };
+        Self::from_ty(limits, [WidgetType::ColorArea.ty(), wheel, 0, 0])
+    }
+
+    /// A slider of `channel` of a color picker, see `ColorPickerLayout`.
+    pub(super) fn color_slider(limits: [f32; 4], channel: ColorChannel, orientation: Orientation) -> Self {
+        let vertical = if let Orientation::Vertical = orientation { COLOR_VERTICAL } else { 0 };
+        Self::from_ty(limits, [WidgetType::ColorSlider.ty(), 0, vertical | channel.ty() << COLOR_CHANNEL_SHIFT, 0])
+    }
+
+    /// A swatch of `color`, as RGBA from 0 to 1.
+    pub(super) fn swatch(limits: [f32; 4], color: [f32; 4]) -> Self {
+        Self::from_ty(limits, [WidgetType::Swatch.ty(), pack_rgba(color), 0, 0])
+    }
+
+    /// A custom widget of type `ty`, registered in `custom`, with the parameters `values`, see `CustomWidgets::pack`.
+    pub(super) fn custom(limits: [f32; 4], custom: &CustomWidgets, ty: CustomType, values: &[ParamValue]) -> Self {
+        let [params1, params2] = custom.pack(ty, values);
+        Self::from_ty(limits, [ty.ty(), params1, params2, 0])
+    }
+
+    /// A viewport without a scene, see `State::set_viewport`.
+    pub(super) fn viewport(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Viewport)
+    }
+
+    /// A chart without series, see `State::set_series`.
+    pub(super) fn chart(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Chart)
+    }
+
+    /// A canvas without drawing, see `State::set_canvas`.
+    pub(super) fn canvas(limits: [f32; 4]) -> Self {
+        Self::new(limits, WidgetType::Canvas)
+    }
+
+    /// The row of a tree view, see `WidgetType::TreeRow`.
+    pub(super) fn tree_row(limits: [f32; 4], ty: u32) -> Self {
+        Self::from_ty(limits, [WidgetType::TreeRow.ty(), ty, 0, 0])
+    }
+
+    fn set_progress(&mut self, progress: Option<f32>) {
+        match progress {
+            Some(progress) => {
+                self.ty[1] = progress.clamp(0.0, 1.0).to_bits();
+                self.ty[2] &= !PROGRESS_INDETERMINATE;
+            }
+            None => self.ty[2] |= PROGRESS_INDETERMINATE,
         }
     }
+
+    /// Whether the widget is animated by the shaders, needing the screen to be redrawn continuously.
+    pub(super) fn is_animated(&self) -> bool {
+        self.ty[0] == WidgetType::Spinner.ty()
+            || (self.ty[0] == WidgetType::ProgressBar.ty() && self.ty[2] & PROGRESS_INDETERMINATE != 0)
+    }
+
+    /// Paint the widget with the style set at `index` in the styles given to `State::new`.
+    pub(super) fn with_style(mut self, index: u32) -> Self {
+        self.ty[3] = (self.ty[3] & !STYLE_INDEX_MASK) | (index & STYLE_INDEX_MASK);
+        self
+    }
+
+    /// A widget that doesn't react to input, and is drawn with its disabled style.
+    pub(super) fn disabled(mut self) -> Self {
+        self.ty[3] |= STATE_DISABLED;
+        self
+    }
+
+    pub(super) fn is_disabled(&self) -> bool {
+        self.ty[3] & STATE_DISABLED != 0
+    }
+
+    /// A widget that isn't drawn nor picked, with the widgets in it.
+    pub(super) fn hidden(mut self) -> Self {
+        self.ty[3] |= STATE_HIDDEN;
+        self
+    }
+
+    pub(super) fn is_hidden(&self) -> bool {
+        self.ty[3] & STATE_HIDDEN != 0
+    }
+
+    /// Whether the widget is a checkbox, radio button or toggle.
+    pub(super) fn is_checkable(&self) -> bool {
+        [WidgetType::Checkbox.ty(), WidgetType::RadioButton.ty(), WidgetType::Toggle.ty()].contains(&self.ty[0])
+    }
+
+    /// The radio group of a radio button, `None` for other widgets.
+    fn radio_group(&self) -> Option<u32> {
+        (self.ty[0] == WidgetType::RadioButton.ty()).then_some(self.ty[1] >> RADIO_GROUP_SHIFT)
+    }
+
+    /// The tab bar of a tab, `None` for other widgets.
+    fn tab_bar(&self) -> Option<u32> {
+        (self.ty[0] == WidgetType::Tab.ty()).then_some(self.ty[1] >> TAB_BAR_SHIFT)
+    }
+
+    /// Whether the widget takes the focus with the tab key.
+    pub(super) fn is_focusable(&self) -> bool {
+        self.is_checkable() || [
+            WidgetType::TextInput.ty(),
+            WidgetType::Slider.ty(),
+            WidgetType::RangeSlider.ty(),
+            WidgetType::Tab.ty(),
+            WidgetType::ComboBox.ty(),
+            WidgetType::Button.ty(),
+            WidgetType::ColorArea.ty(),
+            WidgetType::ColorSlider.ty(),
+            WidgetType::Chart.ty(),
+            WidgetType::Viewport.ty(),
+        ].contains(&self.ty[0])
+    }
+
+    /// Whether the widget is a menu item that isn't a separator.
+    fn is_menu_item(&self) -> bool {
+        self.ty[0] == WidgetType::MenuItem.ty() && self.ty[1] & MENU_SEPARATOR == 0
+    }
+
 }
 
 pub(super) enum WidgetType {
     EllipticButton,
+    /// A single line text field.
+    /// - `ty[1]` is the caret column, ORed with `TEXT_FOCUSED` when focused.
+    /// - `ty[2]` is the range of columns of the IME preedit text, packed as `start | end << 16`.
+    TextInput,
+    /// A rectangle, ignoring the radii of its style.
+    Rect,
+    /// A rectangle with the per-corner radii of its style.
+    RoundedRect,
+    /// An image of the atlas, see `Widget::image`.
+    /// - `ty[1]` is the index of the image.
+    /// - `ty[2]` is the `ImageFit`, ORed with `IMAGE_NEAREST` for nearest filtering.
+    Image,
+    /// A vector path, see `Widget::path`.
+    /// - `ty[1]` is the index of the path.
+    Path,
+    /// A box, with a check mark when checked and a dash when indeterminate. Its border color is the checked color.
+    /// - `ty[1]` is the `CheckState`.
+    /// - `ty[2]` is the progress of the transition to the checked look, as `f32` bits.
+    Checkbox,
+    /// A circle, with a dot of the border color when checked. Checking one unchecks the others of its group.
+    /// - `ty[1]` is the `CheckState`, ORed with the radio group shifted by `RADIO_GROUP_SHIFT`.
+    /// - `ty[2]` is the progress of the transition to the checked look, as `f32` bits.
+    RadioButton,
+    /// A switch whose knob slides to the right when on, its track getting the border color.
+    /// - `ty[1]` is the `CheckState`.
+    /// - `ty[2]` is the progress of the transition to the on look, as `f32` bits.
+    Toggle,
+    /// A track filled with the border color up to its thumb.
+    /// - `ty[1]` is the position of the thumb on the track, in 1/65535ths, shifted left by 16.
+    /// - `ty[2]` is ORed from `SLIDER_VERTICAL`, and the number of tick marks shifted by `SLIDER_TICKS_SHIFT`.
+    Slider,
+    /// A track filled with the border color between its two thumbs.
+    /// - `ty[1]` is the position of the thumbs on the track, in 1/65535ths, packed as `low | high << 16`.
+    /// - `ty[2]` is like for `Slider`, ORed with `SLIDER_SECOND_THUMB` when the keyboard moves the high thumb.
+    RangeSlider,
+    /// A track of the fill color, filled with the border color up to the progress. Its radii round the track.
+    /// - `ty[1]` is the progress from 0 to 1, as `f32` bits.
+    /// - `ty[2]` is `PROGRESS_INDETERMINATE` when the progress is unknown, a segment sliding along the track instead.
+    ProgressBar,
+    /// A ring of the fill color, with a spinning arc of the border color.
+    Spinner,
+    /// A rectangle with the radii of its style, clipping and scrolling the widgets in it, with scrollbars of its border color.
+    /// - `ty[1]` and `ty[2]` are the horizontal and vertical scroll offsets in NDC, as `f32` bits.
+    ScrollView,
+    /// A header cell of a table, a rectangle with the radii of its style and a triangle of its border color when sorted.
+    /// - `ty[1]` is `SORT_ASCENDING` or `SORT_DESCENDING` when the table is sorted by its column, 0 otherwise.
+    TableHeader,
+    /// The background of a row of a tree view, with indentation guides and an arrow of its border color.
+    /// - `ty[1]` is the depth of the node, ORed with `TREE_EXPANDABLE` and `TREE_EXPANDED`,
+    ///   and the `DropPosition` shifted by `TREE_DROP_SHIFT` while dragging nodes over it.
+    TreeRow,
+    /// A rectangle with the radii of its style, drawn above the other widgets while open, moving and clipping the widgets in it.
+    /// - `ty[1]` and `ty[2]` are how far it is moved from its limits to where it opened in NDC, as `f32` bits,
+    ///   like the scroll offsets of `ScrollView`.
+    Popup,
+    /// A tab of a tab bar, a rectangle with the top radii of its style, underlined with its border color when selected.
+    /// - `ty[1]` is `TAB_SELECTED` when selected, ORed with its tab bar shifted by `TAB_BAR_SHIFT`.
+    Tab,
+    /// An item of a menu bar or a menu, a rectangle with the radii of its style, hovered while highlighted by the keyboard.
+    /// - `ty[1]` is `MENU_SUBMENU` for items with an arrow opening a submenu, or `MENU_SEPARATOR` for a line of the border color.
+    MenuItem,
+    /// A rectangle with the radii of its style, with an arrow of its border color showing its options below it.
+    /// - `ty[1]` is the index of the selected option.
+    /// - `ty[2]` is `COMBO_OPEN` while its options are shown, flipping the arrow.
+    ComboBox,
+    /// A tooltip or popover, like `Popup` but drawn in a pass of its own after all the other widgets.
+    /// - `ty[1]` and `ty[2]` are how far it is moved from its limits in NDC, as `f32` bits.
+    Overlay,
+    /// A rectangle with the radii of its style, with a focus ring when focused.
+    Button,
+    /// A rectangle with the radii of its style, shown above the other widgets with a scrim dimming them, holding widgets.
+    Dialog,
+    /// The colors of a color picker for its hue or value, see `PickerShape`, with a ring marking its color.
+    /// Its radii round the square.
+    /// - `ty[1]` is the hue in 1/65535ths, ORed with `COLOR_WHEEL` for a wheel.
+    /// - `ty[2]` is the saturation and value of the color in 1/65535ths, packed as `s | v << 16`.
+    ColorArea,
+    /// A track showing the range of a `ColorChannel`, with the radii of its style and a thumb at the color's.
+    /// - `ty[1]` is the color the track goes to, packed by `pack_rgba`, for the value and alpha channels.
+    /// - `ty[2]` is the position of the thumb in 1/65535ths, ORed with `COLOR_VERTICAL`,
+    ///   and with the channel shifted by `COLOR_CHANNEL_SHIFT`.
+    ColorSlider,
+    /// A rectangle with the radii and border of its style, filled with a color over a checkerboard.
+    /// - `ty[1]` is the color, packed by `pack_rgba`.
+    Swatch,
+    /// A plot of up to `MAX_CHART_SERIES` series of points, with a grid, axes and their values, in the background of its style.
+    /// The pointer and the keyboard zoom and pan it, and show the values of the point under the pointer.
+    /// - `ty[1]` is the index of its chart in the charts buffer, assigned by `State::new`.
+    Chart,
+    /// The texture a scene of the application is rendered to, with the radii, border and opacity of its style.
+    /// The pointer and the keyboard are forwarded to the scene, see `Viewport`.
+    Viewport,
+    /// A rectangle with the radii of its style, under the primitives of its `Canvas`, which aren't cut to the radii.
+    /// Its primitives report their hit regions to the ID buffer, in the bits above `HIT_REGION_SHIFT`.
+    Canvas,
+    /// A divider between the panels of a dock, a rectangle with the radii of its style and a grip of its border color.
+    /// - `ty[1]` is `DIVIDER_VERTICAL` between panels side by side.
+    Divider,
+    /// The background of a dock, a rectangle with the radii of its style holding its tabs, panels and dividers, see `DockLayout`.
+    /// Unlike scroll views and popups, it doesn't move the widgets in it: `ty[1]` and `ty[2]` stay 0.
+    Dock,
 }
 impl WidgetType {
     pub(super) fn ty(&self) -> u32 {
         match self {
             Self::EllipticButton => 0,
+            Self::TextInput => 1,
+            Self::Rect => 2,
+            Self::RoundedRect => 3,
+            Self::Image => 4,
+            Self::Path => 5,
+            Self::Checkbox => 6,
+            Self::RadioButton => 7,
+            Self::Toggle => 8,
+            Self::Slider => 9,
+            Self::RangeSlider => 10,
+            Self::ProgressBar => 11,
+            Self::Spinner => 12,
+            Self::ScrollView => 13,
+            Self::TableHeader => 14,
+            Self::TreeRow => 15,
+            Self::Popup => 16,
+            Self::Tab => 17,
+            Self::MenuItem => 18,
+            Self::ComboBox => 19,
+            Self::Overlay => 20,
+            Self::Button => 21,
+            Self::Dialog => 22,
+            Self::ColorArea => 23,
+            Self::ColorSlider => 24,
+            Self::Swatch => 25,
+            Self::Chart => 26,
+            Self::Viewport => 27,
+            Self::Canvas => 28,
+            Self::Divider => 29,
+            Self::Dock => 30,
         }
     }
 }
@@ -82,9 +559,32 @@ pub(super) struct State<'window> {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_indices: u32,
+    /// Index in the index buffer of the first quad of the overlays, drawn in their own pass after the other widgets.
+    overlay_start: u32,
+    /// Index in the index buffer and blur radius of the widgets with a backdrop blur, in drawing order.
+    backdrops: Vec<(u32, f32)>,
+    /// The range of the index buffer holding each dialog with the widgets in it, by widget index.
+    /// The shown ones are drawn in the order they were shown, see `draw_ranges`.
+    dialog_quads: Vec<(usize, Range<u32>)>,
+    /// Index in the index buffer of the quads drawn on their own, in drawing order.
+    split_quads: Vec<(u32, SplitQuad)>,
+    backdrop: Backdrop,
+
+    atlas: Atlas,
+    atlas_texture: wgpu::Texture,
+    /// The fonts of the text inputs, their glyphs rasterized into the atlas as they are drawn.
+    fonts: Fonts,
+    atlas_view: wgpu::TextureView,
+    nearest_sampler: wgpu::Sampler,
+    images_buffer: wgpu::Buffer,
+    /// Blurred backdrop and atlas, with their samplers.
+    textures_bind_group_layout: wgpu::BindGroupLayout,
+    textures_bind_group: wgpu::BindGroup,
 
     gen_buffer: wgpu::Buffer,
     gen_bind_group: wgpu::BindGroup,
+    /// When the state was created, the origin of `General::time`.
+    start: web_time::Instant,
 
     id_buffer: wgpu::Buffer,
     id_buffer_len: u64,
@@ -92,20 +592,129 @@ pub(super) struct State<'window> {
     id_bind_group_layout: wgpu::BindGroupLayout,
     id_bind_group: wgpu::BindGroup,
 
-    #[allow(unused)]
+    widgets: Vec<Widget>,
     widgets_buffer: wgpu::Buffer,
-    #[allow(unused)]
+    styles_buffer: wgpu::Buffer,
+    paths_buffer: wgpu::Buffer,
+    /// A `ChartHeader` per chart widget, the points of their series in the order of their headers,
+    /// then the primitives of all the canvases, placed by `data_layout`.
+    data_buffer: wgpu::Buffer,
+    data_layout: DataLayout,
+    widgets_bind_group_layout: wgpu::BindGroupLayout,
     widgets_bind_group: wgpu::BindGroup,
     
     pipeline: wgpu::RenderPipeline,
+    /// Number of samples per pixel, 1 if multisampling is disabled.
+    sample_count: u32,
+    /// Multisampled color target, resolved to the surface. `None` if multisampling is disabled.
+    msaa_view: Option<wgpu::TextureView>,
 
     resized: bool,
 
     cursor_pos: PhysicalPosition<f64>,
+    modifiers: ModifiersState,
+
+    /// Content of the text widgets, by widget index.
+    texts: HashMap<usize, TextEdit>,
+    /// Index of the widget under the cursor.
+    hovered: Option<usize>,
+    /// Index of the widget the pointer was pressed on, until it is released.
+    pressed: Option<usize>,
+    /// Index of the widget receiving keyboard input.
+    focused: Option<usize>,
+    clipboard: Box<dyn Clipboard>,
+    /// Values of the sliders and range sliders, by widget index.
+    sliders: HashMap<usize, SliderModel>,
+    /// Offsets of the scroll views, by widget index.
+    scrolls: HashMap<usize, ScrollModel>,
+    /// The pointer dragging in a scroll view, if any.
+    scroll_drag: Option<ScrollDrag>,
+    /// Whether the ID buffer must be redrawn with the next frame, after widgets moved.
+    ids_dirty: bool,
+    /// Whether the primitives of the canvases or text inputs changed, uploaded together at most once per frame.
+    primitives_dirty: bool,
+    /// Whether the pointer moved since the widget under it was picked, which is done at most once per frame.
+    pointer_moved: bool,
+    /// Running transitions of toggle widgets, by widget index.
+    transitions: HashMap<usize, Transition>,
+    /// The widgets animated by the shaders, see `Widget::is_animated`, kept as their `ty` is uploaded.
+    animated: HashSet<usize>,
+    /// Data sources and slots of the list and grid views, by widget index.
+    virtual_views: HashMap<usize, VirtualView>,
+    /// Tables, by the widget index of their header.
+    tables: HashMap<usize, Table>,
+    /// The pointer resizing a column of a table, if any.
+    column_drag: Option<ColumnDrag>,
+    /// The resizing cursor shown over the edges of header cells and the dividers of docks, and while resizing.
+    resize_cursor: Option<CursorIcon>,
+    /// Nodes of the tree views, by the widget index of their list view.
+    trees: HashMap<usize, Rc<RefCell<TreeModel>>>,
+    /// The mouse dragging nodes of a tree view, if any.
+    tree_drag: Option<TreeDrag>,
+    /// The popup opened by activating a widget, and where it opens, by widget index.
+    openers: HashMap<usize, (usize, PopupAnchor)>,
+    /// The popup opened by right clicking a widget or a widget in it, by widget index.
+    context_menus: HashMap<usize, usize>,
+    /// The popup holding the options of each combo box, by widget index.
+    combos: HashMap<usize, usize>,
+    /// Keys activating menu items, with the index of their item.
+    accelerators: Vec<(Accelerator, usize)>,
+    /// The open popups, topmost last, with the widget that opened each one.
+    open_popups: Vec<(usize, Option<usize>)>,
+    /// Index of the menu item highlighted by the cursor or the keyboard in the open menus.
+    menu_highlight: Option<usize>,
+    /// The widget shown while each tab is selected, by widget index.
+    tab_pages: HashMap<usize, usize>,
+    /// The overlay shown once the pointer rests on each widget, by widget index.
+    tooltips: HashMap<usize, usize>,
+    /// The widget with a tooltip under the pointer, with since when, `None` once pressed until the pointer leaves it.
+    tooltip_owner: Option<(usize, Option<web_time::Instant>)>,
+    /// Index of the shown tooltip.
+    tooltip: Option<usize>,
+    /// The overlay shown by activating each widget, and on which side of it, by widget index.
+    popovers: HashMap<usize, (usize, Side)>,
+    /// The shown popovers with their anchor and side, the last shown last.
+    open_popovers: Vec<(usize, usize, Side)>,
+    /// The buttons and input of the stock dialogs, by widget index.
+    dialogs: HashMap<usize, DialogParts>,
+    /// The shown dialogs, the topmost last.
+    modals: Vec<Modal>,
+    /// The color pickers, by the widget index of their panel.
+    color_pickers: HashMap<usize, ColorPicker>,
+    /// Series and views of the charts, by widget index.
+    charts: HashMap<usize, ChartModel>,
+    /// The mouse panning a chart, if any.
+    chart_drag: Option<ChartDrag>,
+    /// The custom widget types, packing their parameters.
+    custom: CustomWidgets,
+    /// The scenes of the viewports and their textures, by widget index.
+    viewports: HashMap<usize, ViewportModel>,
+    /// The viewport the pointer is over, or captured by pressing it.
+    pointer_viewport: Option<usize>,
+    /// The drawings of the canvases, by widget index.
+    canvases: HashMap<usize, CanvasModel>,
+    /// The glyphs of the text inputs, drawn like canvases after them in the canvas buffer, by widget index.
+    text_glyphs: HashMap<usize, CanvasModel>,
+    /// The canvas and hit region under the pointer.
+    hovered_region: Option<(usize, u32)>,
+    /// Arrangements of the docks, by the widget index of their background.
+    docks: HashMap<usize, Dock>,
+    /// The pointer dragging a divider or a tab of a dock, if any.
+    dock_drag: Option<DockDrag>,
+    /// The flexible layout placing widgets in the window, if any, laid out again when it is resized.
+    flex: Option<FlexNode>,
+    /// Events not taken by `take_events` yet.
+    events: Vec<WidgetEvent>,
 }
 
 impl<'window> State<'window> {
-    pub(super) async fn new(window: Arc<Window>, widgets: Vec<Widget>) -> Self {
+    /// - `styles` are referenced by index from the widgets, the default style set is used if empty.
+    /// - `atlas` holds the images shown by image widgets, more can be added with `add_image`.
+    /// - `paths` are the vector shapes shown by path widgets.
+    /// - `custom` are the custom widget types, their WGSL functions being composed into the fragment shader.
+    /// - `sample_count` is the number of MSAA samples per pixel, 1 to disable multisampling.
+    ///   Falls back to 1 if the surface format doesn't support it.
+    pub(super) async fn new(window: Arc<Window>, mut widgets: Vec<Widget>, mut styles: Vec<StyleSet>, mut atlas: Atlas, paths: &[Path], custom: CustomWidgets, sample_count: u32) -> Self {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::VULKAN,
@@ -126,7 +735,11 @@ impl<'window> State<'window> {
                 &wgpu::DeviceDescriptor {
                     label: None,
                     required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::downlevel_defaults(),
+                    // Widgets, styles, images, paths, chart and canvas data, and ids are all read by the fragment shader
+                    required_limits: wgpu::Limits {
+                        max_storage_buffers_per_shader_stage: 6,
+                        ..wgpu::Limits::downlevel_defaults()
+                    },
                 },
                 None, // Trace path
             )
@@ -147,20 +760,116 @@ impl<'window> State<'window> {
         };
         surface.configure(&device, &config);
 
+        let sample_count = if adapter.get_texture_format_features(format).flags.sample_count_supported(sample_count) {
+            sample_count
+        } else {
+            log::warn!("{}x MSAA is not supported by {:?}, disabling multisampling", sample_count, format);
+            1
+        };
+        let msaa_view = create_msaa_view(&device, &config, sample_count);
+
+
+        if styles.is_empty() {
+            styles.push(StyleSet::default());
+        }
+
+        let mut scrolls: HashMap<usize, ScrollModel> = HashMap::new();
+        for i in 0..widgets.len() {
+            if widgets[i].ty[0] == WidgetType::ScrollView.ty() {
+                widgets[i].scroll_max = scroll_extent(&widgets, i);
+                scrolls.insert(i, ScrollModel::new(widgets[i].scroll_max.map(f64::from)));
+            }
+        }
+        // Charts are numbered in widget order, indexing their headers
+        let mut charts: HashMap<usize, ChartModel> = HashMap::new();
+        for (i, w) in widgets.iter_mut().enumerate() {
+            if w.ty[0] == WidgetType::Chart.ty() {
+                w.ty[1] = charts.len() as u32;
+                charts.insert(i, ChartModel::new());
+            }
+        }
+        // Hit regions are above the widget index + 1 in the ID buffer
+        assert!(widgets.len() < 1 << HIT_REGION_SHIFT, "{} widgets don't leave room for the hit regions of canvases", widgets.len());
+        let canvases: HashMap<usize, CanvasModel> = widgets.iter().enumerate()
+            .filter(|(_, w)| w.ty[0] == WidgetType::Canvas.ty())
+            .map(|(i, _)| (i, CanvasModel::new()))
+            .collect();
+        let text_glyphs: HashMap<usize, CanvasModel> = widgets.iter().enumerate()
+            .filter(|(_, w)| w.ty[0] == WidgetType::TextInput.ty())
+            .map(|(i, _)| (i, CanvasModel::new()))
+            .collect();
+        // Scrollbars are drawn over the content, after the last widget in each scroll view
+        let mut last_descendant: HashMap<usize, usize> = scrolls.keys().map(|&i| (i, i)).collect();
+        for i in 0..widgets.len() {
+            for ancestor in ancestors(&widgets, i).filter(|a| scrolls.contains_key(a)) {
+                last_descendant.insert(ancestor, i);
+            }
+        }
+        // Dialogs and the widgets in them are drawn above the others, then popups, and overlays above everything in their own pass,
+        // so they are also picked first
+        let layers: Vec<u8> = (0..widgets.len()).map(|i| {
+            iter::once(i).chain(ancestors(&widgets, i)).map(|a| match widgets[a].ty[0] {
+                ty if ty == WidgetType::Dialog.ty() => 1,
+                ty if ty == WidgetType::Popup.ty() => 2,
+                ty if ty == WidgetType::Overlay.ty() => 3,
+                _ => 0,
+            }).max().unwrap()
+        }).collect();
+        // The widgets of each dialog together, so that dialogs can be drawn in the order they are shown
+        let dialog_of = |i: usize| iter::once(i).chain(ancestors(&widgets, i)).find(|&a| widgets[a].ty[0] == WidgetType::Dialog.ty());
+        let mut order: Vec<usize> = (0..widgets.len()).collect();
+        order.sort_by_key(|&i| (layers[i], if layers[i] == 1 { dialog_of(i) } else { None }));
 
         let mut vertices: Vec<Vertex> = vec![];
-        let mut indices: Vec<u16> = vec![];
-        for (i, w) in widgets.iter().enumerate() {
-            vertices.push(Vertex { position: [w.limits[0], w.limits[2]], id: i as u32 });
-            vertices.push(Vertex { position: [w.limits[0], w.limits[3]], id: i as u32 });
-            vertices.push(Vertex { position: [w.limits[1], w.limits[3]], id: i as u32 });
-            vertices.push(Vertex { position: [w.limits[1], w.limits[2]], id: i as u32 });
-            indices.push(4*i as u16);
-            indices.push(4*i as u16+1);
-            indices.push(4*i as u16+2);
-            indices.push(4*i as u16+0);
-            indices.push(4*i as u16+2);
-            indices.push(4*i as u16+3);
+        let mut indices: Vec<u32> = vec![];
+        let mut backdrops: Vec<(u32, f32)> = vec![];
+        let mut overlay_start = None;
+        let mut split_quads: Vec<(u32, SplitQuad)> = vec![];
+        let mut dialog_quads: Vec<(usize, Range<u32>)> = vec![];
+        for i in order {
+            let w = &widgets[i];
+            let first_index = indices.len() as u32;
+            let style = &styles[(w.ty[3] & STYLE_INDEX_MASK) as usize];
+            if layers[i] == 3 && overlay_start.is_none() {
+                overlay_start = Some(indices.len() as u32);
+            }
+            if w.ty[0] == WidgetType::Dialog.ty() {
+                let screen = Widget { limits: [-1.0, 1.0, -1.0, 1.0], ..*w };
+                push_quad(&mut vertices, &mut indices, &screen, i as u32 | SCRIM_BIT);
+            }
+            if style.has_shadow() {
+                push_quad(&mut vertices, &mut indices, w, i as u32 | SHADOW_BIT);
+            }
+            // The overlay pass comes after the backdrop is composited, so overlays can't blur it
+            if style.backdrop_blur() > 0.0 && layers[i] < 3 {
+                backdrops.push((indices.len() as u32, style.backdrop_blur()));
+            }
+            if w.ty[0] == WidgetType::Viewport.ty() {
+                split_quads.push((indices.len() as u32, SplitQuad::Viewport(i)));
+            }
+            push_quad(&mut vertices, &mut indices, w, i as u32);
+            if charts.contains_key(&i) {
+                for slot in 0..=MAX_CHART_SERIES {
+                    split_quads.push((indices.len() as u32, SplitQuad::Series { chart: i, slot }));
+                    push_series_quad(&mut vertices, &mut indices, i as u32 | SERIES_BIT, slot);
+                }
+            }
+            if canvases.contains_key(&i) || text_glyphs.contains_key(&i) {
+                split_quads.push((indices.len() as u32, SplitQuad::Canvas(i)));
+                push_series_quad(&mut vertices, &mut indices, i as u32 | PRIMITIVE_BIT, 0);
+            }
+            // Inner scroll views first, so that outer scrollbars are on top
+            for view in iter::once(i).chain(ancestors(&widgets, i)) {
+                if last_descendant.get(&view) == Some(&i) {
+                    push_quad(&mut vertices, &mut indices, &widgets[view], view as u32 | SCROLLBAR_BIT);
+                }
+            }
+            if let Some(dialog) = dialog_of(i).filter(|_| layers[i] == 1) {
+                match dialog_quads.last_mut() {
+                    Some((d, range)) if *d == dialog => range.end = indices.len() as u32,
+                    _ => dialog_quads.push((dialog, first_index..indices.len() as u32)),
+                }
+            }
         }
 
 
@@ -175,12 +884,16 @@ impl<'window> State<'window> {
             usage: wgpu::BufferUsages::INDEX,
         });
         let num_indices: u32 = indices.len() as u32;
+        let overlay_start = overlay_start.unwrap_or(num_indices);
         println!("{}", num_indices);
 
 
+        let start = web_time::Instant::now();
         let gen_info = General {
             resolution: [size.width, size.height],
             resized: [1, 0],
+            time: 0.0,
+            _padding: [0.0; 3],
         };
         let gen_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Gen Uniform Buffer"),
@@ -192,7 +905,7 @@ impl<'window> State<'window> {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -256,20 +969,80 @@ impl<'window> State<'window> {
         );
 
 
-        let widgets_info: Vec<Widget> = widgets;
         let widgets_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Widgets Storage Buffer"),
-            contents: bytemuck::cast_slice(widgets_info.as_slice()),
+            contents: bytemuck::cast_slice(widgets.as_slice()),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+        let styles_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Styles Storage Buffer"),
+            contents: bytemuck::cast_slice(styles.as_slice()),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        });
+        let images_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Images Storage Buffer"),
+            size: (MAX_IMAGES * mem::size_of::<ImageRegion>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&images_buffer, 0, bytemuck::cast_slice(atlas.regions()));
+        let paths_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Paths Storage Buffer"),
+            contents: bytemuck::cast_slice(&path::encode_paths(paths)),
             usage: wgpu::BufferUsages::STORAGE,
         });
+        let data_layout = DataLayout { charts: charts.len().max(1), points_capacity: INITIAL_POINTS, canvas_capacity: INITIAL_PRIMITIVES };
+        let data_buffer = create_data_buffer(&device, &queue, data_layout);
+        queue.write_buffer(&data_buffer, data_layout.chart_offset(0), bytemuck::cast_slice(&vec![ChartModel::new().header(); data_layout.charts]));
         let widgets_bind_group_layout = 
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // Series quads are placed by their points in the vertex shader, and canvas quads by their primitives
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
                         has_dynamic_offset: false,
                         min_binding_size: None,
                     },
@@ -278,18 +1051,44 @@ impl<'window> State<'window> {
             ],
             label: Some("widgets_bind_group_layout"),
         });
-        let widgets_bind_group = device.create_bind_group(
-            &wgpu::BindGroupDescriptor {
-                layout: &widgets_bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::Buffer(widgets_buffer.as_entire_buffer_binding()),
-                    },
-                ],
-                label: Some("widgets_bind_group"),
-            }
-        );
+        let widgets_bind_group = create_widgets_bind_group(&device, &widgets_bind_group_layout, [&widgets_buffer, &styles_buffer, &images_buffer, &paths_buffer, &data_buffer]);
+
+
+        let backdrop = Backdrop::new(&device, &config);
+
+        let atlas_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Atlas Texture"),
+            size: wgpu::Extent3d {
+                width: atlas.size(),
+                height: atlas.size(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (position, image) in atlas.take_pending() {
+            write_image(&queue, &atlas_texture, position, &image);
+        }
+        let atlas_view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let nearest_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Nearest Sampler"),
+            ..Default::default()
+        });
+        let textures_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                backdrop::texture_entry(0),
+                backdrop::sampler_entry(1),
+                backdrop::texture_entry(2),
+                backdrop::sampler_entry(3),
+            ],
+            label: Some("textures_bind_group_layout"),
+        });
+        let textures_bind_group = create_textures_bind_group(&device, &textures_bind_group_layout, &backdrop, &atlas_view, &nearest_sampler);
 
 
         let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -298,7 +1097,7 @@ impl<'window> State<'window> {
         });
         let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Fragment Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("w__fragment.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(custom.fragment_shader().into()),
         });
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
@@ -306,6 +1105,7 @@ impl<'window> State<'window> {
                 &gen_bind_group_layout,
                 &widgets_bind_group_layout,
                 &id_bind_group_layout,
+                &textures_bind_group_layout,
             ],
             push_constant_ranges: &[],
         });
@@ -332,7 +1132,10 @@ impl<'window> State<'window> {
                 ..Default::default()
             },
             depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
             multiview: None,
         });
 
@@ -341,7 +1144,17 @@ impl<'window> State<'window> {
         window.set_cursor_position(PhysicalPosition::new(500.0f64, 500.0f64)).unwrap();
         let cursor_pos: PhysicalPosition<f64> = PhysicalPosition::new(500.0f64, 500.0f64);
 
-        Self {
+        let texts: HashMap<usize, TextEdit> = widgets.iter().enumerate()
+            .filter(|(_, w)| w.ty[0] == WidgetType::TextInput.ty())
+            .map(|(i, _)| (i, TextEdit::default()))
+            .collect();
+        let sliders: HashMap<usize, SliderModel> = widgets.iter().enumerate()
+            .filter(|(_, w)| w.ty[0] == WidgetType::Slider.ty() || w.ty[0] == WidgetType::RangeSlider.ty())
+            .map(|(i, w)| (i, SliderModel::new(w.ty[0] == WidgetType::RangeSlider.ty())))
+            .collect();
+        let animated: HashSet<usize> = widgets.iter().enumerate().filter(|(_, w)| w.is_animated()).map(|(i, _)| i).collect();
+
+        let mut state = Self {
             instance,
             window,
             surface,
@@ -353,9 +1166,24 @@ impl<'window> State<'window> {
             vertex_buffer,
             index_buffer,
             num_indices,
+            overlay_start,
+            dialog_quads,
+            backdrops,
+            split_quads,
+            backdrop,
+
+            atlas,
+            atlas_texture,
+            fonts: Fonts::default(),
+            atlas_view,
+            nearest_sampler,
+            images_buffer,
+            textures_bind_group_layout,
+            textures_bind_group,
 
             gen_buffer,
             gen_bind_group,
+            start,
 
             id_buffer,
             id_buffer_len,
@@ -363,15 +1191,77 @@ impl<'window> State<'window> {
             id_bind_group_layout,
             id_bind_group,
             
+            widgets,
             widgets_buffer,
+            styles_buffer,
+            paths_buffer,
+            data_buffer,
+            data_layout,
+            widgets_bind_group_layout,
             widgets_bind_group,
 
             pipeline,
+            sample_count,
+            msaa_view,
 
             resized,
 
             cursor_pos,
+            modifiers: ModifiersState::empty(),
+
+            texts,
+            hovered: None,
+            pressed: None,
+            focused: None,
+            clipboard: clipboard::default_clipboard(),
+            sliders,
+            scrolls,
+            scroll_drag: None,
+            ids_dirty: false,
+            primitives_dirty: false,
+            pointer_moved: false,
+            virtual_views: HashMap::new(),
+            tables: HashMap::new(),
+            column_drag: None,
+            resize_cursor: None,
+            trees: HashMap::new(),
+            tree_drag: None,
+            openers: HashMap::new(),
+            context_menus: HashMap::new(),
+            combos: HashMap::new(),
+            accelerators: vec![],
+            open_popups: vec![],
+            menu_highlight: None,
+            tab_pages: HashMap::new(),
+            tooltips: HashMap::new(),
+            tooltip_owner: None,
+            tooltip: None,
+            popovers: HashMap::new(),
+            open_popovers: vec![],
+            dialogs: HashMap::new(),
+            modals: vec![],
+            color_pickers: HashMap::new(),
+            charts,
+            chart_drag: None,
+            custom,
+            viewports: HashMap::new(),
+            pointer_viewport: None,
+            canvases,
+            text_glyphs,
+            hovered_region: None,
+            docks: HashMap::new(),
+            dock_drag: None,
+            flex: None,
+            transitions: HashMap::new(),
+            animated,
+            events: vec![],
+        };
+        // Range sliders start with their high thumb at the end, which the widgets don't know yet
+        let sliders: Vec<usize> = state.sliders.keys().copied().collect();
+        for index in sliders {
+            state.sync_slider(index, false);
         }
+        state
     }
 
     pub(super) fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -381,11 +1271,21 @@ impl<'window> State<'window> {
         self.config.width = new_size.width.max(1);
         self.config.height = new_size.height.max(1);
         self.surface.configure(&self.device, &self.config);
+        self.msaa_view = create_msaa_view(&self.device, &self.config, self.sample_count);
+        self.backdrop.resize(&self.device, &self.config);
+        self.textures_bind_group = create_textures_bind_group(&self.device, &self.textures_bind_group_layout, &self.backdrop, &self.atlas_view, &self.nearest_sampler);
+        for viewport in self.viewports.values_mut() {
+            if let Some(view) = &viewport.view {
+                viewport.bind_group = Some(create_textures_bind_group(&self.device, &self.textures_bind_group_layout, &self.backdrop, view, &self.nearest_sampler));
+            }
+        }
 
         // Modify Gen buffer accordingly
         let gen_info = General {
             resolution: [self.size.width, self.size.height],
-            resized: [1, 0]
+            resized: [1, 0],
+            time: self.start.elapsed().as_secs_f32(),
+            _padding: [0.0; 3],
         };
         self.queue.write_buffer(&self.gen_buffer, 0, bytemuck::cast_slice(&[gen_info]));
         
@@ -427,76 +1327,2784 @@ impl<'window> State<'window> {
         }
 
         self.resized = true;
+        // Flexible layouts are in pixels, before the widgets they place are laid out
+        self.layout_flex();
+        // Tree views are indented by a number of pixels
+        let trees: Vec<usize> = self.trees.keys().copied().collect();
+        for index in trees {
+            self.trees[&index].borrow_mut().indent = TREE_INDENT * 2.0 / self.size.width as f32;
+            self.reload_items(index);
+        }
+        // Dividers are a number of pixels thick
+        let docks: Vec<usize> = self.docks.keys().copied().collect();
+        for index in docks {
+            self.layout_dock(index);
+        }
+        // Glyphs are as tall as text inputs in pixels
+        let texts: Vec<usize> = self.texts.keys().copied().collect();
+        for index in texts {
+            self.sync_text(index);
+        }
     }
 
     #[allow(unused)]
     pub(super) fn input(&mut self, event: &WindowEvent) -> bool {
+        // Other events act on the widget under the pointer where it is now
+        if !matches!(event, WindowEvent::CursorMoved { .. }) {
+            self.move_pointer();
+        }
         match event {
             WindowEvent::CursorMoved { device_id, position } => {
                 self.cursor_pos = *position;
+                self.pointer_moved = true;
+                self.window.request_redraw();
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.hover(None);
+                self.hover_region(None);
+                self.hover_tooltip();
+                self.hover_chart();
+                self.move_viewport_pointer();
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
             }
             WindowEvent::MouseInput {
-                state: ElementState::Pressed,
+                state,
                 button: MouseButton::Left,
                 ..
             } => {
-                self.click();
+                let pressed = *state == ElementState::Pressed;
+                self.viewport_button(MouseButton::Left, pressed);
+                self.press(pressed);
+                if pressed {
+                    self.begin_scroll_drag(false);
+                    self.begin_tree_drag();
+                } else {
+                    self.end_scroll_drag();
+                    self.end_tree_drag();
+                }
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Right,
+                ..
+            } => {
+                return self.viewport_button(MouseButton::Right, true) || self.open_context_menu();
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                return self.viewport_button(*button, *state == ElementState::Pressed);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [x * WHEEL_LINE, y * WHEEL_LINE],
+                    MouseScrollDelta::PixelDelta(position) => [position.x as f32, position.y as f32],
+                };
+                return self.viewport_wheel(delta) || self.zoom_chart(delta) || self.wheel(delta);
             }
             WindowEvent::Touch(Touch {
-                phase: TouchPhase::Started,
+                phase,
                 location,
                 ..
             })=> {
                 self.cursor_pos = *location;
 
-                self.click();
+                match phase {
+                    TouchPhase::Started => {
+                        let touched = self.pick();
+                        self.hover(touched);
+                        self.press(true);
+                        self.begin_scroll_drag(true);
+                    }
+                    TouchPhase::Moved => {
+                        self.drag_scroll();
+                        self.drag_chart();
+                        if let Some(index) = self.pressed {
+                            self.drag_slider(index, false);
+                            self.drag_color(index);
+                        }
+                    }
+                    TouchPhase::Ended | TouchPhase::Cancelled => {
+                        self.press(false);
+                        self.end_scroll_drag();
+                        self.hover(None);
+                    }
+                }
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                return self.menu_key(event) || self.popover_key(event) || self.button_key(event) || self.dialog_key(event)
+                    || self.focus_key(event) || self.accelerator_key(event) || self.tab_key(event)
+                    || self.toggle_key(event) || self.slider_key(event) || self.color_key(event) || self.chart_key(event) || self.viewport_key(event) || self.tree_key(event) || self.text_key(event);
+            }
+            WindowEvent::Ime(ime) => {
+                return self.text_ime(ime);
             }
             _ => {  }
         }
         false
     }
 
-    fn click(&mut self) {
-        let id_buffer_index = 4 * (self.cursor_pos.y as u64 * self.size.width as u64 + self.cursor_pos.x as u64);
-
-        let mapped_id_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Readable ID Buffer"),
-            size: 4,
-            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+    /// Decode an image and add it to the atlas, returning its index for `Widget::image`.
+    #[allow(unused)]
+    pub(super) fn add_image(&mut self, bytes: &[u8]) -> Result<u32, AtlasError> {
+        let image = self.atlas.add(bytes)?;
+        self.write_images(image as usize);
+        Ok(image)
+    }
 
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-        {
-            encoder.copy_buffer_to_buffer(
-                &self.id_buffer, 
-                id_buffer_index as u64, 
-                &mapped_id_buffer, 
-                0, 4
-            );
+    /// Parse a TrueType or OpenType font for the text inputs, falling back on it for the characters of the fonts
+    /// added before. Until a font is added, text inputs are drawn in the pixel font of canvases.
+    #[allow(unused)]
+    pub(super) fn add_font(&mut self, bytes: Vec<u8>) -> Result<(), FontError> {
+        self.fonts.add(bytes)?;
+        let texts: Vec<usize> = self.texts.keys().copied().collect();
+        for index in texts {
+            self.sync_text(index);
         }
-        self.queue.submit(iter::once(encoder.finish()));
-
-
-        mapped_id_buffer
-            .slice(..)
-            .map_async(wgpu::MapMode::Read, |result| {
-                OnceCell::new().set(result).unwrap();
-            });
-
-        self.device.poll(wgpu::Maintain::Wait);
+        Ok(())
+    }
 
-        let slice: &[u8] = &mut mapped_id_buffer.slice(..).get_mapped_range();
-        println!("{:?}", bytemuck::cast_slice::<u8, u32>(slice));
+    /// Set the nine-slice insets of the atlas image `image`, in pixels: left, top, right, bottom.
+    #[allow(unused)]
+    pub(super) fn set_image_insets(&mut self, image: u32, insets: [f32; 4]) {
+        self.atlas.set_insets(image, insets);
+        self.write_image_region(image);
+    }
+
+    /// Upload the images packed into the atlas since the image `first`, with their regions.
+    fn write_images(&mut self, first: usize) {
+        for (position, image) in self.atlas.take_pending() {
+            write_image(&self.queue, &self.atlas_texture, position, &image);
+        }
+        let regions = &self.atlas.regions()[first..];
+        if regions.is_empty() {
+            return;
+        }
+        self.queue.write_buffer(
+            &self.images_buffer,
+            (first * mem::size_of::<ImageRegion>()) as wgpu::BufferAddress,
+            bytemuck::cast_slice(regions),
+        );
+        self.window.request_redraw();
+    }
+
+    fn write_image_region(&mut self, image: u32) {
+        let region = &self.atlas.regions()[image as usize];
+        self.queue.write_buffer(
+            &self.images_buffer,
+            (image as usize * mem::size_of::<ImageRegion>()) as wgpu::BufferAddress,
+            bytemuck::cast_slice(std::slice::from_ref(region)),
+        );
+        self.window.request_redraw();
+    }
+
+    /// Enable or disable the `state` flag of the widget `index`, and upload it if it changed.
+    fn set_state(&mut self, index: usize, state: u32, enabled: bool) {
+        let ty = &mut self.widgets[index].ty[3];
+        let new = if enabled { *ty | state } else { *ty & !state };
+        if new != *ty {
+            *ty = new;
+            self.write_widget(index);
+        }
+    }
+
+    /// Enable or disable the widget `index`. Disabled widgets can't be hovered, pressed or focused.
+    #[allow(unused)]
+    pub(super) fn set_disabled(&mut self, index: usize, disabled: bool) {
+        self.set_state(index, STATE_DISABLED, disabled);
+        if disabled {
+            if self.hovered == Some(index) {
+                self.hover(None);
+            }
+            if self.pressed == Some(index) {
+                self.press(false);
+            }
+            if self.focused == Some(index) {
+                self.focus(None);
+            }
+        }
+    }
+
+    /// Move the hover to the widget `index`.
+    fn hover(&mut self, index: Option<usize>) {
+        let index = index.filter(|&i| !self.widgets[i].is_disabled() && self.is_shown(i) && !self.blocked(i));
+        if index == self.hovered {
+            return;
+        }
+        if let Some(previous) = mem::replace(&mut self.hovered, index) {
+            self.set_state(previous, STATE_HOVERED, false);
+        }
+        if let Some(index) = index {
+            self.set_state(index, STATE_HOVERED, true);
+        }
+    }
+
+    /// Press or release the pointer on the hovered widget. Pressing also moves the focus to it.
+    /// Releasing it on the widget it was pressed on activates that widget.
+    /// Pressing outside of the open popups only closes them, pressing outside of the popovers also hides them.
+    fn press(&mut self, pressed: bool) {
+        if pressed {
+            // Pressing hides the tooltip until the pointer leaves its widget
+            self.hide_tooltip();
+            self.tooltip_owner = self.tooltip_owner.map(|(owner, _)| (owner, None));
+            self.dismiss_popovers();
+            if self.dismiss_popups() {
+                return;
+            }
+            self.pressed = self.hovered;
+            if let Some(index) = self.pressed {
+                self.set_state(index, STATE_PRESSED, true);
+            }
+            log::debug!("Pressed {:?}", self.pressed);
+            self.focus(self.pressed);
+            if let Some(index) = self.pressed {
+                self.drag_slider(index, true);
+                self.drag_color(index);
+                self.begin_column_drag(index);
+                self.begin_dock_drag(index);
+                self.begin_chart_drag(index);
+                self.select_row_at(index);
+                self.press_tree_row(index);
+            }
+        }
+        else if let Some(index) = self.pressed.take() {
+            self.set_state(index, STATE_PRESSED, false);
+            // Resizing a column doesn't sort by it, nor dragging a tab select it
+            let resized = self.column_drag.take().is_some();
+            let docked = self.end_dock_drag();
+            self.chart_drag = None;
+            if self.hovered == Some(index) && !resized && !docked {
+                self.activate(index);
+            }
+        }
+    }
+
+    /// Toggle the widget `index` as if clicked, if it is checkable, or open, choose or select with it if it is a popup opener,
+    /// menu item or tab.
+    fn activate(&mut self, index: usize) {
+        let ty = self.widgets[index].ty[0];
+        if ty == WidgetType::Button.ty() || self.custom.custom_type(ty).is_some() {
+            self.click(index);
+            return;
+        }
+        if self.canvases.contains_key(&index) {
+            let region = self.hovered_region.filter(|&(canvas, _)| canvas == index).map(|(_, region)| region);
+            self.events.push(WidgetEvent::CanvasClicked { index, region });
+            return;
+        }
+        if self.toggle_popover(index) || self.activate_menu(index) || self.pick_swatch(index) {
+            return;
+        }
+        if let Some((table, column)) = self.header_cell(index) {
+            let rows = self.tables[&table].rows.borrow();
+            if rows.is_sortable(column) {
+                let order = match rows.sort {
+                    Some((sorted, SortOrder::Ascending)) if sorted == column => SortOrder::Descending,
+                    _ => SortOrder::Ascending,
+                };
+                drop(rows);
+                self.sort_table(table, column, order);
+            }
+            return;
+        }
+        let widget = &self.widgets[index];
+        if !widget.is_checkable() || widget.is_disabled() {
+            return;
+        }
+        let state = CheckState::from_ty(widget.ty[1]);
+        let new = match widget.radio_group() {
+            // Radio buttons can't be unchecked by the user, only by checking another one
+            Some(_) => CheckState::Checked,
+            None => state.toggled(),
+        };
+        for (index, state) in self.change_check_state(index, new) {
+            self.events.push(WidgetEvent::CheckChanged { index, state });
+        }
+    }
+
+    /// Activate the focused checkable widget with the space or enter key. Returns whether the event was consumed.
+    fn toggle_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused.filter(|&i| self.widgets[i].is_checkable()) else { return false };
+        if !matches!(event.logical_key, Key::Named(NamedKey::Space | NamedKey::Enter)) {
+            return false;
+        }
+        if event.state == ElementState::Pressed && !event.repeat {
+            self.activate(index);
+        }
+        true
+    }
+
+    /// The state of the checkbox, radio button or toggle `index`.
+    #[allow(unused)]
+    pub(super) fn check_state(&self, index: usize) -> CheckState {
+        CheckState::from_ty(self.widgets[index].ty[1])
+    }
+
+    /// Set the state of the checkbox, radio button or toggle `index`, unchecking the other radio buttons of its group.
+    #[allow(unused)]
+    pub(super) fn set_check_state(&mut self, index: usize, state: CheckState) {
+        self.change_check_state(index, state);
+    }
+
+    /// Set the state of the checkable widget `index` and start its transition.
+    /// Returns the widgets whose state changed, with their new state.
+    fn change_check_state(&mut self, index: usize, state: CheckState) -> Vec<(usize, CheckState)> {
+        let mut changed = vec![];
+        if state == CheckState::from_ty(self.widgets[index].ty[1]) {
+            return changed;
+        }
+        if let (Some(group), CheckState::Checked) = (self.widgets[index].radio_group(), state) {
+            let others: Vec<usize> = self.widgets.iter().enumerate()
+                .filter(|&(i, w)| i != index && w.radio_group() == Some(group) && CheckState::from_ty(w.ty[1]) != CheckState::Unchecked)
+                .map(|(i, _)| i)
+                .collect();
+            for other in others {
+                self.set_check_bits(other, CheckState::Unchecked);
+                changed.push((other, CheckState::Unchecked));
+            }
+        }
+        self.set_check_bits(index, state);
+        changed.push((index, state));
+        changed
+    }
+
+    fn set_check_bits(&mut self, index: usize, state: CheckState) {
+        let widget = &mut self.widgets[index];
+        widget.ty[1] = (widget.ty[1] & !CHECK_STATE_MASK) | state.ty();
+        let from = f32::from_bits(widget.ty[2]);
+        self.transitions.insert(index, Transition::new(from, state.progress()));
+        self.write_widget(index);
+    }
+
+    /// Move a thumb of the slider `index` under the pointer, if it is a slider.
+    /// When `grab` is set, the thumb nearest to the pointer becomes the dragged one.
+    fn drag_slider(&mut self, index: usize, grab: bool) {
+        if !self.sliders.contains_key(&index) {
+            return;
+        }
+        let [left, top, right, bottom] = pixel_limits(self.screen_limits(index), self.size);
+        let widget = &self.widgets[index];
+        let (x, y) = (self.cursor_pos.x as f32, self.cursor_pos.y as f32);
+        // Thumbs stop half the track's thickness from its ends
+        let fraction = if widget.ty[2] & SLIDER_VERTICAL != 0 {
+            let inset = (right - left) * 0.5;
+            (bottom - inset - y) / (bottom - top - 2.0 * inset).max(1.0)
+        } else {
+            let inset = (bottom - top) * 0.5;
+            (x - left - inset) / (right - left - 2.0 * inset).max(1.0)
+        };
+        let slider = self.sliders.get_mut(&index).unwrap();
+        let value = slider.value_at(fraction);
+        let thumb = if grab { slider.nearest_thumb(value) } else { slider.active() };
+        let changed = slider.set(thumb, value);
+        if changed || grab {
+            self.sync_slider(index, changed);
+        }
+    }
+
+    /// Move the active thumb of the focused slider, the last one dragged, with the arrow, page, home and end keys.
+    /// Returns whether the event was consumed.
+    fn slider_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused else { return false };
+        let Some(slider) = self.sliders.get_mut(&index) else { return false };
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        let changed = match event.logical_key {
+            Key::Named(NamedKey::ArrowRight | NamedKey::ArrowUp) => slider.step_by(1.0),
+            Key::Named(NamedKey::ArrowLeft | NamedKey::ArrowDown) => slider.step_by(-1.0),
+            Key::Named(NamedKey::PageUp) => slider.step_by(10.0),
+            Key::Named(NamedKey::PageDown) => slider.step_by(-10.0),
+            Key::Named(NamedKey::Home) => slider.set_to_end(false),
+            Key::Named(NamedKey::End) => slider.set_to_end(true),
+            _ => return false,
+        };
+        if changed {
+            self.sync_slider(index, true);
+        }
+        true
+    }
+
+    /// The value of the slider `index`, or the low and high values of the range slider `index`.
+    #[allow(unused)]
+    pub(super) fn slider_values(&self, index: usize) -> &[f32] {
+        self.sliders[&index].values()
+    }
+
+    /// Set the bounds of the slider or range slider `index`, and the step its values snap to, 0 for none.
+    #[allow(unused)]
+    pub(super) fn set_slider_bounds(&mut self, index: usize, min: f32, max: f32, step: f32) {
+        self.sliders.get_mut(&index).unwrap().set_bounds(min, max, step);
+        self.sync_slider(index, false);
+    }
+
+    /// Move the thumb `thumb` of the slider or range slider `index` to `value`.
+    #[allow(unused)]
+    pub(super) fn set_slider_value(&mut self, index: usize, thumb: usize, value: f32) {
+        self.sliders.get_mut(&index).unwrap().set(thumb, value);
+        self.sync_slider(index, false);
+    }
+
+    /// Upload the thumbs of the slider `index`, and report its values if `notify` is set.
+    fn sync_slider(&mut self, index: usize, notify: bool) {
+        let slider = &self.sliders[&index];
+        let widget = &mut self.widgets[index];
+        widget.ty[1] = slider.ty();
+        widget.ty[2] = if slider.active() == 1 { widget.ty[2] | SLIDER_SECOND_THUMB } else { widget.ty[2] & !SLIDER_SECOND_THUMB };
+        if notify {
+            let event = match *slider.values() {
+                [value] => WidgetEvent::SliderMoved { index, value },
+                [low, high] => WidgetEvent::RangeMoved { index, low, high },
+                _ => unreachable!(),
+            };
+            self.events.push(event);
+        }
+        self.write_widget(index);
+    }
+
+    /// Set the progress of the progress bar `index`, from 0 to 1, or `None` if it is unknown.
+    #[allow(unused)]
+    pub(super) fn set_progress(&mut self, index: usize, progress: Option<f32>) {
+        self.widgets[index].set_progress(progress);
+        self.write_widget(index);
+    }
+
+    /// The limits of the widget `index` in NDC, moved by the scroll views and popup it is in.
+    fn screen_limits(&self, index: usize) -> [f32; 4] {
+        let mut limits = self.widgets[index].limits;
+        for view in ancestors(&self.widgets, index) {
+            let [x, y] = self.drawn_offset(view);
+            limits = [limits[0] - x, limits[1] - x, limits[2] + y, limits[3] + y];
+        }
+        limits
+    }
+
+    /// Scroll the innermost scroll view under the cursor that can scroll by `delta` pixels, +Y being up.
+    /// Returns whether the cursor is over a scroll view.
+    fn wheel(&mut self, delta: [f32; 2]) -> bool {
+        let Some(hovered) = self.pick() else { return false };
+        let views: Vec<usize> = iter::once(hovered).chain(ancestors(&self.widgets, hovered))
+            .filter(|i| self.scrolls.contains_key(i))
+            .collect();
+        let delta = [delta[0] * 2.0 / self.size.width as f32, -delta[1] * 2.0 / self.size.height as f32];
+        for &view in &views {
+            if self.scrolls.get_mut(&view).unwrap().scroll_by(delta) {
+                self.sync_scroll(view, true);
+                break;
+            }
+        }
+        !views.is_empty()
+    }
+
+    /// Start dragging the scroll view under the pressed pointer.
+    /// The mouse drags the content of a scroll view pressed directly, or its scrollbar thumbs.
+    /// A touch also drags the scroll view of the pressed widget, once it has moved past `TOUCH_SLOP`.
+    fn begin_scroll_drag(&mut self, touch: bool) {
+        let Some(pressed) = self.pressed else { return };
+        let drag = if self.scrolls.contains_key(&pressed) {
+            match self.scrollbar_at(pressed) {
+                Some(axis) => Some((pressed, DragKind::Thumb(axis))),
+                None => Some((pressed, if touch { DragKind::Pending } else { DragKind::Content })),
+            }
+        } else if touch && !self.sliders.contains_key(&pressed) {
+            ancestors(&self.widgets, pressed).find(|v| self.scrolls.contains_key(v)).map(|view| (view, DragKind::Pending))
+        } else {
+            None
+        };
+        if let Some((index, kind)) = drag {
+            self.scrolls.get_mut(&index).unwrap().stop();
+            let position = [self.cursor_pos.x, self.cursor_pos.y];
+            self.scroll_drag = Some(ScrollDrag {
+                index,
+                kind,
+                start: position,
+                last: position,
+                last_time: web_time::Instant::now(),
+                velocity: [0.0; 2],
+            });
+        }
+    }
+
+    /// The axis of the scrollbar of the scroll view `index` under the cursor, if any.
+    fn scrollbar_at(&self, index: usize) -> Option<usize> {
+        let [_, _, right, bottom] = pixel_limits(self.screen_limits(index), self.size);
+        let max = self.scrolls[&index].max();
+        let (x, y) = (self.cursor_pos.x as f32, self.cursor_pos.y as f32);
+        if max[1] > 0.0 && x >= right - SCROLLBAR_WIDTH {
+            Some(1)
+        } else if max[0] > 0.0 && y >= bottom - SCROLLBAR_WIDTH {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// Follow the pointer with the dragged scroll view.
+    fn drag_scroll(&mut self) {
+        let Some(drag) = &mut self.scroll_drag else { return };
+        let position = [self.cursor_pos.x, self.cursor_pos.y];
+        if drag.kind == DragKind::Pending {
+            if (position[0] - drag.start[0]).hypot(position[1] - drag.start[1]) < TOUCH_SLOP {
+                return;
+            }
+            // The touch scrolls instead of pressing
+            drag.kind = DragKind::Content;
+            if let Some(pressed) = self.pressed.take() {
+                self.set_state(pressed, STATE_PRESSED, false);
+            }
+        }
+
+        let Some(drag) = &mut self.scroll_drag else { return };
+        let (width, height) = (self.size.width as f32, self.size.height as f32);
+        let moved = [(position[0] - drag.last[0]) as f32, (position[1] - drag.last[1]) as f32];
+        let index = drag.index;
+        let delta = match drag.kind {
+            DragKind::Thumb(axis) => {
+                // The thumb follows the pointer along its track
+                let [left, top, right, bottom] = pixel_limits(self.screen_limits(index), self.size);
+                let model = &self.scrolls[&index];
+                let view = self.widgets[index].limits;
+                let (view, track) = if axis == 0 {
+                    (view[1] - view[0], right - left - if model.max()[1] > 0.0 { SCROLLBAR_WIDTH } else { 0.0 })
+                } else {
+                    (view[3] - view[2], bottom - top - if model.max()[0] > 0.0 { SCROLLBAR_WIDTH } else { 0.0 })
+                };
+                let Some((_, length)) = model.thumb(axis, view, track) else { return };
+                let mut delta = [0.0; 2];
+                delta[axis] = (f64::from(moved[axis]) * model.max()[axis] / f64::from((track - length).max(1.0))) as f32;
+                delta
+            }
+            _ => [-moved[0] * 2.0 / width, -moved[1] * 2.0 / height],
+        };
+
+        let Some(drag) = &mut self.scroll_drag else { return };
+        let now = web_time::Instant::now();
+        let dt = now.saturating_duration_since(drag.last_time).as_secs_f32().max(1e-3);
+        drag.velocity = [0, 1].map(|i| 0.8 * delta[i] / dt + 0.2 * drag.velocity[i]);
+        drag.last = position;
+        drag.last_time = now;
+        if self.scrolls.get_mut(&index).unwrap().scroll_by(delta) {
+            self.sync_scroll(index, true);
+        }
+    }
+
+    /// Release the dragged scroll view, flinging its content if it was moving.
+    fn end_scroll_drag(&mut self) {
+        let Some(drag) = self.scroll_drag.take() else { return };
+        // The pointer must still be moving when released
+        if drag.kind == DragKind::Content && drag.last_time.elapsed().as_secs_f32() < 0.1 {
+            self.scrolls.get_mut(&drag.index).unwrap().fling(drag.velocity);
+            self.window.request_redraw();
+        }
+    }
+
+    /// The scroll offset of the scroll view `index`, in NDC.
+    #[allow(unused)]
+    pub(super) fn scroll_offset(&self, index: usize) -> [f64; 2] {
+        self.scrolls[&index].offset()
+    }
+
+    /// Scroll the scroll view `index` to `offset`, in NDC.
+    #[allow(unused)]
+    pub(super) fn set_scroll_offset(&mut self, index: usize, offset: [f64; 2]) {
+        let model = self.scrolls.get_mut(&index).unwrap();
+        model.stop();
+        if model.set_offset(offset) {
+            self.sync_scroll(index, false);
+        }
+    }
+
+    /// Scroll the scroll views containing the widget `index` just enough to show it.
+    pub(super) fn scroll_to(&mut self, index: usize) {
+        let views: Vec<usize> = ancestors(&self.widgets, index).filter(|v| self.scrolls.contains_key(v)).collect();
+        for view in views {
+            let target = self.screen_limits(index);
+            let bounds = self.screen_limits(view);
+            // Show the left and top edges rather than the right and bottom ones if the widget is too big
+            let mut delta = [0.0; 2];
+            if target[1] > bounds[1] {
+                delta[0] = target[1] - bounds[1];
+            }
+            if target[0] - delta[0] < bounds[0] {
+                delta[0] = target[0] - bounds[0];
+            }
+            if target[2] < bounds[2] {
+                delta[1] = bounds[2] - target[2];
+            }
+            if target[3] + delta[1] > bounds[3] {
+                delta[1] = bounds[3] - target[3];
+            }
+            if self.scrolls.get_mut(&view).unwrap().scroll_by(delta) {
+                self.sync_scroll(view, false);
+            }
+        }
+    }
+
+    /// The offset the scroll view `index` is drawn with, less the height its items are placed from if it is a list or grid view.
+    /// Popups and overlays are moved by the offset in their `ty`, the other widgets holding widgets aren't.
+    fn drawn_offset(&self, index: usize) -> [f32; 2] {
+        let Some(scroll) = self.scrolls.get(&index) else {
+            let ty = self.widgets[index].ty;
+            if ty[0] != WidgetType::Popup.ty() && ty[0] != WidgetType::Overlay.ty() {
+                return [0.0, 0.0];
+            }
+            return [f32::from_bits(ty[1]), f32::from_bits(ty[2])];
+        };
+        let [x, y] = scroll.offset();
+        match self.virtual_views.get(&index) {
+            Some(view) => [x as f32, view.layout.drawn_offset(y, view.base_row)],
+            None => [x as f32, y as f32],
+        }
+    }
+
+    /// Make the scroll view `index`, created with `layout.widgets(index)`, a list or grid view showing the items of `source`.
+    pub(super) fn set_data_source(&mut self, index: usize, layout: ItemLayout, source: impl DataSource + 'static) {
+        self.virtual_views.insert(index, VirtualView::new(layout, Box::new(source), index + 1));
+        self.reload_items(index);
+    }
+
+    /// Ask the data source of the list or grid view `index` for its items in view again, after they changed.
+    pub(super) fn reload_items(&mut self, index: usize) {
+        let Some(view) = self.virtual_views.get_mut(&index) else { return };
+        view.items.fill(None);
+        let max = view.layout.scroll_max(view.source.len());
+        self.widgets[index].scroll_max = max.map(|m| m as f32);
+        self.scrolls.get_mut(&index).unwrap().set_max(max);
+        self.sync_scroll(index, false);
+    }
+
+    /// The list or grid view showing an item in the widget `index`, with that item.
+    /// Events of widgets in list and grid views are about their slots, this gives the item they show.
+    pub(super) fn slot_item(&self, index: usize) -> Option<(usize, usize)> {
+        self.virtual_views.iter().find_map(|(&view, v)| v.item(index).map(|item| (view, item)))
+    }
+
+    /// Fill the slots of the list or grid view `index` with the items in view.
+    /// Slots keep their item while it stays in view, the others are given the items scrolled in.
+    /// With `relayout`, the slots keeping their item are placed again too, after the layout changed.
+    fn place_items(&mut self, index: usize, relayout: bool) {
+        let Some(view) = self.virtual_views.get_mut(&index) else { return };
+        let base_row = view.layout.first_row(self.scrolls[&index].offset()[1]);
+        let rebased = base_row != view.base_row || relayout;
+        view.base_row = base_row;
+        let wanted: Vec<Option<usize>> = view.wanted(base_row).collect();
+        for (slot, item) in wanted.into_iter().enumerate() {
+            let view = self.virtual_views.get_mut(&index).unwrap();
+            let widget_index = view.first_slot + slot;
+            let recycled = view.items[slot] != item;
+            if !recycled && !rebased {
+                continue;
+            }
+            view.items[slot] = item;
+            let old = self.widgets[widget_index];
+            let mut widget = match item {
+                Some(item) if recycled => view.source.widget(item, view.layout.item_limits(item, base_row)),
+                Some(item) => Widget { limits: view.layout.item_limits(item, base_row), ..old },
+                None => Widget { limits: view.layout.hidden(), ..old },
+            };
+            widget.parent = index as u32 + 1;
+            if recycled {
+                // The pointer stays over the slot, but the keyboard and transitions were for the item
+                widget.ty[3] |= old.ty[3] & (STATE_HOVERED | STATE_PRESSED);
+                self.transitions.remove(&widget_index);
+                if self.focused == Some(widget_index) {
+                    self.focused = None;
+                }
+            }
+            self.widgets[widget_index] = widget;
+            self.write_widget(widget_index);
+        }
+    }
+
+    /// Make the widgets created with `layout.widgets(index)` a table of `len` rows, with cells rendered by `columns`.
+    pub(super) fn set_table(&mut self, index: usize, layout: TableLayout, len: usize, columns: Vec<Box<dyn Column>>) {
+        let rows = Rc::new(RefCell::new(TableRows::new(&layout, len, columns)));
+        let body = layout.body_index(index);
+        self.set_data_source(body, layout.body(), TableSource(rows.clone()));
+        self.tables.insert(index, Table { layout, rows, body });
+        self.fit_header(index);
+    }
+
+    /// Let the header of the table `index` scroll horizontally as far as its rows, and scroll it with them.
+    fn fit_header(&mut self, index: usize) {
+        let body = &self.scrolls[&self.tables[&index].body];
+        let (max, offset) = (body.max()[0], body.offset()[0]);
+        let header = self.scrolls.get_mut(&index).unwrap();
+        header.set_max([max, 0.0]);
+        header.set_offset([offset, 0.0]);
+        self.write_scroll(index);
+    }
+
+    /// The table and column of the header cell `index`, if it is one.
+    fn header_cell(&self, index: usize) -> Option<(usize, usize)> {
+        self.tables.iter().find_map(|(&table, t)| (index > table && index < t.body).then_some((table, index - table - 1)))
+    }
+
+    /// The table and column whose header cell has its right edge under the cursor.
+    /// Between two header cells, the left one is resized.
+    fn column_edge_at(&self, index: usize) -> Option<(usize, usize)> {
+        let (table, column) = self.header_cell(index)?;
+        let [left, _, right, _] = pixel_limits(self.screen_limits(index), self.size);
+        let x = self.cursor_pos.x as f32;
+        if x >= right - RESIZE_GRIP {
+            Some((table, column))
+        } else if x < left + RESIZE_GRIP && column > 0 {
+            Some((table, column - 1))
+        } else {
+            None
+        }
+    }
+
+    /// Start resizing a column if the header cell `index` was pressed on its edge.
+    fn begin_column_drag(&mut self, index: usize) {
+        if let Some((table, column)) = self.column_edge_at(index) {
+            let width = self.tables[&table].layout.widths[column];
+            self.column_drag = Some(ColumnDrag { table, column, start: self.cursor_pos.x, width });
+        }
+    }
+
+    /// Resize the column dragged by the pointer.
+    fn drag_column(&mut self) {
+        let Some(drag) = &self.column_drag else { return };
+        let width = drag.width + (self.cursor_pos.x - drag.start) as f32 * 2.0 / self.size.width as f32;
+        self.resize_column(drag.table, drag.column, width);
+    }
+
+    /// Show the resizing cursors over the edges of header cells and the dividers of docks, and while resizing.
+    fn update_resize_cursor(&mut self) {
+        let column = self.column_drag.is_some() || self.hovered.and_then(|i| self.column_edge_at(i)).is_some();
+        let divider = match self.dock_drag {
+            Some(DockDrag::Divider { dock, divider, .. }) => Some((dock, divider)),
+            _ => self.hovered.and_then(|i| self.divider_at(i)),
+        };
+        let cursor = if column {
+            Some(CursorIcon::ColResize)
+        } else {
+            divider.map(|(dock, divider)| match self.docks[&dock].placement.dividers[divider].orientation {
+                Orientation::Horizontal => CursorIcon::ColResize,
+                Orientation::Vertical => CursorIcon::RowResize,
+            })
+        };
+        if cursor != self.resize_cursor {
+            self.resize_cursor = cursor;
+            self.window.set_cursor_icon(cursor.unwrap_or(CursorIcon::Default));
+        }
+    }
+
+    /// Set the width of `column` of the table `index`, in NDC.
+    pub(super) fn resize_column(&mut self, index: usize, column: usize, width: f32) {
+        let min = MIN_COLUMN_WIDTH * 2.0 / self.size.width as f32;
+        let table = self.tables.get_mut(&index).unwrap();
+        table.layout.widths[column] = width.max(min);
+        let (layout, body) = (table.layout.clone(), table.body);
+        for column in 0..layout.widths.len() {
+            self.widgets[index + 1 + column].limits = layout.header_cell(column);
+            self.write_widget(index + 1 + column);
+        }
+        let view = self.virtual_views.get_mut(&body).unwrap();
+        view.layout = layout.body();
+        let max = view.layout.scroll_max(view.source.len());
+        self.widgets[body].scroll_max = max.map(|m| m as f32);
+        self.scrolls.get_mut(&body).unwrap().set_max(max);
+        self.place_items(body, true);
+        self.write_scroll(body);
+        self.fit_header(index);
+    }
+
+    /// Sort the rows of the table `index` by `column`, showing the order in its header cell.
+    pub(super) fn sort_table(&mut self, index: usize, column: usize, order: SortOrder) {
+        let table = &self.tables[&index];
+        let body = table.body;
+        let mut rows = table.rows.borrow_mut();
+        rows.sort_by(column, order);
+        rows.sort = Some((column, order));
+        let columns = rows.column_count();
+        drop(rows);
+        for c in 0..columns {
+            self.widgets[index + 1 + c].ty[1] = if c == column { order.ty() } else { 0 };
+            self.write_widget(index + 1 + c);
+        }
+        self.reload_items(body);
+    }
+
+    /// The data row selected in the table `index`.
+    #[allow(unused)]
+    pub(super) fn selected_row(&self, index: usize) -> Option<usize> {
+        self.tables[&index].rows.borrow().selected
+    }
+
+    /// Select the data row `row` of the table `index`, or none.
+    pub(super) fn select_row(&mut self, index: usize, row: Option<usize>) {
+        let table = &self.tables[&index];
+        let body = table.body;
+        let changed = mem::replace(&mut table.rows.borrow_mut().selected, row) != row;
+        if changed {
+            self.reload_items(body);
+        }
+    }
+
+    /// Scroll the table `index` to show the data row `row`.
+    pub(super) fn scroll_to_row(&mut self, index: usize, row: usize) {
+        let table = &self.tables[&index];
+        let body = table.body;
+        let position = table.rows.borrow().position(row);
+        self.show_row(body, position);
+    }
+
+    /// Scroll the list or grid view `index` just enough to show its row `row`.
+    fn show_row(&mut self, index: usize, row: usize) {
+        let layout = &self.virtual_views[&index].layout;
+        let [top, bottom] = [layout.rows_height(row), layout.rows_height(row + 1)];
+        let view = f64::from(self.widgets[index].limits[3] - self.widgets[index].limits[2]);
+        let [x, y] = self.scrolls[&index].offset();
+        let y = if top < y { top } else if bottom > y + view { bottom - view } else { y };
+        self.set_scroll_offset(index, [x, y]);
+    }
+
+    /// Make the list view `index`, created with `layout.widgets(index)` from `ItemLayout::tree`, a tree view of the nodes of `source`.
+    /// Its rows have the style `row_style`, or `selected_style` when selected.
+    pub(super) fn set_tree(&mut self, index: usize, layout: ItemLayout, source: impl TreeSource + 'static, row_style: u32, selected_style: u32) {
+        let mut tree = TreeModel::new(Box::new(source), row_style, selected_style);
+        tree.indent = TREE_INDENT * 2.0 / self.size.width as f32;
+        let tree = Rc::new(RefCell::new(tree));
+        self.trees.insert(index, tree.clone());
+        self.set_data_source(index, layout, TreeRows(tree));
+    }
+
+    /// Ask the source of the tree view `index` for the children of its expanded nodes again, after they changed.
+    #[allow(unused)]
+    pub(super) fn reload_tree(&mut self, index: usize) {
+        self.trees[&index].borrow_mut().reload();
+        self.reload_items(index);
+    }
+
+    /// Expand or collapse `node` of the tree view `index`.
+    pub(super) fn set_expanded(&mut self, index: usize, node: NodeId, expanded: bool) {
+        if self.trees[&index].borrow_mut().set_expanded(node, expanded) {
+            self.reload_items(index);
+        }
+    }
+
+    /// The selected nodes of the tree view `index`, in the order of their rows.
+    pub(super) fn selected_nodes(&self, index: usize) -> Vec<NodeId> {
+        let tree = self.trees[&index].borrow();
+        tree.rows.iter().map(|r| r.node).filter(|n| tree.selected.contains(n)).collect()
+    }
+
+    /// The tree view and row of the slot `index`, if it is in one.
+    fn tree_row_of(&self, index: usize) -> Option<(usize, usize)> {
+        let (view, item) = self.slot_item(index)?;
+        self.trees.contains_key(&view).then_some((view, item / 2))
+    }
+
+    /// Expand or collapse the node of the pressed row on its arrow, or select it according to the modifiers.
+    /// The tree view gets the keyboard focus.
+    fn press_tree_row(&mut self, index: usize) {
+        let Some((view, row)) = self.tree_row_of(index) else { return };
+        self.focus(Some(view));
+        let tree = self.trees[&view].clone();
+        let mut t = tree.borrow_mut();
+        let r = t.rows[row];
+        let left = pixel_limits(self.screen_limits(index), self.size)[0] + r.depth as f32 * TREE_INDENT;
+        let x = self.cursor_pos.x as f32;
+        if r.expandable && x >= left && x < left + TREE_INDENT {
+            t.set_expanded(r.node, !r.expanded);
+            t.cursor = Some(r.node);
+            drop(t);
+            self.reload_items(view);
+            self.events.push(WidgetEvent::Expanded { index: view, node: r.node, expanded: !r.expanded });
+            return;
+        }
+        let mode = if self.modifiers.shift_key() {
+            SelectMode::Range
+        } else if self.modifiers.control_key() || self.modifiers.super_key() {
+            SelectMode::Toggle
+        } else if t.selected.contains(&r.node) {
+            // Pressing a selected node keeps the selection, to drag it
+            t.cursor = Some(r.node);
+            return;
+        } else {
+            SelectMode::Single
+        };
+        let changed = t.select(r.node, mode);
+        drop(t);
+        if changed {
+            self.reload_items(view);
+            self.events.push(WidgetEvent::SelectionChanged { index: view });
+        }
+    }
+
+    /// Move the cursor of the focused tree view with the arrow, home and end keys, selecting the node under it,
+    /// or extending the selection with shift. Left and right collapse and expand the node, or go to its parent and child.
+    /// Space toggles the selection of the node. Returns whether the event was consumed.
+    fn tree_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused.filter(|i| self.trees.contains_key(i)) else { return false };
+        let Key::Named(key) = &event.logical_key else { return false };
+        if !matches!(key, NamedKey::ArrowUp | NamedKey::ArrowDown | NamedKey::ArrowLeft | NamedKey::ArrowRight
+            | NamedKey::Home | NamedKey::End | NamedKey::Space) {
+            return false;
+        }
+        if event.state != ElementState::Pressed {
+            return true;
+        }
+        let tree = self.trees[&index].clone();
+        let mut t = tree.borrow_mut();
+        let Some(last) = t.rows.len().checked_sub(1) else { return true };
+        let row = t.cursor.and_then(|c| t.row_of(c));
+        let current = row.map(|r| t.rows[r]);
+        let target = match (key, row, current) {
+            (NamedKey::ArrowLeft, Some(_), Some(r)) if r.expanded => {
+                t.set_expanded(r.node, false);
+                drop(t);
+                self.reload_items(index);
+                self.events.push(WidgetEvent::Expanded { index, node: r.node, expanded: false });
+                return true;
+            }
+            (NamedKey::ArrowRight, Some(_), Some(r)) if r.expandable && !r.expanded => {
+                t.set_expanded(r.node, true);
+                drop(t);
+                self.reload_items(index);
+                self.events.push(WidgetEvent::Expanded { index, node: r.node, expanded: true });
+                return true;
+            }
+            (NamedKey::ArrowLeft, Some(row), _) => t.parent_row(row).unwrap_or(row),
+            (NamedKey::ArrowRight, Some(row), Some(r)) if r.expanded => (row + 1).min(last),
+            (NamedKey::ArrowUp, Some(row), _) => row.saturating_sub(1),
+            (NamedKey::ArrowDown, Some(row), _) => (row + 1).min(last),
+            (NamedKey::Home, ..) => 0,
+            (NamedKey::End, ..) => last,
+            (NamedKey::Space, Some(row), _) => row,
+            (_, Some(row), _) => row,
+            _ => 0,
+        };
+        let mode = match key {
+            NamedKey::Space => SelectMode::Toggle,
+            _ if self.modifiers.shift_key() => SelectMode::Range,
+            _ => SelectMode::Single,
+        };
+        let node = t.rows[target].node;
+        let changed = t.select(node, mode);
+        drop(t);
+        if changed {
+            self.reload_items(index);
+            self.events.push(WidgetEvent::SelectionChanged { index });
+        }
+        self.show_row(index, target);
+        true
+    }
+
+    /// Start dragging the selected nodes if a row of a tree view was pressed, off its arrow.
+    fn begin_tree_drag(&mut self) {
+        let Some((tree, _)) = self.pressed.and_then(|i| self.tree_row_of(i)) else { return };
+        if self.trees[&tree].borrow().selected.is_empty() {
+            return;
+        }
+        self.tree_drag = Some(TreeDrag { tree, start: [self.cursor_pos.x, self.cursor_pos.y], moving: false });
+    }
+
+    /// Show where the dragged nodes would be dropped, once the pointer moved past `TOUCH_SLOP`.
+    /// Nodes can't be dropped on selected ones, and only expandable nodes take them inside.
+    fn drag_tree(&mut self) {
+        let Some(drag) = &mut self.tree_drag else { return };
+        let position = [self.cursor_pos.x, self.cursor_pos.y];
+        if !drag.moving && (position[0] - drag.start[0]).hypot(position[1] - drag.start[1]) < TOUCH_SLOP {
+            return;
+        }
+        drag.moving = true;
+        let index = drag.tree;
+
+        // The row under the pointer, and how far down it
+        let layout = &self.virtual_views[&index].layout;
+        let limits = self.screen_limits(index);
+        let y = 1.0 - 2.0 * self.cursor_pos.y as f32 / self.size.height as f32;
+        let content = (f64::from(limits[3] - y) + self.scrolls[&index].offset()[1]) / layout.rows_height(1);
+        let tree = self.trees[&index].clone();
+        let mut t = tree.borrow_mut();
+        let target = (y >= limits[2] && y <= limits[3] && content >= 0.0 && (content as usize) < t.rows.len())
+            .then(|| (content as usize, content.fract()))
+            .filter(|&(row, _)| !t.selected.contains(&t.rows[row].node))
+            .map(|(row, fraction)| {
+                let position = match fraction {
+                    f if t.rows[row].expandable && (0.25..0.75).contains(&f) => DropPosition::Inside,
+                    f if f < 0.5 => DropPosition::Before,
+                    _ => DropPosition::After,
+                };
+                (row, position)
+            });
+        if t.drop != target {
+            t.drop = target;
+            drop(t);
+            self.reload_items(index);
+        }
+    }
+
+    /// Drop the dragged nodes where shown, if anywhere.
+    fn end_tree_drag(&mut self) {
+        let Some(drag) = self.tree_drag.take() else { return };
+        let tree = self.trees[&drag.tree].clone();
+        let mut t = tree.borrow_mut();
+        let Some((row, position)) = t.drop.take() else { return };
+        let target = t.rows[row].node;
+        drop(t);
+        self.reload_items(drag.tree);
+        self.events.push(WidgetEvent::Dropped { index: drag.tree, target, position });
+    }
+
+    /// Select the row of the cell `index` when pressed, if it is in a table.
+    fn select_row_at(&mut self, index: usize) {
+        let Some((view, item)) = self.slot_item(index) else { return };
+        let Some((&table, t)) = self.tables.iter().find(|(_, t)| t.body == view) else { return };
+        let rows = t.rows.borrow();
+        let row = rows.row(item / (rows.column_count() + 1));
+        let changed = rows.selected != Some(row);
+        drop(rows);
+        if changed {
+            self.select_row(table, Some(row));
+            self.events.push(WidgetEvent::RowSelected { index: table, row });
+        }
+    }
+
+    /// Show or hide the widget `index`, with the widgets in it.
+    pub(super) fn set_hidden(&mut self, index: usize, hidden: bool) {
+        if self.widgets[index].is_hidden() != hidden {
+            self.set_state(index, STATE_HIDDEN, hidden);
+            self.ids_dirty = true;
+        }
+    }
+
+    /// Whether the widget `index` and the widgets containing it are shown.
+    fn is_shown(&self, index: usize) -> bool {
+        !iter::once(index).chain(ancestors(&self.widgets, index)).any(|i| self.widgets[i].is_hidden())
+    }
+
+    /// The popup the widget `index` is, or is in.
+    fn popup_of(&self, index: usize) -> Option<usize> {
+        iter::once(index).chain(ancestors(&self.widgets, index)).find(|&i| self.widgets[i].ty[0] == WidgetType::Popup.ty())
+    }
+
+    /// The menu items in the popup `popup` and not in a popup inside of it, in order, which are the options of a combo box.
+    /// They may be in scroll views of the popup, like `popup_of` finds them.
+    fn popup_items(&self, popup: usize) -> Vec<usize> {
+        (popup + 1..self.widgets.len())
+            .filter(|&i| self.widgets[i].is_menu_item() && self.popup_of(i) == Some(popup))
+            .collect()
+    }
+
+    /// Open `popup` with its top left corner at `at` in NDC, above the open popups.
+    #[allow(unused)]
+    pub(super) fn open_popup(&mut self, popup: usize, at: [f32; 2]) {
+        self.show_popup(popup, at, None);
+    }
+
+    fn show_popup(&mut self, popup: usize, at: [f32; 2], opener: Option<usize>) {
+        self.move_by(popup, popup_offset(self.widgets[popup].limits, at));
+        self.set_hidden(popup, false);
+        self.open_popups.retain(|&(p, _)| p != popup);
+        self.open_popups.push((popup, opener));
+        if let Some(combo) = opener.filter(|o| self.combos.contains_key(o)) {
+            self.widgets[combo].ty[2] |= COMBO_OPEN;
+            self.write_widget(combo);
+        }
+    }
+
+    /// Move the popup or overlay `index` by `offset` from its limits, in NDC, +X moving it left and +Y up.
+    fn move_by(&mut self, index: usize, offset: [f32; 2]) {
+        self.widgets[index].ty[1] = offset[0].to_bits();
+        self.widgets[index].ty[2] = offset[1].to_bits();
+        self.write_widget(index);
+        self.ids_dirty = true;
+    }
+
+    /// The position of the cursor in NDC.
+    fn cursor_ndc(&self) -> [f32; 2] {
+        [
+            self.cursor_pos.x as f32 * 2.0 / self.size.width as f32 - 1.0,
+            1.0 - self.cursor_pos.y as f32 * 2.0 / self.size.height as f32,
+        ]
+    }
+
+    /// Close all the popups.
+    pub(super) fn close_popups(&mut self) {
+        self.close_popups_above(0);
+    }
+
+    /// Close the open popups but the `keep` lowest ones.
+    fn close_popups_above(&mut self, keep: usize) {
+        while self.open_popups.len() > keep {
+            let (popup, opener) = self.open_popups.pop().unwrap();
+            self.set_hidden(popup, true);
+            if let Some(combo) = opener.filter(|o| self.combos.contains_key(o)) {
+                self.widgets[combo].ty[2] &= !COMBO_OPEN;
+                self.write_widget(combo);
+            }
+            if self.focused.is_some_and(|f| self.popup_of(f) == Some(popup)) {
+                self.focus(None);
+            }
+            if self.menu_highlight.is_some_and(|h| self.popup_of(h) == Some(popup)) {
+                self.highlight(None);
+            }
+        }
+    }
+
+    /// How many open popups stay open when the widget `index` opens one: those up to the popup it is in.
+    fn popups_below(&self, index: usize) -> usize {
+        self.popup_of(index)
+            .and_then(|popup| self.open_popups.iter().position(|&(p, _)| p == popup))
+            .map_or(0, |position| position + 1)
+    }
+
+    fn is_open(&self, popup: usize) -> bool {
+        self.open_popups.iter().any(|&(p, _)| p == popup)
+    }
+
+    /// Open the popup of the widget `index` next to it, closing those above the popup it is in.
+    fn open_from(&mut self, index: usize) {
+        let (popup, anchor) = self.openers[&index];
+        self.close_popups_above(self.popups_below(index));
+        let [left, right, bottom, top] = self.screen_limits(index);
+        let at = match anchor {
+            PopupAnchor::Below => [left, bottom],
+            PopupAnchor::Right => [right, top],
+        };
+        self.show_popup(popup, at, Some(index));
+    }
+
+    /// Open the popup of the widget `index` from the keyboard, highlighting the selected option of combo boxes,
+    /// or the first item of menus.
+    fn open_from_key(&mut self, index: usize) {
+        self.open_from(index);
+        let popup = self.openers[&index].0;
+        let items: Vec<usize> = self.popup_items(popup).into_iter().filter(|&i| !self.widgets[i].is_disabled()).collect();
+        let selected = self.combos.contains_key(&index)
+            .then(|| self.popup_items(popup).get(self.widgets[index].ty[1] as usize).copied())
+            .flatten()
+            .filter(|i| items.contains(i));
+        self.highlight(selected.or(items.first().copied()));
+    }
+
+    /// Open `popup` below the widget `index` when it is activated, or to its right if `index` is in a popup, as a submenu.
+    pub(super) fn set_submenu(&mut self, index: usize, popup: usize) {
+        let anchor = if self.popup_of(index).is_some() { PopupAnchor::Right } else { PopupAnchor::Below };
+        if anchor == PopupAnchor::Right && self.widgets[index].ty[0] == WidgetType::MenuItem.ty() {
+            self.widgets[index].ty[1] |= MENU_SUBMENU;
+            self.write_widget(index);
+        }
+        self.openers.insert(index, (popup, anchor));
+    }
+
+    /// Make the menu items in `popup` the options of the combo box `index`, shown below it when it is activated.
+    pub(super) fn set_combo_options(&mut self, index: usize, popup: usize) {
+        self.combos.insert(index, popup);
+        self.openers.insert(index, (popup, PopupAnchor::Below));
+    }
+
+    /// The index of the selected option of the combo box `index`.
+    #[allow(unused)]
+    pub(super) fn combo_selection(&self, index: usize) -> usize {
+        self.widgets[index].ty[1] as usize
+    }
+
+    /// Open `popup` at the cursor when the widget `index`, or a widget in it, is right clicked.
+    pub(super) fn set_context_menu(&mut self, index: usize, popup: usize) {
+        self.context_menus.insert(index, popup);
+    }
+
+    /// Activate the menu item `index` when `accelerator` is pressed, even if its menu is closed.
+    pub(super) fn set_accelerator(&mut self, index: usize, accelerator: Accelerator) {
+        self.accelerators.retain(|&(_, i)| i != index);
+        self.accelerators.push((accelerator, index));
+    }
+
+    /// Move the highlight of the open menus to the menu item `index`.
+    fn highlight(&mut self, index: Option<usize>) {
+        if index == self.menu_highlight {
+            return;
+        }
+        if let Some(previous) = mem::replace(&mut self.menu_highlight, index) {
+            if self.focused != Some(previous) {
+                self.set_state(previous, STATE_FOCUSED, false);
+            }
+        }
+        if let Some(index) = index {
+            self.set_state(index, STATE_FOCUSED, true);
+        }
+    }
+
+    /// Open, close or choose with the widget `index`, if it is a popup opener, a menu item or a tab. Returns whether it was one.
+    fn activate_menu(&mut self, index: usize) -> bool {
+        if let Some(&(popup, anchor)) = self.openers.get(&index) {
+            // Menus of menu bars and combo boxes close when activated again, submenus stay open
+            let close = anchor == PopupAnchor::Below && self.is_open(popup);
+            self.close_popups_above(self.popups_below(index));
+            if !close {
+                self.open_from(index);
+            }
+            return true;
+        }
+        if self.widgets[index].tab_bar().is_some() {
+            self.change_tab(index, true);
+            return true;
+        }
+        if !self.widgets[index].is_menu_item() {
+            return false;
+        }
+        let combo = self.popup_of(index).and_then(|popup| self.combos.iter().find(|(_, &p)| p == popup).map(|(&c, _)| c));
+        match combo {
+            Some(combo) => {
+                let Some(option) = self.popup_items(self.combos[&combo]).iter().position(|&i| i == index) else {
+                    return false;
+                };
+                self.widgets[combo].ty[1] = option as u32;
+                self.write_widget(combo);
+                self.events.push(WidgetEvent::OptionSelected { index: combo, option });
+            }
+            None => self.events.push(WidgetEvent::MenuActivated { index }),
+        }
+        self.close_popups();
+        true
+    }
+
+    /// Close the popups when pressing outside of them and of the widgets that opened them. Returns whether it did.
+    fn dismiss_popups(&mut self) -> bool {
+        if self.open_popups.is_empty() {
+            return false;
+        }
+        let inside = self.hovered.is_some_and(|h| {
+            let popup = self.popup_of(h);
+            self.open_popups.iter().any(|&(p, opener)| popup == Some(p) || opener == Some(h))
+        });
+        if !inside {
+            self.close_popups();
+        }
+        !inside
+    }
+
+    /// Open the context menu of the widget under the cursor, or of the innermost widget containing it with one, at the cursor.
+    /// Returns whether it did.
+    fn open_context_menu(&mut self) -> bool {
+        let popup = self.hovered.and_then(|hovered| {
+            iter::once(hovered).chain(ancestors(&self.widgets, hovered)).find_map(|i| self.context_menus.get(&i).copied())
+        });
+        self.close_popups();
+        let Some(popup) = popup else { return false };
+        self.show_popup(popup, self.cursor_ndc(), None);
+        true
+    }
+
+    /// The menu bar item whose menu is open, if any.
+    fn open_bar_item(&self) -> Option<usize> {
+        let (_, opener) = self.open_popups.first()?;
+        opener.filter(|&o| self.widgets[o].ty[0] == WidgetType::MenuItem.ty() && self.popup_of(o).is_none())
+    }
+
+    /// Follow the cursor in the open menus: highlight the item under it, open the submenu of submenu items,
+    /// and move to the menu of another item of the menu bar.
+    fn hover_menu(&mut self) {
+        let Some(index) = self.hovered.filter(|&i| self.widgets[i].is_menu_item()) else { return };
+        match self.popup_of(index) {
+            Some(popup) if self.is_open(popup) => {
+                self.highlight(Some(index));
+                match self.openers.get(&index) {
+                    Some(&(submenu, _)) => {
+                        if !self.is_open(submenu) {
+                            self.open_from(index);
+                        }
+                    }
+                    None => self.close_popups_above(self.popups_below(index)),
+                }
+            }
+            Some(_) => {}
+            None => {
+                let bar = self.open_bar_item();
+                if bar.is_some_and(|bar| bar != index && self.widgets[bar].parent() == self.widgets[index].parent())
+                    && self.openers.contains_key(&index) {
+                    self.open_from(index);
+                }
+            }
+        }
+    }
+
+    /// Move to the menu of the item `by` items after `item` in its menu bar, wrapping around.
+    fn switch_bar_menu(&mut self, item: usize, by: isize) {
+        let parent = self.widgets[item].parent();
+        let mut bar: Vec<usize> = self.openers.keys().copied()
+            .filter(|&i| self.widgets[i].ty[0] == WidgetType::MenuItem.ty() && self.popup_of(i).is_none() && self.widgets[i].parent() == parent)
+            .collect();
+        bar.sort_unstable();
+        let position = bar.iter().position(|&i| i == item).unwrap() as isize;
+        let next = bar[(position + by).rem_euclid(bar.len() as isize) as usize];
+        self.open_from_key(next);
+    }
+
+    /// Navigate the open menus with the arrows, home and end, choosing with enter or space and closing with escape.
+    /// Also opens the popup of the focused opener with enter, space or the down arrow. Returns whether the event was consumed.
+    /// A focused text input, in a popup or not, keeps all the keys but escape.
+    fn menu_key(&mut self, event: &KeyEvent) -> bool {
+        let Key::Named(key) = event.logical_key else { return false };
+        let typing = self.focused.is_some_and(|f| self.texts.contains_key(&f));
+        if typing && key != NamedKey::Escape {
+            return false;
+        }
+        let pressed = event.state == ElementState::Pressed;
+        let Some(&(popup, opener)) = self.open_popups.last() else {
+            let Some(index) = self.focused.filter(|i| self.openers.contains_key(i)) else { return false };
+            if !matches!(key, NamedKey::Enter | NamedKey::Space | NamedKey::ArrowDown) {
+                return false;
+            }
+            if pressed {
+                self.open_from_key(index);
+            }
+            return true;
+        };
+        if !matches!(key, NamedKey::ArrowUp | NamedKey::ArrowDown | NamedKey::ArrowLeft | NamedKey::ArrowRight
+            | NamedKey::Home | NamedKey::End | NamedKey::Enter | NamedKey::Space | NamedKey::Escape) {
+            return false;
+        }
+        if !pressed {
+            return true;
+        }
+        let items: Vec<usize> = self.popup_items(popup).into_iter().filter(|&i| !self.widgets[i].is_disabled()).collect();
+        let current = self.menu_highlight.and_then(|h| items.iter().position(|&i| i == h));
+        let submenu = self.menu_highlight.filter(|h| self.openers.get(h).is_some_and(|&(_, anchor)| anchor == PopupAnchor::Right));
+        match key {
+            NamedKey::ArrowUp | NamedKey::ArrowDown | NamedKey::Home | NamedKey::End if !items.is_empty() => {
+                let last = items.len() - 1;
+                let next = match (key, current) {
+                    (NamedKey::Home, _) | (NamedKey::ArrowDown, None) => 0,
+                    (NamedKey::ArrowDown, Some(c)) => if c == last { 0 } else { c + 1 },
+                    (NamedKey::ArrowUp, Some(c)) => if c == 0 { last } else { c - 1 },
+                    _ => last,
+                };
+                self.highlight(Some(items[next]));
+            }
+            NamedKey::ArrowRight => match (submenu, self.open_bar_item()) {
+                (Some(item), _) => self.open_from_key(item),
+                (None, Some(bar)) => self.switch_bar_menu(bar, 1),
+                _ => {}
+            },
+            NamedKey::ArrowLeft if self.open_popups.len() > 1 => {
+                self.close_popups_above(self.open_popups.len() - 1);
+                self.highlight(opener);
+            }
+            NamedKey::ArrowLeft => {
+                if let Some(bar) = self.open_bar_item() {
+                    self.switch_bar_menu(bar, -1);
+                }
+            }
+            NamedKey::Enter | NamedKey::Space => match (submenu, self.menu_highlight) {
+                (Some(item), _) => self.open_from_key(item),
+                (None, Some(item)) => self.activate(item),
+                _ => {}
+            },
+            NamedKey::Escape => {
+                self.close_popups_above(self.open_popups.len() - 1);
+                if opener.is_some_and(|o| self.popup_of(o).is_some()) {
+                    self.highlight(opener);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Activate the menu item whose accelerator is pressed, if any. Returns whether the event was consumed.
+    /// Accelerators typed as text are left to the focused text input, if any.
+    fn accelerator_key(&mut self, event: &KeyEvent) -> bool {
+        let typing = self.focused.is_some_and(|f| self.texts.contains_key(&f));
+        let item = self.accelerators.iter()
+            .filter(|(accelerator, _)| !(typing && accelerator.types_text()))
+            .find(|(accelerator, i)| accelerator.matches(&event.logical_key, self.modifiers) && !self.widgets[*i].is_disabled() && !self.blocked(*i))
+            .map(|&(_, i)| i);
+        let Some(item) = item else { return false };
+        if event.state == ElementState::Pressed && !event.repeat {
+            self.activate(item);
+        }
+        true
+    }
+
+    /// Show `page` while the tab `index` is selected, hiding it otherwise.
+    /// The page is usually a scroll view holding the widgets of the tab.
+    pub(super) fn set_tab_page(&mut self, index: usize, page: usize) {
+        self.tab_pages.insert(index, page);
+        let selected = self.widgets[index].ty[1] & TAB_SELECTED != 0;
+        self.set_hidden(page, !selected);
+    }
+
+    /// Select the tab `index`, deselecting the others of its bar.
+    #[allow(unused)]
+    pub(super) fn select_tab(&mut self, index: usize) {
+        self.change_tab(index, false);
+    }
+
+    fn change_tab(&mut self, index: usize, notify: bool) {
+        let Some(bar) = self.widgets[index].tab_bar() else { return };
+        if self.widgets[index].ty[1] & TAB_SELECTED != 0 {
+            return;
+        }
+        let tabs: Vec<usize> = (0..self.widgets.len()).filter(|&i| self.widgets[i].tab_bar() == Some(bar)).collect();
+        for tab in tabs {
+            let selected = tab == index;
+            let ty = &mut self.widgets[tab].ty[1];
+            *ty = if selected { *ty | TAB_SELECTED } else { *ty & !TAB_SELECTED };
+            self.write_widget(tab);
+            if let Some(&page) = self.tab_pages.get(&tab) {
+                self.set_hidden(page, !selected);
+            }
+        }
+        if let Some((dock, panel)) = self.dock_tab(index) {
+            self.docks.get_mut(&dock).unwrap().root.select(panel);
+        }
+        if notify {
+            self.events.push(WidgetEvent::TabSelected { index });
+        }
+    }
+
+    /// Select the previous or next tab of the bar of the focused tab with the left and right arrows, wrapping around.
+    /// Returns whether the event was consumed.
+    fn tab_key(&mut self, event: &KeyEvent) -> bool {
+        let Some((index, bar)) = self.focused.and_then(|i| Some((i, self.widgets[i].tab_bar()?))) else { return false };
+        let by = match event.logical_key {
+            Key::Named(NamedKey::ArrowLeft) => -1,
+            Key::Named(NamedKey::ArrowRight) => 1,
+            _ => return false,
+        };
+        if event.state == ElementState::Pressed {
+            let tabs: Vec<usize> = (0..self.widgets.len())
+                .filter(|&i| self.widgets[i].tab_bar() == Some(bar) && !self.widgets[i].is_disabled())
+                .collect();
+            let Some(position) = tabs.iter().position(|&i| i == index) else { return true };
+            let next = tabs[(position as isize + by).rem_euclid(tabs.len() as isize) as usize];
+            self.focus(Some(next));
+            self.change_tab(next, true);
+        }
+        true
+    }
+
+    /// The overlay the widget `index` is, or is in.
+    fn overlay_of(&self, index: usize) -> Option<usize> {
+        iter::once(index).chain(ancestors(&self.widgets, index)).find(|&i| self.widgets[i].ty[0] == WidgetType::Overlay.ty())
+    }
+
+    /// The size of a pixel in NDC.
+    fn pixel_size(&self) -> [f32; 2] {
+        [2.0 / self.size.width as f32, 2.0 / self.size.height as f32]
+    }
+
+    /// Show `tooltip` once the pointer rests on the widget `index`, or on a widget in it without a tooltip, for `TOOLTIP_DELAY`.
+    pub(super) fn set_tooltip(&mut self, index: usize, tooltip: usize) {
+        self.tooltips.insert(index, tooltip);
+    }
+
+    /// Follow the pointer with the tooltips: start waiting when it moves to a widget with a tooltip,
+    /// and hide the shown one when it leaves its widget, unless it moves onto the tooltip.
+    fn hover_tooltip(&mut self) {
+        if self.tooltip.is_some() && self.hovered.and_then(|h| self.overlay_of(h)) == self.tooltip {
+            return;
+        }
+        let owner = self.hovered.and_then(|hovered| {
+            iter::once(hovered).chain(ancestors(&self.widgets, hovered)).find(|i| self.tooltips.contains_key(i))
+        });
+        if owner == self.tooltip_owner.map(|(o, _)| o) {
+            return;
+        }
+        self.hide_tooltip();
+        self.tooltip_owner = owner.map(|o| (o, Some(web_time::Instant::now())));
+        if owner.is_some() {
+            self.window.request_redraw();
+        }
+    }
+
+    fn hide_tooltip(&mut self) {
+        if let Some(tooltip) = self.tooltip.take() {
+            self.set_hidden(tooltip, true);
+        }
+    }
+
+    /// Show the tooltip of the widget the pointer rests on once it did for `TOOLTIP_DELAY`, redrawing until then.
+    fn update_tooltip(&mut self, now: web_time::Instant) {
+        let Some((owner, Some(since))) = self.tooltip_owner else { return };
+        if self.tooltip.is_some() {
+            return;
+        }
+        if now.saturating_duration_since(since) < TOOLTIP_DELAY {
+            self.window.request_redraw();
+            return;
+        }
+        let tooltip = self.tooltips[&owner];
+        let [x, y] = self.pixel_size();
+        let offset = tooltip_offset(self.widgets[tooltip].limits, self.cursor_ndc(), [TOOLTIP_OFFSET[0] * x, TOOLTIP_OFFSET[1] * y]);
+        self.move_by(tooltip, offset);
+        self.set_hidden(tooltip, false);
+        self.tooltip = Some(tooltip);
+    }
+
+    /// Show `popover` on `side` of the widget `index` when it is activated, hiding it when activated again.
+    pub(super) fn set_popover(&mut self, index: usize, popover: usize, side: Side) {
+        self.popovers.insert(index, (popover, side));
+    }
+
+    /// Show `popover` on `side` of the widget `anchor`, following it when it scrolls.
+    /// It goes on the opposite side if it doesn't fit on `side`.
+    pub(super) fn show_popover(&mut self, popover: usize, anchor: usize, side: Side) {
+        self.open_popovers.retain(|&(p, ..)| p != popover);
+        self.open_popovers.push((popover, anchor, side));
+        self.place_popover(popover, anchor, side);
+        self.set_hidden(popover, false);
+    }
+
+    /// Hide `popover`, if shown.
+    pub(super) fn hide_popover(&mut self, popover: usize) {
+        let Some(position) = self.open_popovers.iter().position(|&(p, ..)| p == popover) else { return };
+        self.open_popovers.remove(position);
+        self.set_hidden(popover, true);
+        if self.focused.is_some_and(|f| self.overlay_of(f) == Some(popover)) {
+            self.focus(None);
+        }
+    }
+
+    fn place_popover(&mut self, popover: usize, anchor: usize, side: Side) {
+        let [x, y] = self.pixel_size();
+        let offset = popover_offset(self.widgets[popover].limits, self.screen_limits(anchor), side, [POPOVER_GAP * x, POPOVER_GAP * y]);
+        self.move_by(popover, offset);
+    }
+
+    /// Move the shown popovers next to their anchors again, after these moved.
+    fn place_popovers(&mut self) {
+        for (popover, anchor, side) in self.open_popovers.clone() {
+            self.place_popover(popover, anchor, side);
+        }
+    }
+
+    /// Show or hide the popover of the widget `index`, if it has one. Returns whether it has.
+    fn toggle_popover(&mut self, index: usize) -> bool {
+        let Some(&(popover, side)) = self.popovers.get(&index) else { return false };
+        if self.open_popovers.iter().any(|&(p, ..)| p == popover) {
+            self.hide_popover(popover);
+        } else {
+            self.show_popover(popover, index, side);
+        }
+        true
+    }
+
+    /// Hide the popovers when pressing outside of them and of their anchors. The press still goes to the widget pressed.
+    fn dismiss_popovers(&mut self) {
+        let hovered_overlay = self.hovered.and_then(|h| self.overlay_of(h));
+        let dismissed: Vec<usize> = self.open_popovers.iter()
+            .filter(|&&(popover, anchor, _)| hovered_overlay != Some(popover) && self.hovered != Some(anchor))
+            .map(|&(popover, ..)| popover)
+            .collect();
+        for popover in dismissed {
+            self.hide_popover(popover);
+        }
+    }
+
+    /// Hide the last shown popover with escape. Returns whether the event was consumed.
+    fn popover_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(&(popover, ..)) = self.open_popovers.last() else { return false };
+        if event.logical_key != Key::Named(NamedKey::Escape) {
+            return false;
+        }
+        if event.state == ElementState::Pressed {
+            self.hide_popover(popover);
+        }
+        true
+    }
+
+    /// Make the dialog `index`, created with `layout.widgets(index)`, a stock dialog closed by its buttons.
+    pub(super) fn set_dialog(&mut self, index: usize, layout: &DialogLayout) {
+        self.dialogs.insert(index, layout.parts(index));
+    }
+
+    /// Whether the widget `index` is under the topmost dialog, not getting input.
+    fn blocked(&self, index: usize) -> bool {
+        let Some(modal) = self.modals.last() else { return false };
+        // Popups and overlays are above the dialog when opened from it, following the widgets that opened them
+        let mut index = index;
+        for _ in 0..=self.open_popups.len() + self.open_popovers.len() + 1 {
+            if iter::once(index).chain(ancestors(&self.widgets, index)).any(|i| i == modal.dialog) {
+                return false;
+            }
+            let opener = if let Some(popup) = self.popup_of(index) {
+                match self.open_popups.iter().find(|&&(p, _)| p == popup) {
+                    Some(&(_, Some(opener))) => opener,
+                    // Showing and closing dialogs closes the popups, so those opened at a point were opened over the topmost one
+                    Some(&(_, None)) => return false,
+                    None => return true,
+                }
+            } else if let Some(overlay) = self.overlay_of(index) {
+                match self.open_popovers.iter().find(|&&(p, ..)| p == overlay) {
+                    Some(&(_, anchor, _)) => anchor,
+                    None => match self.tooltip_owner {
+                        Some((owner, _)) if self.tooltip == Some(overlay) => owner,
+                        _ => return true,
+                    },
+                }
+            } else {
+                return true;
+            };
+            index = opener;
+        }
+        true
+    }
+
+    /// Show the dialog `index` above the other widgets, dimming them and keeping the pointer and keyboard from them,
+    /// until it is closed. `on_close` is then called with how it was closed.
+    /// Prompts start empty. The focus goes to their input, to the OK button, or to the first widget of the dialog taking it.
+    pub(super) fn show_dialog(&mut self, index: usize, on_close: impl FnOnce(DialogResult) + 'static) {
+        self.close_popups();
+        self.hide_tooltip();
+        for (popover, ..) in self.open_popovers.clone() {
+            self.hide_popover(popover);
+        }
+        if let Some(pressed) = self.pressed.take() {
+            self.set_state(pressed, STATE_PRESSED, false);
+        }
+        let parts = self.dialogs.get(&index).copied();
+        if let Some(input) = parts.and_then(|p| p.input) {
+            self.texts.insert(input, TextEdit::default());
+            self.sync_text(input);
+        }
+        self.modals.push(Modal { dialog: index, on_close: Some(Box::new(on_close)), restore_focus: self.focused });
+        self.set_hidden(index, false);
+        if self.hovered.is_some_and(|h| self.blocked(h)) {
+            self.hover(None);
+        }
+        let first = parts.map(|p| p.input.unwrap_or(p.ok))
+            .or_else(|| (index..self.widgets.len()).find(|&i| self.is_focusable(i)));
+        self.focus(first);
+    }
+
+    /// Show the dialog `index` like `show_dialog`, returning a future resolving to how it was closed.
+    #[allow(unused)]
+    pub(super) fn ask(&mut self, index: usize) -> DialogFuture {
+        let (future, on_close) = DialogFuture::new();
+        self.show_dialog(index, on_close);
+        future
+    }
+
+    /// Close the topmost dialog, calling its callback with `result`, and give the focus back to the widget that had it.
+    pub(super) fn close_dialog(&mut self, result: DialogResult) {
+        let Some(modal) = self.modals.pop() else { return };
+        self.close_popups();
+        self.set_hidden(modal.dialog, true);
+        self.focus(modal.restore_focus);
+        self.events.push(WidgetEvent::DialogClosed { index: modal.dialog });
+        if let Some(on_close) = modal.on_close {
+            on_close(result);
+        }
+    }
+
+    /// Click the button `index`: close its stock dialog if it is one of its buttons, or report it.
+    fn click(&mut self, index: usize) {
+        let Some(modal) = self.modals.last() else {
+            self.events.push(WidgetEvent::Clicked { index });
+            return;
+        };
+        match self.dialogs.get(&modal.dialog).copied() {
+            Some(parts) if parts.ok == index => {
+                let result = match parts.input {
+                    Some(input) => DialogResult::Text(self.texts[&input].text().to_string()),
+                    None => DialogResult::Ok,
+                };
+                self.close_dialog(result);
+            }
+            Some(parts) if parts.cancel == Some(index) => self.close_dialog(DialogResult::Cancel),
+            _ => self.events.push(WidgetEvent::Clicked { index }),
+        }
+    }
+
+    /// Click the focused button with space or enter. Returns whether the event was consumed.
+    fn button_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused.filter(|&i| self.widgets[i].ty[0] == WidgetType::Button.ty()) else { return false };
+        if !matches!(event.logical_key, Key::Named(NamedKey::Space | NamedKey::Enter)) {
+            return false;
+        }
+        if event.state == ElementState::Pressed && !event.repeat {
+            self.activate(index);
+        }
+        true
+    }
+
+    /// Cancel the shown dialog with escape, or press its OK button with enter. Returns whether the event was consumed.
+    fn dialog_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(dialog) = self.modals.last().map(|m| m.dialog) else { return false };
+        let pressed = event.state == ElementState::Pressed && !event.repeat;
+        match event.logical_key {
+            Key::Named(NamedKey::Escape) => {
+                if pressed {
+                    self.close_dialog(DialogResult::Cancel);
+                }
+                true
+            }
+            Key::Named(NamedKey::Enter) => {
+                let Some(parts) = self.dialogs.get(&dialog).copied() else { return false };
+                if pressed {
+                    self.activate(parts.ok);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the widget `index` can get the focus with the tab key.
+    fn is_focusable(&self, index: usize) -> bool {
+        let widget = &self.widgets[index];
+        widget.is_focusable() && !widget.is_disabled() && self.is_shown(index) && !self.blocked(index)
+    }
+
+    /// Move the focus to the next widget taking it with tab, or to the previous one with shift and tab, wrapping around.
+    /// While a dialog is shown, the focus stays in it. Returns whether the event was consumed.
+    fn focus_key(&mut self, event: &KeyEvent) -> bool {
+        if event.logical_key != Key::Named(NamedKey::Tab) {
+            return false;
+        }
+        if event.state == ElementState::Pressed {
+            let focusable: Vec<usize> = (0..self.widgets.len()).filter(|&i| self.is_focusable(i)).collect();
+            let len = focusable.len();
+            if len == 0 {
+                return true;
+            }
+            let back = self.modifiers.shift_key();
+            let next = match self.focused.and_then(|f| focusable.iter().position(|&i| i == f)) {
+                Some(position) if back => (position + len - 1) % len,
+                Some(position) => (position + 1) % len,
+                None if back => len - 1,
+                None => 0,
+            };
+            self.focus(Some(focusable[next]));
+            self.scroll_to(focusable[next]);
+        }
+        true
+    }
+
+    /// Make the widgets created with `layout.widgets()` at `index` a color picker, its color being opaque white.
+    pub(super) fn set_color_picker(&mut self, index: usize, layout: &ColorPickerLayout) {
+        self.color_pickers.insert(index, ColorPicker::new(layout, index));
+        self.sync_color(index, None);
+    }
+
+    /// The color of the color picker `index`, as RGBA from 0 to 1.
+    #[allow(unused)]
+    pub(super) fn color(&self, index: usize) -> [f32; 4] {
+        self.color_pickers[&index].color.rgba()
+    }
+
+    /// Set the color of the color picker `index`, as RGBA from 0 to 1.
+    pub(super) fn set_color(&mut self, index: usize, rgba: [f32; 4]) {
+        let picker = self.color_pickers.get_mut(&index).unwrap();
+        picker.color = Hsva::from_rgba(rgba, picker.color);
+        self.sync_color(index, None);
+    }
+
+    /// Set the color of the swatch `index`, as RGBA from 0 to 1.
+    pub(super) fn set_swatch(&mut self, index: usize, color: [f32; 4]) {
+        self.widgets[index].ty[1] = pack_rgba(color);
+        self.write_widget(index);
+    }
+
+    /// The color picker the widget `index` is a part of.
+    fn color_picker_of(&self, index: usize) -> Option<usize> {
+        self.color_pickers.iter().find_map(|(&picker, p)| p.contains(index).then_some(picker))
+    }
+
+    /// Move the marker of the color area or the thumb of the color slider `index` under the pointer,
+    /// if it is a part of a color picker.
+    fn drag_color(&mut self, index: usize) {
+        let Some(picker) = self.color_picker_of(index) else { return };
+        let [left, top, right, bottom] = pixel_limits(self.screen_limits(index), self.size);
+        let (x, y) = (self.cursor_pos.x as f32, self.cursor_pos.y as f32);
+        let p = self.color_pickers.get_mut(&picker).unwrap();
+        let old = p.color;
+        if index == p.parts.area {
+            match p.shape {
+                PickerShape::Square => p.set_area([(x - left) / (right - left).max(1.0), (y - top) / (bottom - top).max(1.0)]),
+                PickerShape::Wheel => {
+                    let radius = ((right - left).min(bottom - top) * 0.5).max(1.0);
+                    p.set_area([(x - (left + right) * 0.5) / radius, (y - (top + bottom) * 0.5) / radius]);
+                }
+            }
+        } else if let Some(channel) = p.channel_of(index) {
+            let fraction = if self.widgets[index].ty[2] & COLOR_VERTICAL != 0 {
+                (bottom - y) / (bottom - top).max(1.0)
+            } else {
+                (x - left) / (right - left).max(1.0)
+            };
+            p.set_channel(channel, fraction);
+        }
+        if p.color != old {
+            self.change_color(picker, None);
+        }
+    }
+
+    /// Move the marker of the focused color area with the arrow keys, or the thumb of the focused color slider
+    /// with the arrow, page, home and end keys. Returns whether the event was consumed.
+    fn color_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused else { return false };
+        let Some(picker) = self.color_picker_of(index) else { return false };
+        let p = self.color_pickers.get_mut(&picker).unwrap();
+        if event.state != ElementState::Pressed || (index != p.parts.area && p.channel_of(index).is_none()) {
+            return false;
+        }
+        let old = p.color;
+        if index == p.parts.area {
+            let steps = match event.logical_key {
+                Key::Named(NamedKey::ArrowRight) => [1.0, 0.0],
+                Key::Named(NamedKey::ArrowLeft) => [-1.0, 0.0],
+                Key::Named(NamedKey::ArrowUp) => [0.0, 1.0],
+                Key::Named(NamedKey::ArrowDown) => [0.0, -1.0],
+                _ => return false,
+            };
+            p.step_area(steps);
+        } else {
+            let channel = p.channel_of(index).unwrap();
+            let value = p.channel(channel);
+            let value = match event.logical_key {
+                Key::Named(NamedKey::ArrowRight | NamedKey::ArrowUp) => value + 0.01,
+                Key::Named(NamedKey::ArrowLeft | NamedKey::ArrowDown) => value - 0.01,
+                Key::Named(NamedKey::PageUp) => value + 0.1,
+                Key::Named(NamedKey::PageDown) => value - 0.1,
+                Key::Named(NamedKey::Home) => 0.0,
+                Key::Named(NamedKey::End) => 1.0,
+                _ => return false,
+            };
+            p.set_channel(channel, value);
+        }
+        if p.color != old {
+            self.change_color(picker, None);
+        }
+        true
+    }
+
+    /// Give a color picker the color of its swatch `index`, if it is one. Returns whether it was one.
+    fn pick_swatch(&mut self, index: usize) -> bool {
+        let Some(picker) = self.color_picker_of(index) else { return false };
+        let p = self.color_pickers.get_mut(&picker).unwrap();
+        if !p.parts.swatches.contains(&index) {
+            return false;
+        }
+        let rgba = unpack_rgba(self.widgets[index].ty[1]);
+        let old = p.color;
+        p.color = Hsva::from_rgba(rgba, old);
+        if p.color != old {
+            self.change_color(picker, None);
+        }
+        true
+    }
+
+    /// Give a color picker the color typed in its hex, red, green or blue field `index`, if it is one and the color is valid.
+    fn edit_color_field(&mut self, index: usize) {
+        let Some(picker) = self.color_picker_of(index) else { return };
+        let p = self.color_pickers.get_mut(&picker).unwrap();
+        let text = self.texts[&index].text();
+        let mut rgba = p.color.rgba();
+        if index == p.parts.hex {
+            let Some(color) = parse_hex(text) else { return };
+            rgba = color;
+        } else if let Some(channel) = p.parts.rgb.iter().position(|&i| i == index) {
+            let Ok(value) = text.trim().parse::<u8>() else { return };
+            rgba[channel] = value as f32 / 255.0;
+        } else {
+            return;
+        }
+        let old = p.color;
+        p.color = Hsva::from_rgba(rgba, old);
+        if p.color != old {
+            self.change_color(picker, Some(index));
+        }
+    }
+
+    /// Show the new color of the color picker `index` and report it, leaving the field being typed in, if any, as it is.
+    fn change_color(&mut self, index: usize, editing: Option<usize>) {
+        self.sync_color(index, editing);
+        let color = self.color_pickers[&index].color.rgba();
+        self.events.push(WidgetEvent::ColorChanged { index, color });
+    }
+
+    /// Upload the parts of the color picker `index` showing its color, and write it in its fields but `editing`.
+    fn sync_color(&mut self, index: usize, editing: Option<usize>) {
+        let p = &self.color_pickers[&index];
+        let parts = p.parts.clone();
+        let rgba = p.color.rgba();
+        let [area_color, area_marker] = p.area_ty();
+        self.widgets[parts.area].ty[1] = area_color;
+        self.widgets[parts.area].ty[2] = area_marker;
+        for slider in [parts.channel, parts.alpha] {
+            let channel = p.channel_of(slider).unwrap();
+            let widget = &mut self.widgets[slider];
+            widget.ty[1] = p.slider_color(channel);
+            widget.ty[2] = (widget.ty[2] & !0xffff) | fraction(p.channel(channel));
+        }
+        self.widgets[parts.preview].ty[1] = pack_rgba(rgba);
+        let mut fields = vec![(parts.hex, to_hex(rgba))];
+        fields.extend(parts.rgb.iter().zip(rgba).map(|(&i, c)| (i, ((c * 255.0).round() as u8).to_string())));
+        for (field, text) in fields {
+            if Some(field) != editing {
+                self.texts.get_mut(&field).unwrap().set_text(&text);
+                self.sync_text(field);
+            }
+        }
+        for part in [parts.area, parts.channel, parts.alpha, parts.preview] {
+            self.write_widget(part);
+        }
+    }
+
+    /// Set the parameters of the custom widget `index`, see `CustomWidgets::pack`.
+    pub(super) fn set_params(&mut self, index: usize, values: &[ParamValue]) {
+        let ty = self.custom.custom_type(self.widgets[index].ty[0]).unwrap();
+        let [params1, params2] = self.custom.pack(ty, values);
+        self.widgets[index].ty[1] = params1;
+        self.widgets[index].ty[2] = params2;
+        self.write_widget(index);
+    }
+
+    /// Show `scene` in the viewport `index`.
+    pub(super) fn set_viewport(&mut self, index: usize, scene: impl Viewport + 'static) {
+        self.viewports.insert(index, ViewportModel::new(Box::new(scene)));
+        self.window.request_redraw();
+    }
+
+    /// Resize the textures of the shown viewports to their widgets, then let their scenes draw to them.
+    fn draw_viewports(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let shown: Vec<usize> = self.viewports.keys().copied().filter(|&i| self.is_shown(i)).collect();
+        for index in shown {
+            let [left, top, right, bottom] = pixel_limits(self.screen_limits(index), self.size);
+            let size = [(right - left).round().max(1.0) as u32, (bottom - top).round().max(1.0) as u32];
+            let viewport = self.viewports.get_mut(&index).unwrap();
+            if viewport.size != size {
+                let view = viewport.scene.resize(&self.device, size);
+                viewport.bind_group = Some(create_textures_bind_group(&self.device, &self.textures_bind_group_layout, &self.backdrop, &view, &self.nearest_sampler));
+                viewport.view = Some(view);
+                viewport.size = size;
+            }
+            viewport.scene.draw(&self.device, &self.queue, encoder);
+        }
+    }
+
+    /// Give `event` to the scene of the viewport `index`, drawing it again if it was consumed.
+    fn viewport_input(&mut self, index: usize, event: ViewportEvent) -> bool {
+        let consumed = self.viewports.get_mut(&index).unwrap().scene.input(&event);
+        if consumed {
+            self.window.request_redraw();
+        }
+        consumed
+    }
+
+    /// The pointer in pixels from the top left corner of the widget `index`.
+    fn local_pointer(&self, index: usize) -> [f32; 2] {
+        let [left, top, _, _] = pixel_limits(self.screen_limits(index), self.size);
+        [self.cursor_pos.x as f32 - left, self.cursor_pos.y as f32 - top]
+    }
+
+    /// The viewport the pointer is over, or the one pressed, which captures it until released.
+    fn viewport_under_pointer(&self) -> Option<usize> {
+        match self.pressed {
+            Some(pressed) => Some(pressed),
+            None => self.hovered,
+        }.filter(|i| self.viewports.contains_key(i))
+    }
+
+    /// Tell the viewport under the pointer where it is, and the one it was over before that it left.
+    fn move_viewport_pointer(&mut self) {
+        let viewport = self.viewport_under_pointer();
+        if let Some(left) = self.pointer_viewport.filter(|&v| Some(v) != viewport) {
+            self.viewport_input(left, ViewportEvent::PointerLeft);
+        }
+        self.pointer_viewport = viewport;
+        if let Some(index) = viewport {
+            let position = self.local_pointer(index);
+            self.viewport_input(index, ViewportEvent::PointerMoved { position });
+        }
+    }
+
+    /// Give a mouse button to the viewport under the pointer. Returns whether it was consumed.
+    fn viewport_button(&mut self, button: MouseButton, pressed: bool) -> bool {
+        let Some(index) = self.viewport_under_pointer() else { return false };
+        let position = self.local_pointer(index);
+        self.viewport_input(index, ViewportEvent::Button { button, pressed, position })
+    }
+
+    /// Give the wheel to the viewport under the pointer. Returns whether it was consumed.
+    fn viewport_wheel(&mut self, delta: [f32; 2]) -> bool {
+        let Some(index) = self.hovered.filter(|i| self.viewports.contains_key(i)) else { return false };
+        let position = self.local_pointer(index);
+        self.viewport_input(index, ViewportEvent::Wheel { delta, position })
+    }
+
+    /// Give the keys to the focused viewport. Returns whether the event was consumed.
+    fn viewport_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused.filter(|i| self.viewports.contains_key(i)) else { return false };
+        self.viewport_input(index, ViewportEvent::Key(event.clone()))
+    }
+
+    /// Show `series` in the chart `index`, the first `MAX_CHART_SERIES` of them, fitting its view to them.
+    pub(super) fn set_series(&mut self, index: usize, series: Vec<Series>) {
+        let chart = self.charts.get_mut(&index).unwrap();
+        chart.series = series;
+        chart.series.truncate(MAX_CHART_SERIES);
+        chart.view = chart.fitted_view();
+        chart.hovered = None;
+        self.write_points();
+    }
+
+    /// The data shown by the chart `index`, as (Xmin, Xmax, Ymin, Ymax).
+    #[allow(unused)]
+    pub(super) fn chart_view(&self, index: usize) -> [f32; 4] {
+        self.charts[&index].view
+    }
+
+    /// Show the data in `view` in the chart `index`, as (Xmin, Xmax, Ymin, Ymax).
+    #[allow(unused)]
+    pub(super) fn set_chart_view(&mut self, index: usize, view: [f32; 4]) {
+        self.charts.get_mut(&index).unwrap().view = view;
+        self.write_chart(index);
+    }
+
+    /// Show all the points of the chart `index`.
+    pub(super) fn fit_chart(&mut self, index: usize) {
+        let chart = self.charts.get_mut(&index).unwrap();
+        chart.view = chart.fitted_view();
+        self.write_chart(index);
+    }
+
+    /// Upload the points of all the charts, growing the data buffer if needed, and their headers.
+    fn write_points(&mut self) {
+        let mut charts: Vec<usize> = self.charts.keys().copied().collect();
+        charts.sort_by_key(|&i| self.widgets[i].ty[1]);
+        let mut points: Vec<[f32; 2]> = vec![];
+        for &index in &charts {
+            let chart = self.charts.get_mut(&index).unwrap();
+            chart.start = points.len() as u32;
+            for series in &chart.series {
+                points.extend_from_slice(series.points());
+            }
+        }
+        if points.len() > self.data_layout.points_capacity {
+            self.data_layout.points_capacity = points.len().next_power_of_two();
+            self.grow_data_buffer();
+            return;
+        }
+        self.queue.write_buffer(&self.data_buffer, self.data_layout.points_offset(), bytemuck::cast_slice(&points));
+        for index in charts {
+            self.write_chart(index);
+        }
+    }
+
+    /// Upload the header of the chart `index`.
+    fn write_chart(&mut self, index: usize) {
+        self.queue.write_buffer(
+            &self.data_buffer,
+            self.data_layout.chart_offset(self.widgets[index].ty[1] as usize),
+            bytemuck::cast_slice(&[self.charts[&index].header()]),
+        );
+        self.window.request_redraw();
+    }
+
+    /// The plot of the chart `index` on the screen.
+    fn chart_plot(&self, index: usize) -> Plot {
+        Plot::new(pixel_limits(self.screen_limits(index), self.size), self.charts[&index].view)
+    }
+
+    /// Show the readout of the point nearest to the pointer in the hovered chart, hiding the others.
+    fn hover_chart(&mut self) {
+        let p = [self.cursor_pos.x as f32, self.cursor_pos.y as f32];
+        let charts: Vec<usize> = self.charts.keys().copied().collect();
+        for index in charts {
+            let point = if self.hovered == Some(index) && self.chart_drag.is_none() {
+                let plot = self.chart_plot(index);
+                plot.contains(p).then(|| self.charts[&index].nearest(&plot, p, CHART_HOVER_RADIUS)).flatten()
+            } else {
+                None
+            };
+            let chart = self.charts.get_mut(&index).unwrap();
+            if chart.hovered != point {
+                chart.hovered = point;
+                self.write_chart(index);
+                self.events.push(WidgetEvent::PointHovered { index, point });
+            }
+        }
+    }
+
+    /// Zoom the chart under the pointer around it with the vertical wheel, and pan it with the horizontal one.
+    /// Returns whether the pointer is over the plot of a chart.
+    fn zoom_chart(&mut self, delta: [f32; 2]) -> bool {
+        let Some(index) = self.hovered.filter(|i| self.charts.contains_key(i)) else { return false };
+        let plot = self.chart_plot(index);
+        let p = [self.cursor_pos.x as f32, self.cursor_pos.y as f32];
+        if !plot.contains(p) {
+            return false;
+        }
+        let factor = (-delta[1] * CHART_ZOOM_SPEED).exp();
+        let by = plot.delta([-delta[0], 0.0]);
+        let chart = self.charts.get_mut(&index).unwrap();
+        chart.zoom(plot.to_data(p), [factor, factor]);
+        chart.pan(by);
+        self.write_chart(index);
+        self.hover_chart();
+        true
+    }
+
+    /// Start panning the chart `index` with the pointer, if it was pressed in its plot.
+    fn begin_chart_drag(&mut self, index: usize) {
+        let Some(chart) = self.charts.get(&index) else { return };
+        if self.chart_plot(index).contains([self.cursor_pos.x as f32, self.cursor_pos.y as f32]) {
+            self.chart_drag = Some(ChartDrag { chart: index, start: [self.cursor_pos.x, self.cursor_pos.y], view: chart.view });
+        }
+    }
+
+    /// Move the view of the dragged chart with the pointer.
+    fn drag_chart(&mut self) {
+        let Some(drag) = &self.chart_drag else { return };
+        let index = drag.chart;
+        let by = Plot::new(pixel_limits(self.screen_limits(index), self.size), drag.view)
+            .delta([(self.cursor_pos.x - drag.start[0]) as f32, (self.cursor_pos.y - drag.start[1]) as f32]);
+        let chart = self.charts.get_mut(&index).unwrap();
+        chart.view = drag.view;
+        chart.pan([-by[0], -by[1]]);
+        self.write_chart(index);
+    }
+
+    /// Pan the focused chart with the arrow keys, zoom it with plus and minus, and show all its points with home.
+    /// Returns whether the event was consumed.
+    fn chart_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused.filter(|i| self.charts.contains_key(i)) else { return false };
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        if event.logical_key == Key::Named(NamedKey::Home) {
+            self.fit_chart(index);
+            return true;
+        }
+        let chart = self.charts.get_mut(&index).unwrap();
+        let [left, right, bottom, top] = chart.view;
+        let center = [(left + right) * 0.5, (bottom + top) * 0.5];
+        let (width, height) = ((right - left) * 0.1, (top - bottom) * 0.1);
+        match &event.logical_key {
+            Key::Named(NamedKey::ArrowRight) => chart.pan([width, 0.0]),
+            Key::Named(NamedKey::ArrowLeft) => chart.pan([-width, 0.0]),
+            Key::Named(NamedKey::ArrowUp) => chart.pan([0.0, height]),
+            Key::Named(NamedKey::ArrowDown) => chart.pan([0.0, -height]),
+            Key::Character(c) if c == "+" || c == "=" => chart.zoom(center, [0.8, 0.8]),
+            Key::Character(c) if c == "-" => chart.zoom(center, [1.25, 1.25]),
+            _ => return false,
+        }
+        self.write_chart(index);
+        true
+    }
+
+    /// Show `canvas` in the canvas `index`, replacing what it drew before.
+    pub(super) fn set_canvas(&mut self, index: usize, canvas: Canvas) {
+        self.canvases.get_mut(&index).unwrap().canvas = canvas;
+        self.primitives_dirty = true;
+        self.window.request_redraw();
+    }
+
+    /// Size of the canvas `index` on the screen in pixels, the extent of its drawing.
+    pub(super) fn canvas_size(&self, index: usize) -> [f32; 2] {
+        let [left, top, right, bottom] = pixel_limits(self.widgets[index].limits, self.size);
+        [right - left, bottom - top]
+    }
+
+    /// Upload the primitives of all the canvases then the glyphs of the text inputs, growing the data buffer if needed.
+    fn write_primitives(&mut self) {
+        self.primitives_dirty = false;
+        let mut primitives: Vec<CanvasPrimitive> = vec![];
+        for models in [&mut self.canvases, &mut self.text_glyphs] {
+            let mut indices: Vec<usize> = models.keys().copied().collect();
+            indices.sort();
+            for index in indices {
+                let model = models.get_mut(&index).unwrap();
+                model.start = primitives.len() as u32;
+                primitives.extend_from_slice(model.canvas.primitives());
+            }
+        }
+        if primitives.len() > self.data_layout.canvas_capacity {
+            self.data_layout.canvas_capacity = primitives.len().next_power_of_two();
+            self.grow_data_buffer();
+            return;
+        }
+        self.queue.write_buffer(&self.data_buffer, self.data_layout.canvas_offset(), bytemuck::cast_slice(&primitives));
+        self.window.request_redraw();
+    }
+
+    /// Replace the data buffer by one fitting `data_layout`, uploading the charts, their points and the primitives again.
+    fn grow_data_buffer(&mut self) {
+        self.data_buffer = create_data_buffer(&self.device, &self.queue, self.data_layout);
+        self.recreate_widgets_bind_group();
+        self.write_points();
+        self.write_primitives();
+    }
+
+    /// Bind the buffers again after one of them was grown.
+    fn recreate_widgets_bind_group(&mut self) {
+        self.widgets_bind_group = create_widgets_bind_group(&self.device, &self.widgets_bind_group_layout, [
+            &self.widgets_buffer, &self.styles_buffer, &self.images_buffer, &self.paths_buffer, &self.data_buffer,
+        ]);
+    }
+
+    /// Track the hit region under the pointer, `region` being the one picked with the hovered widget, reporting its changes.
+    fn hover_region(&mut self, region: Option<u32>) {
+        let hit = self.hovered.filter(|i| self.canvases.contains_key(i)).zip(region);
+        let previous = mem::replace(&mut self.hovered_region, hit);
+        if previous == hit {
+            return;
+        }
+        if let Some((index, _)) = previous.filter(|&(i, _)| hit.map(|(index, _)| index) != Some(i)) {
+            self.events.push(WidgetEvent::RegionHovered { index, region: None });
+        }
+        if let Some((index, region)) = hit {
+            self.events.push(WidgetEvent::RegionHovered { index, region: Some(region) });
+        }
+    }
+
+    /// Place the widgets of `root` and its descendants in the window, and again whenever it is resized.
+    /// Widgets in the placed ones move with them; the layout is set again when its tree changes.
+    pub(super) fn set_flex(&mut self, root: FlexNode) {
+        self.flex = Some(root);
+        self.layout_flex();
+    }
+
+    /// Place the widgets of the flexible layout in the window, converting its pixels to NDC.
+    fn layout_flex(&mut self) {
+        let Some(root) = &self.flex else {
+            return;
+        };
+        let [width, height] = [self.size.width.max(1) as f32, self.size.height.max(1) as f32];
+        let placed: Vec<(usize, [f32; 4])> = root.layout([width, height]).into_iter()
+            .map(|(index, [left, top, right, bottom])| {
+                (index, [left / width * 2.0 - 1.0, right / width * 2.0 - 1.0, 1.0 - bottom / height * 2.0, 1.0 - top / height * 2.0])
+            })
+            .collect();
+        self.place_widgets(&placed);
+    }
+
+    /// Make the widgets created with `layout.widgets(index)` a dock, arranging its panels.
+    pub(super) fn set_dock(&mut self, index: usize, layout: &DockLayout) {
+        for panel in 0..layout.panel_count() {
+            self.tab_pages.insert(layout.tab(index, panel), layout.panel(index, panel));
+        }
+        self.docks.insert(index, Dock::new(layout.clone()));
+        self.layout_dock(index);
+    }
+
+    /// The arrangement of the panels of the dock `index`, to be written with `to_string` and restored with `set_dock_layout`.
+    pub(super) fn dock_layout(&self, index: usize) -> &DockNode {
+        &self.docks[&index].root
+    }
+
+    /// Arrange the panels of the dock `index` as `root`, which must hold each of them once.
+    pub(super) fn set_dock_layout(&mut self, index: usize, root: DockNode) -> Result<(), DockError> {
+        let dock = self.docks.get_mut(&index).unwrap();
+        root.validate(dock.layout.panel_count())?;
+        dock.root = root;
+        self.layout_dock(index);
+        Ok(())
+    }
+
+    /// Place the tabs, panels and dividers of the dock `index` as arranged, hiding the collapsed ones and the unused dividers.
+    fn layout_dock(&mut self, index: usize) {
+        let pixel = self.pixel_size();
+        let dock = self.docks.get_mut(&index).unwrap();
+        dock.place(pixel);
+        let dock = &self.docks[&index];
+        let mut tabs = vec![];
+        for group in &dock.placement.groups {
+            let shown = !is_empty(group.limits);
+            // The tabs of a group form a bar, named after its first tab
+            let bar = DOCK_TAB_BARS | dock.layout.tab(index, group.panels[0]) as u32;
+            for (i, &panel) in group.panels.iter().enumerate() {
+                let (tab, limits) = dock.tab_and_panel(group, i);
+                tabs.push((panel, tab, limits, bar, shown, panel == group.selected));
+            }
+        }
+        let dividers: Vec<Option<([f32; 4], u32)>> = (1..dock.layout.panel_count()).map(|divider| {
+            dock.placement.dividers.get(divider - 1).map(|d| {
+                let vertical = if let Orientation::Horizontal = d.orientation { DIVIDER_VERTICAL } else { 0 };
+                (d.limits, vertical)
+            })
+        }).collect();
+        let layout = dock.layout.clone();
+        let mut panels = vec![];
+        for (panel, tab_limits, panel_limits, bar, shown, selected) in tabs {
+            let tab = layout.tab(index, panel);
+            self.widgets[tab].limits = tab_limits;
+            self.widgets[tab].ty[1] = bar << TAB_BAR_SHIFT | if selected { TAB_SELECTED } else { 0 };
+            self.write_widget(tab);
+            self.set_hidden(tab, !shown || is_empty(tab_limits));
+            let page = layout.panel(index, panel);
+            if shown {
+                panels.push((page, panel_limits));
+            }
+            self.set_hidden(page, !shown || !selected);
+        }
+        for (divider, place) in dividers.into_iter().enumerate() {
+            let widget = layout.divider(index, divider);
+            if let Some((limits, vertical)) = place {
+                self.widgets[widget].limits = limits;
+                self.widgets[widget].ty[1] = vertical;
+                self.write_widget(widget);
+            }
+            self.set_hidden(widget, place.is_none_or(|(limits, _)| is_empty(limits)));
+        }
+        self.place_widgets(&panels);
+    }
+
+    /// Move the widgets to their limits in NDC, in order, with the widgets in them, which keep their size.
+    /// List, grid and table views and docks only move, keeping their own size, and scroll views get the extent of their content again.
+    fn place_widgets(&mut self, placed: &[(usize, [f32; 4])]) {
+        let mut children = vec![vec![]; self.widgets.len()];
+        for (i, widget) in self.widgets.iter().enumerate() {
+            if let Some(parent) = widget.parent() {
+                children[parent].push(i);
+            }
+        }
+        let mut docks = vec![];
+        let mut changed = vec![];
+        for &(index, limits) in placed {
+            let old = self.widgets[index].limits;
+            let offset = [limits[0] - old[0], limits[3] - old[3]];
+            let keeps_size = self.virtual_views.contains_key(&index) || self.tables.contains_key(&index) || self.docks.contains_key(&index);
+            self.widgets[index].limits = if keeps_size {
+                [old[0] + offset[0], old[1] + offset[0], old[2] + offset[1], old[3] + offset[1]]
+            } else {
+                limits
+            };
+            if self.widgets[index].limits != old {
+                changed.push(index);
+            }
+            if offset == [0.0; 2] {
+                continue;
+            }
+            let mut moved = vec![index];
+            let mut stack = children[index].clone();
+            while let Some(i) = stack.pop() {
+                stack.extend(&children[i]);
+                let l = self.widgets[i].limits;
+                self.widgets[i].limits = [l[0] + offset[0], l[1] + offset[0], l[2] + offset[1], l[3] + offset[1]];
+                moved.push(i);
+            }
+            changed.extend(&moved[1..]);
+            for i in moved {
+                if let Some(view) = self.virtual_views.get_mut(&i) {
+                    view.layout.translate(offset);
+                }
+                if let Some(table) = self.tables.get_mut(&i) {
+                    table.layout.translate(offset);
+                }
+                if let Some(dock) = self.docks.get_mut(&i) {
+                    dock.layout.translate(offset);
+                    docks.push(i);
+                }
+            }
+        }
+        self.write_widgets(changed);
+        self.ids_dirty = true;
+
+        // The placed scroll views and the ones holding placed widgets
+        let mut views: Vec<usize> = placed.iter()
+            .flat_map(|&(index, _)| iter::once(index).chain(self.widgets[index].parent()))
+            .filter(|i| self.scrolls.contains_key(i) && !self.virtual_views.contains_key(i))
+            .collect();
+        views.sort_unstable();
+        views.dedup();
+        for index in views {
+            let max = scroll_extent(&self.widgets, index);
+            self.widgets[index].scroll_max = max;
+            self.scrolls.get_mut(&index).unwrap().set_max(max.map(f64::from));
+            self.write_scroll(index);
+        }
+        for index in docks {
+            self.layout_dock(index);
+        }
+    }
+
+    /// The dock and panel of the tab `index`, if it is the tab of a dock.
+    fn dock_tab(&self, index: usize) -> Option<(usize, usize)> {
+        self.docks.iter().find_map(|(&dock, d)| Some((dock, d.tab_panel(dock, index)?)))
+    }
+
+    /// The dock and divider of the widget `index`, if it is a divider of a dock.
+    fn divider_at(&self, index: usize) -> Option<(usize, usize)> {
+        self.docks.iter().find_map(|(&dock, d)| Some((dock, d.divider_of(dock, index)?)))
+    }
+
+    /// Start dragging a divider or a tab of a dock if the widget `index` is one.
+    fn begin_dock_drag(&mut self, index: usize) {
+        if let Some((dock, divider)) = self.divider_at(index) {
+            let grab = self.docks[&dock].grab(divider, self.cursor_ndc());
+            self.dock_drag = Some(DockDrag::Divider { dock, divider, grab });
+        } else if let Some((dock, panel)) = self.dock_tab(index) {
+            let start = [self.cursor_pos.x, self.cursor_pos.y];
+            self.dock_drag = Some(DockDrag::Tab { dock, panel, start, moving: false, target: None });
+        }
+    }
+
+    /// Resize the split of the dragged divider, or show where the dragged tab would be dropped once it moved past `TOUCH_SLOP`.
+    fn drag_dock(&mut self) {
+        let cursor = self.cursor_ndc();
+        let pixel = self.pixel_size();
+        let position = [self.cursor_pos.x, self.cursor_pos.y];
+        match &mut self.dock_drag {
+            Some(DockDrag::Divider { dock, divider, grab }) => {
+                let index = *dock;
+                if self.docks.get_mut(&index).unwrap().drag_divider(*divider, *grab, cursor, pixel) {
+                    self.layout_dock(index);
+                    self.events.push(WidgetEvent::DockChanged { index });
+                }
+            }
+            Some(DockDrag::Tab { dock, panel, start, moving, target }) => {
+                if !*moving && (position[0] - start[0]).hypot(position[1] - start[1]) < TOUCH_SLOP {
+                    return;
+                }
+                *moving = true;
+                let index = *dock;
+                let drop = self.docks[&index].drop_target(*panel, cursor);
+                *target = drop.map(|(target, _)| target);
+                let indicator = self.docks[&index].layout.indicator(index);
+                if let Some((_, limits)) = drop {
+                    self.widgets[indicator].limits = limits;
+                    self.write_widget(indicator);
+                    self.ids_dirty = true;
+                }
+                self.set_hidden(indicator, drop.is_none());
+            }
+            None => {}
+        }
+    }
+
+    /// Drop the dragged tab where shown, if anywhere. Returns whether a divider or a tab was dragged.
+    fn end_dock_drag(&mut self) -> bool {
+        match self.dock_drag.take() {
+            Some(DockDrag::Divider { .. }) => true,
+            Some(DockDrag::Tab { dock, panel, moving, target, .. }) => {
+                let indicator = self.docks[&dock].layout.indicator(dock);
+                self.set_hidden(indicator, true);
+                if let Some(target) = target {
+                    self.docks.get_mut(&dock).unwrap().root.move_panel(panel, target);
+                    self.layout_dock(dock);
+                    self.events.push(WidgetEvent::DockChanged { index: dock });
+                }
+                moving
+            }
+            None => false,
+        }
+    }
+
+    /// Upload the offset of the scroll view `index`.
+    fn write_scroll(&mut self, index: usize) {
+        self.place_items(index, false);
+        let offset = self.scrolls[&index].offset();
+        let drawn = self.drawn_offset(index);
+        let widget = &mut self.widgets[index];
+        widget.ty[1] = drawn[0].to_bits();
+        widget.ty[2] = drawn[1].to_bits();
+        widget.scroll_base = (offset[1] - f64::from(drawn[1])) as f32;
+        self.write_widget(index);
+        self.ids_dirty = true;
+        self.place_popovers();
+    }
+
+    /// Upload the offset of the scroll view `index`, and report it if `notify` is set.
+    fn sync_scroll(&mut self, index: usize, notify: bool) {
+        self.write_scroll(index);
+        let offset = self.scrolls[&index].offset();
+        // The header of a table scrolls horizontally with its rows
+        let linked = self.tables.iter().find_map(|(&header, t)| match index {
+            i if i == header => Some(t.body),
+            i if i == t.body => Some(header),
+            _ => None,
+        });
+        if let Some(linked) = linked {
+            let model = self.scrolls.get_mut(&linked).unwrap();
+            if model.set_offset([offset[0], model.offset()[1]]) {
+                self.write_scroll(linked);
+            }
+        }
+        if notify {
+            self.events.push(WidgetEvent::Scrolled { index, offset });
+        }
+    }
+
+    /// Clear the ID buffer and draw it again with the next frame, after widgets moved.
+    /// The buffer is cleared on the GPU by `render`, so that this is cheap enough for every frame of a scroll.
+    fn invalidate_ids(&mut self) {
+        self.ids_dirty = false;
+        let gen_info = General {
+            resolution: [self.size.width, self.size.height],
+            resized: [1, 0],
+            time: self.start.elapsed().as_secs_f32(),
+            _padding: [0.0; 3],
+        };
+        self.queue.write_buffer(&self.gen_buffer, 0, bytemuck::cast_slice(&[gen_info]));
+        self.resized = true;
+        self.window.request_redraw();
+    }
+
+    /// The events that happened since the last call.
+    pub(super) fn take_events(&mut self) -> Vec<WidgetEvent> {
+        mem::take(&mut self.events)
+    }
+
+    /// Give the keyboard focus to the widget `index`, enabling the IME if it is a text widget.
+    /// Widgets under the shown dialog can't get it.
+    fn focus(&mut self, index: Option<usize>) {
+        let index = index.filter(|&i| !self.blocked(i));
+        if index == self.focused {
+            return;
+        }
+        let previous = mem::replace(&mut self.focused, index);
+        if let Some(previous) = previous {
+            self.set_state(previous, STATE_FOCUSED, false);
+        }
+        if let Some(index) = index {
+            self.set_state(index, STATE_FOCUSED, true);
+        }
+        if let Some(previous) = previous.filter(|i| self.texts.contains_key(i)) {
+            self.texts.get_mut(&previous).unwrap().set_preedit(String::new(), None);
+            self.sync_text(previous);
+            // Color fields left with text that isn't a color show the color again
+            if let Some(picker) = self.color_picker_of(previous) {
+                self.sync_color(picker, None);
+            }
+        }
+
+        let is_text = index.is_some_and(|i| self.texts.contains_key(&i));
+        self.window.set_ime_allowed(is_text);
+        if is_text {
+            self.sync_text(index.unwrap());
+        }
+    }
+
+    /// Edit the focused text widget according to a key press. Returns whether the event was consumed.
+    fn text_key(&mut self, event: &KeyEvent) -> bool {
+        let Some(index) = self.focused else { return false };
+        let Some(edit) = self.texts.get_mut(&index) else { return false };
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+
+        let select = self.modifiers.shift_key();
+        let shortcut = self.modifiers.control_key() || self.modifiers.super_key();
+        match &event.logical_key {
+            Key::Named(NamedKey::Backspace) => edit.backspace(),
+            Key::Named(NamedKey::Delete) => edit.delete(),
+            Key::Named(NamedKey::ArrowLeft) => edit.move_left(select),
+            Key::Named(NamedKey::ArrowRight) => edit.move_right(select),
+            Key::Named(NamedKey::Home) => edit.move_home(select),
+            Key::Named(NamedKey::End) => edit.move_end(select),
+            Key::Named(NamedKey::Copy) => edit.copy(self.clipboard.as_mut()),
+            Key::Named(NamedKey::Cut) => edit.cut(self.clipboard.as_mut()),
+            Key::Named(NamedKey::Paste) => edit.paste(self.clipboard.as_mut()),
+            Key::Character(c) if shortcut => match c.to_lowercase().as_str() {
+                "a" => edit.select_all(),
+                "c" => edit.copy(self.clipboard.as_mut()),
+                "x" => edit.cut(self.clipboard.as_mut()),
+                "v" => edit.paste(self.clipboard.as_mut()),
+                _ => return false,
+            },
+            _ => match &event.text {
+                Some(text) if !text.chars().any(char::is_control) => edit.insert(text),
+                _ => return false,
+            },
+        }
+        self.sync_text(index);
+        self.edit_color_field(index);
+        true
+    }
+
+    /// Forward IME composition to the focused text widget. Returns whether the event was consumed.
+    fn text_ime(&mut self, ime: &Ime) -> bool {
+        let Some(index) = self.focused else { return false };
+        let Some(edit) = self.texts.get_mut(&index) else { return false };
+        match ime {
+            Ime::Enabled => {}
+            Ime::Preedit(text, cursor) => edit.set_preedit(text.clone(), *cursor),
+            Ime::Commit(text) => edit.commit(text),
+            Ime::Disabled => edit.set_preedit(String::new(), None),
+        }
+        self.sync_text(index);
+        true
+    }
+
+    /// Upload the caret and preedit of the text widget `index`, and tell the IME where it is.
+    fn sync_text(&mut self, index: usize) {
+        let Some(edit) = self.texts.get(&index) else { return };
+        let focused = self.focused == Some(index);
+        let caret = edit.caret_column() as u32;
+        let preedit = edit.preedit_columns().unwrap_or_default();
+
+        let [left, top, _, bottom] = pixel_limits(self.screen_limits(index), self.size);
+        let cell_height = bottom - top - 2.0 * TEXT_PADDING;
+        let images = self.atlas.regions().len();
+        self.text_glyphs.get_mut(&index).unwrap().canvas = edit.glyphs(cell_height, &mut self.fonts, &mut self.atlas);
+        self.write_images(images);
+
+        let widget = &mut self.widgets[index];
+        widget.ty[1] = if focused { caret | TEXT_FOCUSED } else { caret };
+        widget.ty[2] = (preedit.start as u32 & 0xffff) | (preedit.end as u32) << 16;
+        self.write_widget(index);
+        self.primitives_dirty = true;
+
+        if focused {
+            let cell_width = cell_height * TEXT_CELL_ASPECT;
+            self.window.set_ime_cursor_area(
+                PhysicalPosition::new(left + TEXT_PADDING + caret as f32 * cell_width, top + TEXT_PADDING),
+                PhysicalSize::new(cell_width, cell_height),
+            );
+        }
+    }
+
+    /// Upload the widget `index` to the widgets buffer, and redraw.
+    fn write_widget(&mut self, index: usize) {
+        if self.widgets[index].is_animated() {
+            self.animated.insert(index);
+        } else {
+            self.animated.remove(&index);
+        }
+        self.queue.write_buffer(
+            &self.widgets_buffer,
+            (index * mem::size_of::<Widget>()) as wgpu::BufferAddress,
+            bytemuck::cast_slice(&[self.widgets[index]]),
+        );
+        self.window.request_redraw();
+    }
+
+    /// Upload the widgets `indices`, a write per run of consecutive ones.
+    fn write_widgets(&mut self, mut indices: Vec<usize>) {
+        indices.sort_unstable();
+        indices.dedup();
+        for run in indices.chunk_by(|a, b| a + 1 == *b) {
+            self.queue.write_buffer(
+                &self.widgets_buffer,
+                (run[0] * mem::size_of::<Widget>()) as wgpu::BufferAddress,
+                bytemuck::cast_slice(&self.widgets[run[0]..=run[run.len() - 1]]),
+            );
+        }
+        self.window.request_redraw();
+    }
+
+    /// Hover and drag with the pointer if it moved since it was last done.
+    /// Picking waits for the GPU, so moves are handled together, once per frame or before the next event.
+    fn move_pointer(&mut self) {
+        if !mem::take(&mut self.pointer_moved) {
+            return;
+        }
+        let hit = self.pick_hit();
+        self.hover(hit.map(|(index, _)| index));
+        self.hover_region(hit.and_then(|(_, region)| region));
+        self.hover_menu();
+        self.hover_tooltip();
+        self.drag_scroll();
+        self.drag_column();
+        self.drag_dock();
+        self.drag_tree();
+        self.drag_chart();
+        self.hover_chart();
+        self.move_viewport_pointer();
+        self.update_resize_cursor();
+        // The pressed slider captures the pointer until released
+        if let Some(index) = self.pressed {
+            self.drag_slider(index, false);
+            self.drag_color(index);
+        }
+    }
+
+    /// Read the ID buffer under the cursor, returning the index of the widget there, if any.
+    fn pick(&mut self) -> Option<usize> {
+        self.pick_hit().map(|(index, _)| index)
+    }
+
+    /// Read the ID buffer under the cursor, returning the index of the widget there, if any,
+    /// with the hit region of the canvas primitive there.
+    fn pick_hit(&mut self) -> Option<(usize, Option<u32>)> {
+        if self.cursor_pos.x < 0.0 || self.cursor_pos.y < 0.0
+            || self.cursor_pos.x >= self.size.width as f64 || self.cursor_pos.y >= self.size.height as f64 {
+            return None;
+        }
+        let id_buffer_index = 4 * (self.cursor_pos.y as u64 * self.size.width as u64 + self.cursor_pos.x as u64);
+
+        let mapped_id_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readable ID Buffer"),
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+        {
+            encoder.copy_buffer_to_buffer(
+                &self.id_buffer, 
+                id_buffer_index, 
+                &mapped_id_buffer, 
+                0, 4
+            );
+        }
+        self.queue.submit(iter::once(encoder.finish()));
+
+
+        mapped_id_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, |result| {
+                OnceCell::new().set(result).unwrap();
+            });
+
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let slice: &[u8] = &mut mapped_id_buffer.slice(..).get_mapped_range();
+        let id = bytemuck::cast_slice::<u8, u32>(slice)[0];
+        let region = (id >> HIT_REGION_SHIFT).checked_sub(1);
+        (id & ((1 << HIT_REGION_SHIFT) - 1)).checked_sub(1).map(|index| (index as usize, region))
     }
 
     pub(super) fn update(&mut self) {
         // self.queue.write_buffer(&self.gen_buffer, 0, bytemuck::cast_slice(&[self.gen_info]));
 
+        // Animated widgets only need the time to be updated, but keep redrawing
+        self.move_pointer();
+        let now = web_time::Instant::now();
+        let time = now.saturating_duration_since(self.start).as_secs_f32();
+        self.queue.write_buffer(&self.gen_buffer, mem::offset_of!(General, time) as wgpu::BufferAddress, bytemuck::bytes_of(&time));
+        if !self.animated.is_empty() || self.viewports.values().any(|v| v.scene.is_animated()) {
+            self.window.request_redraw();
+        }
+
+        self.update_tooltip(now);
+
+        // Move the flung scroll views, drawing the ID buffer again in the same frame so that picking follows them
+        let moving: Vec<usize> = self.scrolls.iter().filter(|(_, s)| s.is_moving()).map(|(&i, _)| i).collect();
+        for &index in &moving {
+            if self.scrolls.get_mut(&index).unwrap().step(now) {
+                self.sync_scroll(index, true);
+            }
+        }
+        if !moving.is_empty() {
+            self.window.request_redraw();
+        }
+        if self.primitives_dirty {
+            self.write_primitives();
+        }
+        if self.ids_dirty {
+            self.invalidate_ids();
+        }
+
+        // Advance the transitions of toggle widgets, redrawing until they are over
+        let transitions: Vec<(usize, f32, bool)> = self.transitions.iter()
+            .map(|(&index, transition)| {
+                let (progress, done) = transition.progress(now);
+                (index, progress, done)
+            })
+            .collect();
+        for (index, progress, done) in transitions {
+            self.widgets[index].ty[2] = progress.to_bits();
+            self.write_widget(index);
+            if done {
+                self.transitions.remove(&index);
+            }
+        }
     }
 
     pub(super) fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -511,35 +4119,43 @@ impl<'window> State<'window> {
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+        self.draw_viewports(&mut encoder);
+        // The widgets write their IDs in this frame, over none
+        if self.resized {
+            encoder.clear_buffer(&self.id_buffer, 0, None);
+        }
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.2,
-                            g: 0.247,
-                            b: 0.314,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.gen_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.widgets_bind_group, &[]);
-            render_pass.set_bind_group(2, &self.id_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        let ranges = self.draw_ranges();
+        if self.backdrops.is_empty() {
+            self.widgets_pass(&mut encoder, &view, true, &ranges);
+        }
+        else {
+            // Draw to the scene texture, blurring it before each widget with a backdrop blur
+            let mut pending = vec![];
+            let mut first = true;
+            for range in ranges {
+                let mut start = range.start;
+                for &(index, radius) in self.backdrops.iter().filter(|b| range.contains(&b.0)) {
+                    pending.push(start..index);
+                    self.widgets_pass(&mut encoder, self.backdrop.scene_view(), first, &pending);
+                    pending.clear();
+                    first = false;
+                    self.backdrop.blur(&self.queue, &mut encoder, &self.config, radius);
+                    // The blur radius is in a buffer written by the queue, submit before it changes
+                    self.queue.submit(iter::once(encoder.finish()));
+                    encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Render Encoder"),
+                    });
+                    start = index;
+                }
+                pending.push(start..range.end);
+            }
+            self.widgets_pass(&mut encoder, self.backdrop.scene_view(), first, &pending);
+            self.backdrop.blit(&mut encoder, &view);
+        }
+        if self.overlay_start < self.num_indices {
+            let overlays = self.overlay_start..self.num_indices;
+            self.widgets_pass(&mut encoder, &view, false, slice::from_ref(&overlays));
         }
 
         self.queue.submit(iter::once(encoder.finish()));
@@ -548,7 +4164,9 @@ impl<'window> State<'window> {
         if self.resized {
             let gen_info = General {
                 resolution: [self.size.width, self.size.height],
-                resized: [0, 0]
+                resized: [0, 0],
+                time: self.start.elapsed().as_secs_f32(),
+                _padding: [0.0; 3],
             };
             self.queue.write_buffer(&self.gen_buffer, 0, bytemuck::cast_slice(&[gen_info]));
             self.resized = false;
@@ -557,6 +4175,96 @@ impl<'window> State<'window> {
         Ok(())
     }
 
+    /// Draw the widgets in `indices` of the index buffer to `target`, clearing it first if `clear`.
+    fn widgets_pass(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView, clear: bool, ranges: &[Range<u32>]) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: self.msaa_view.as_ref().unwrap_or(target),
+                resolve_target: self.msaa_view.as_ref().map(|_| target),
+                ops: wgpu::Operations {
+                    load: if clear {
+                        wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.2,
+                            g: 0.247,
+                            b: 0.314,
+                            a: 1.0,
+                        })
+                    } else {
+                        wgpu::LoadOp::Load
+                    },
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.gen_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.widgets_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.id_bind_group, &[]);
+        render_pass.set_bind_group(3, &self.textures_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        // The quads of series are instanced over their points, those of canvases over their primitives,
+        // those of viewports drawn with their textures, and the others drawn once
+        for indices in ranges {
+            self.draw_range(&mut render_pass, indices.clone());
+        }
+    }
+
+    /// The ranges of the index buffer below the overlays in drawing order, the shown dialogs last in the order they were shown.
+    fn draw_ranges(&self) -> Vec<Range<u32>> {
+        let (Some((_, first)), Some((_, last))) = (self.dialog_quads.first(), self.dialog_quads.last()) else {
+            return iter::once(0..self.overlay_start).collect();
+        };
+        let shown = |dialog: usize| self.modals.iter().any(|m| m.dialog == dialog);
+        let mut ranges: Vec<Range<u32>> = iter::once(0..first.start).collect();
+        ranges.extend(self.dialog_quads.iter().filter(|&&(d, _)| !shown(d)).map(|(_, r)| r.clone()));
+        ranges.extend(self.modals.iter().filter_map(|m| self.dialog_quads.iter().find(|&&(d, _)| d == m.dialog)).map(|(_, r)| r.clone()));
+        ranges.push(last.end..self.overlay_start);
+        ranges
+    }
+
+    /// Draw the quads in the range `indices` of the index buffer in `render_pass`.
+    fn draw_range<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, indices: Range<u32>) {
+        let mut start = indices.start;
+        for &(index, quad) in self.split_quads.iter().filter(|q| indices.contains(&q.0)) {
+            render_pass.draw_indexed(start..index, 0, 0..1);
+            match quad {
+                SplitQuad::Series { chart, slot } => {
+                    let chart = &self.charts[&chart];
+                    let instances = match slot {
+                        MAX_CHART_SERIES => 0..1,
+                        slot if slot < chart.series.len() => chart.instances(slot),
+                        _ => 0..0,
+                    };
+                    if !instances.is_empty() {
+                        render_pass.draw_indexed(index..index + 6, 0, instances);
+                    }
+                }
+                // Viewports aren't drawn until they have a texture
+                SplitQuad::Viewport(viewport) => {
+                    if let Some(bind_group) = self.viewports.get(&viewport).and_then(|v| v.bind_group.as_ref()) {
+                        render_pass.set_bind_group(3, bind_group, &[]);
+                        render_pass.draw_indexed(index..index + 6, 0, 0..1);
+                        render_pass.set_bind_group(3, &self.textures_bind_group, &[]);
+                    }
+                }
+                SplitQuad::Canvas(canvas) => {
+                    let instances = self.canvases.get(&canvas).unwrap_or_else(|| &self.text_glyphs[&canvas]).instances();
+                    if !instances.is_empty() {
+                        render_pass.draw_indexed(index..index + 6, 0, instances);
+                    }
+                }
+            }
+            start = index + 6;
+        }
+        render_pass.draw_indexed(start..indices.end, 0, 0..1);
+    }
+
     #[allow(unused)]
     pub(super) fn id_buffer_len(&self) -> u64 {
         self.id_buffer_len
@@ -597,4 +4305,140 @@ impl<'window> State<'window> {
 
         (mapped_id_buffer, self.size.width, self.size.height)
     }
+}
+
+/// The (left, top, right, bottom) limits in pixels of the NDC `limits`, for a surface of size `resolution`.
+fn pixel_limits(limits: [f32; 4], resolution: PhysicalSize<u32>) -> [f32; 4] {
+    let (width, height) = (resolution.width as f32, resolution.height as f32);
+    [
+        (limits[0] + 1.0) * 0.5 * width,
+        (1.0 - limits[3]) * 0.5 * height,
+        (limits[1] + 1.0) * 0.5 * width,
+        (1.0 - limits[2]) * 0.5 * height,
+    ]
+}
+
+/// The scroll views and popup containing the widget `index`, innermost first.
+fn ancestors(widgets: &[Widget], index: usize) -> impl Iterator<Item = usize> + '_ {
+    iter::successors(widgets[index].parent(), |&p| widgets[p].parent()).take(MAX_NESTING)
+}
+
+/// How far the widgets in the scroll view `index` extend past its right and bottom, in NDC.
+fn scroll_extent(widgets: &[Widget], index: usize) -> [f32; 2] {
+    let view = widgets[index].limits;
+    let (right, bottom) = widgets.iter()
+        .filter(|w| w.parent() == Some(index))
+        .fold((view[1], view[2]), |(right, bottom), w| (right.max(w.limits[1]), bottom.min(w.limits[2])));
+    [right - view[1], view[2] - bottom]
+}
+
+/// Add the quad covering the limits of `w` to the vertex and index lists, with vertices of id `id`.
+/// Except for the scrim of dialogs, the vertex shader places its corners from the limits of the widget `id`, following them as they change.
+fn push_quad(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, w: &Widget, id: u32) {
+    let first = vertices.len() as u32;
+    vertices.push(Vertex { position: [w.limits[0], w.limits[2]], id });
+    vertices.push(Vertex { position: [w.limits[0], w.limits[3]], id });
+    vertices.push(Vertex { position: [w.limits[1], w.limits[3]], id });
+    vertices.push(Vertex { position: [w.limits[1], w.limits[2]], id });
+    indices.extend([first, first+1, first+2, first, first+2, first+3]);
+}
+
+/// Add the quad of the series `slot` of a chart, or of the primitives of a canvas with `slot` 0, to the vertex and index lists,
+/// with vertices of id `id`. Its corners are placed by the vertex shader, from their index.
+fn push_series_quad(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, id: u32, slot: usize) {
+    let first = vertices.len() as u32;
+    vertices.extend([Vertex { position: [slot as f32, 0.0], id }; 4]);
+    indices.extend([first, first+1, first+2, first, first+2, first+3]);
+}
+
+/// Copy `image` to `texture` at `position`, in pixels.
+fn write_image(queue: &wgpu::Queue, texture: &wgpu::Texture, position: [u32; 2], image: &image::RgbaImage) {
+    let (width, height) = image.dimensions();
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x: position[0], y: position[1], z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        image.as_raw(),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * width),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+}
+
+fn create_textures_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, backdrop: &Backdrop, atlas_view: &wgpu::TextureView, nearest_sampler: &wgpu::Sampler) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(backdrop.blurred_view()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(backdrop.sampler()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(atlas_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(nearest_sampler),
+            },
+        ],
+        label: Some("textures_bind_group"),
+    })
+}
+
+/// The widgets, styles, images, paths and data buffers, in binding order.
+fn create_widgets_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, buffers: [&wgpu::Buffer; 5]) -> wgpu::BindGroup {
+    let entries: Vec<wgpu::BindGroupEntry> = buffers.iter().enumerate().map(|(binding, buffer)| wgpu::BindGroupEntry {
+        binding: binding as u32,
+        resource: wgpu::BindingResource::Buffer(buffer.as_entire_buffer_binding()),
+    }).collect();
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &entries,
+        label: Some("widgets_bind_group"),
+    })
+}
+
+/// A data buffer placed by `layout`, with its header written.
+fn create_data_buffer(device: &wgpu::Device, queue: &wgpu::Queue, layout: DataLayout) -> wgpu::Buffer {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Data Storage Buffer"),
+        size: layout.size(),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    queue.write_buffer(&buffer, 0, bytemuck::cast_slice(&layout.header()));
+    buffer
+}
+
+/// A multisampled color target matching the surface, if `sample_count` is more than 1.
+fn create_msaa_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, sample_count: u32) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("MSAA Color Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
 }
\ No newline at end of file
@@ -0,0 +1,275 @@
+use bytemuck::{Pod, Zeroable};
+
+/// Maximum number of color stops in a gradient.
+/// Must match the size of the stop arrays in `w__fragment.wgsl`.
+pub(super) const MAX_GRADIENT_STOPS: usize = 4;
+
+/// Set in `ty[3]` of a widget while the cursor is over it.
+pub(super) const STATE_HOVERED: u32 = 1 << 24;
+/// Set in `ty[3]` of a widget while it is pressed.
+pub(super) const STATE_PRESSED: u32 = 1 << 25;
+/// Set in `ty[3]` of a widget that doesn't react to input.
+pub(super) const STATE_DISABLED: u32 = 1 << 26;
+/// Set in `ty[3]` of the widget receiving keyboard input.
+pub(super) const STATE_FOCUSED: u32 = 1 << 27;
+/// Set in `ty[3]` of a widget that isn't drawn nor picked, with the widgets in it.
+/// Must match `STATE_HIDDEN` in `w__vertex.wgsl`.
+pub(super) const STATE_HIDDEN: u32 = 1 << 28;
+/// The bits of `ty[3]` holding the index of the style, the others are state flags.
+pub(super) const STYLE_INDEX_MASK: u32 = 0x00ff_ffff;
+
+/// The shape of a gradient fill. Positions are in the widget's local space,
+/// from (0, 0) at the top left corner to (1, 1) at the bottom right one.
+#[derive(Copy, Clone, Debug)]
+pub(super) enum Gradient {
+    /// Colors change along the direction `angle`, in radians, clockwise from the +X axis.
+    Linear { angle: f32 },
+    /// Colors change with the distance to `center`, reaching the last stop at `radius`.
+    Radial { center: [f32; 2], radius: f32 },
+    /// Colors change with the angle around `center`, starting at `angle` in radians.
+    Conic { center: [f32; 2], angle: f32 },
+}
+
+/// How a widget is painted in one of its states.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub(super) struct Style {
+    /// Fill color, RGBA. Ignored if the style has a gradient.
+    pub(super) fill: [f32; 4],
+    /// Border color, RGBA.
+    pub(super) border_color: [f32; 4],
+    /// Corner radii in pixels: top left, top right, bottom right, bottom left.
+    pub(super) radii: [f32; 4],
+    /// Border width in pixels.
+    pub(super) border_width: f32,
+    /// Multiplies the alpha of the whole widget.
+    pub(super) opacity: f32,
+    /// 0 for a solid fill, then 1, 2, 3 for linear, radial and conic gradients.
+    gradient: u32,
+    stop_count: u32,
+    /// Linear: (angle, _, _, _), radial: (center x, center y, radius, _), conic: (center x, center y, angle, _).
+    gradient_params: [f32; 4],
+    stop_colors: [[f32; 4]; MAX_GRADIENT_STOPS],
+    stop_offsets: [f32; MAX_GRADIENT_STOPS],
+    /// Shadow color, RGBA. The shadow isn't drawn if transparent.
+    pub(super) shadow_color: [f32; 4],
+    /// Shadow offset X and Y, blur radius and spread, in pixels.
+    pub(super) shadow: [f32; 4],
+    /// Radius in pixels of the blur applied to what is behind the widget, 0 for none.
+    pub(super) backdrop_blur: f32,
+    /// Index + 1 of the atlas image replacing the fill, 0 for none.
+    skin: u32,
+    _padding: [f32; 2],
+    /// Nine-slice insets of the skin image in pixels: left, top, right, bottom.
+    skin_insets: [f32; 4],
+}
+
+impl Style {
+    /// A style filled with `fill`, without border and with square corners.
+    pub(super) fn new(fill: [f32; 4]) -> Self {
+        Self {
+            fill,
+            border_color: [0.0; 4],
+            radii: [0.0; 4],
+            border_width: 0.0,
+            opacity: 1.0,
+            gradient: 0,
+            stop_count: 0,
+            gradient_params: [0.0; 4],
+            stop_colors: [[0.0; 4]; MAX_GRADIENT_STOPS],
+            stop_offsets: [0.0; MAX_GRADIENT_STOPS],
+            shadow_color: [0.0; 4],
+            shadow: [0.0; 4],
+            backdrop_blur: 0.0,
+            skin: 0,
+            _padding: [0.0; 2],
+            skin_insets: [0.0; 4],
+        }
+    }
+
+    /// Fill with a solid color, removing the gradient if there was one.
+    pub(super) fn with_fill(mut self, fill: [f32; 4]) -> Self {
+        self.fill = fill;
+        self.gradient = 0;
+        self
+    }
+
+    pub(super) fn with_border(mut self, width: f32, color: [f32; 4]) -> Self {
+        self.border_width = width;
+        self.border_color = color;
+        self
+    }
+
+    /// Corner radii in pixels: top left, top right, bottom right, bottom left.
+    pub(super) fn with_radii(mut self, radii: [f32; 4]) -> Self {
+        self.radii = radii;
+        self
+    }
+
+    pub(super) fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Cast a shadow behind the widget, like CSS `box-shadow`.
+    /// - `offset` moves the shadow, in pixels, +Y being down.
+    /// - `blur` is the blur radius in pixels, twice the standard deviation of the gaussian.
+    /// - `spread` grows the shadow in every direction, in pixels.
+    pub(super) fn with_shadow(mut self, offset: [f32; 2], blur: f32, spread: f32, color: [f32; 4]) -> Self {
+        self.shadow = [offset[0], offset[1], blur, spread];
+        self.shadow_color = color;
+        self
+    }
+
+    /// Blur what is behind the widget by `radius` pixels, seen through its fill with the fill's alpha.
+    /// Only the `normal` style of a `StyleSet` decides whether the widget has a backdrop blur.
+    pub(super) fn with_backdrop_blur(mut self, radius: f32) -> Self {
+        self.backdrop_blur = radius;
+        self
+    }
+
+    /// Fill with the atlas image `image` instead of the fill color or gradient, scaled with nine-slice:
+    /// the `insets` of the image (left, top, right, bottom, in pixels) keep their size, the rest is stretched.
+    /// The shape of the widget still clips the image.
+    pub(super) fn with_skin(mut self, image: u32, insets: [f32; 4]) -> Self {
+        self.skin = image + 1;
+        self.skin_insets = insets;
+        self
+    }
+
+    /// Fill with a gradient instead of a solid color.
+    /// - `stops` are (offset, RGBA color) pairs with increasing offsets in [0, 1].
+    ///
+    /// Panics if there are no stops or more than `MAX_GRADIENT_STOPS`.
+    pub(super) fn with_gradient(mut self, gradient: Gradient, stops: &[(f32, [f32; 4])]) -> Self {
+        assert!(!stops.is_empty() && stops.len() <= MAX_GRADIENT_STOPS, "A gradient needs 1 to {} stops", MAX_GRADIENT_STOPS);
+        (self.gradient, self.gradient_params) = match gradient {
+            Gradient::Linear { angle } => (1, [angle, 0.0, 0.0, 0.0]),
+            Gradient::Radial { center, radius } => (2, [center[0], center[1], radius, 0.0]),
+            Gradient::Conic { center, angle } => (3, [center[0], center[1], angle, 0.0]),
+        };
+        self.stop_count = stops.len() as u32;
+        for (i, &(offset, color)) in stops.iter().enumerate() {
+            self.stop_offsets[i] = offset;
+            self.stop_colors[i] = color;
+        }
+        self
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::new([0.8, 0.8, 0.8, 1.0]).with_border(1.0, [0.3, 0.3, 0.3, 1.0])
+    }
+}
+
+/// The styles of a widget in each of its states, referenced by index from `ty[3]`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub(super) struct StyleSet {
+    pub(super) normal: Style,
+    pub(super) hovered: Style,
+    pub(super) pressed: Style,
+    pub(super) disabled: Style,
+}
+
+impl StyleSet {
+    /// A style set using `normal` in every state.
+    pub(super) fn new(normal: Style) -> Self {
+        Self {
+            normal,
+            hovered: normal,
+            pressed: normal,
+            disabled: normal,
+        }
+    }
+
+    pub(super) fn with_hovered(mut self, style: Style) -> Self {
+        self.hovered = style;
+        self
+    }
+
+    pub(super) fn with_pressed(mut self, style: Style) -> Self {
+        self.pressed = style;
+        self
+    }
+
+    pub(super) fn with_disabled(mut self, style: Style) -> Self {
+        self.disabled = style;
+        self
+    }
+
+    /// Paint every state with the image `skin` gives for it.
+    pub(super) fn with_skin(mut self, skin: &Skin) -> Self {
+        self.normal = self.normal.with_skin(skin.normal, skin.insets);
+        self.hovered = self.hovered.with_skin(skin.hovered, skin.insets);
+        self.pressed = self.pressed.with_skin(skin.pressed, skin.insets);
+        self.disabled = self.disabled.with_skin(skin.disabled, skin.insets);
+        self
+    }
+
+    /// Whether the widget casts a shadow in any of its states.
+    pub(super) fn has_shadow(&self) -> bool {
+        [&self.normal, &self.hovered, &self.pressed, &self.disabled].iter().any(|s| s.shadow_color[3] > 0.0)
+    }
+
+    /// The largest backdrop blur of its states, the radius its backdrop is blurred by in any of them.
+    pub(super) fn backdrop_blur(&self) -> f32 {
+        [&self.normal, &self.hovered, &self.pressed, &self.disabled].iter().map(|s| s.backdrop_blur).fold(0.0, f32::max)
+    }
+}
+
+impl Default for StyleSet {
+    fn default() -> Self {
+        let normal = Style::default();
+        Self::new(normal)
+            .with_hovered(normal.with_fill([0.9, 0.9, 0.9, 1.0]))
+            .with_pressed(normal.with_fill([0.6, 0.6, 0.6, 1.0]))
+            .with_disabled(normal.with_opacity(0.5))
+    }
+}
+
+/// Artist supplied atlas images for each state of a widget, see `StyleSet::with_skin`.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct Skin {
+    pub(super) normal: u32,
+    pub(super) hovered: u32,
+    pub(super) pressed: u32,
+    pub(super) disabled: u32,
+    /// Nine-slice insets in pixels, shared by the images: left, top, right, bottom.
+    pub(super) insets: [f32; 4],
+}
+
+impl Skin {
+    /// A skin using the atlas image `normal` in every state, stretched.
+    pub(super) fn new(normal: u32) -> Self {
+        Self {
+            normal,
+            hovered: normal,
+            pressed: normal,
+            disabled: normal,
+            insets: [0.0; 4],
+        }
+    }
+
+    pub(super) fn with_hovered(mut self, image: u32) -> Self {
+        self.hovered = image;
+        self
+    }
+
+    pub(super) fn with_pressed(mut self, image: u32) -> Self {
+        self.pressed = image;
+        self
+    }
+
+    pub(super) fn with_disabled(mut self, image: u32) -> Self {
+        self.disabled = image;
+        self
+    }
+
+    /// Nine-slice insets in pixels: left, top, right, bottom.
+    pub(super) fn with_insets(mut self, insets: [f32; 4]) -> Self {
+        self.insets = insets;
+        self
+    }
+}
@@ -0,0 +1,507 @@
+use std::fmt;
+
+use crate::path::{FillRule, LineCap, LineJoin, Path, PathCommand, Stroke};
+
+#[derive(Debug)]
+pub(super) enum SvgError {
+    /// The document isn't well formed, or an attribute couldn't be parsed.
+    Syntax(String),
+    /// The document uses a feature outside of the supported subset.
+    Unsupported(String),
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(e) => write!(f, "invalid SVG: {e}"),
+            Self::Unsupported(e) => write!(f, "unsupported SVG feature: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+/// Presentation attributes, inherited from `<svg>` and `<g>` elements.
+#[derive(Clone)]
+struct Paint {
+    fill: Option<FillRule>,
+    stroke: Option<Stroke>,
+}
+
+/// Import an SVG icon, as one path per shape, all showing the viewbox of the document.
+///
+/// The supported subset is the one icon sets use: `<path>`, `<rect>`, `<circle>`, `<ellipse>`, `<line>`,
+/// `<polyline>` and `<polygon>` elements, grouped with `<g>`, with the `fill`, `fill-rule`, `stroke`, `stroke-width`,
+/// `stroke-linecap`, `stroke-linejoin` and `stroke-miterlimit` attributes.
+/// Colors only tell whether the shape is filled and stroked, the widgets' styles paint them.
+/// Transforms, CSS and `<use>` aren't supported.
+pub(super) fn parse(svg: &str) -> Result<Vec<Path>, SvgError> {
+    let mut paths = vec![];
+    let mut viewbox = None;
+    let mut stack = vec![Paint { fill: Some(FillRule::NonZero), stroke: None }];
+    // Depth of the `<defs>` or other skipped element being inside, if any
+    let mut skipping = 0;
+
+    let mut rest = svg;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        // Comments, declarations and processing instructions
+        let skipped = [("!--", "-->"), ("![CDATA[", "]]>"), ("!", ">"), ("?", "?>")].into_iter().find(|(start, _)| rest.starts_with(start));
+        if let Some((start, end)) = skipped {
+            let close = rest.find(end).ok_or_else(|| SvgError::Syntax(format!("unclosed <{start}")))?;
+            rest = &rest[close + end.len()..];
+            continue;
+        }
+        if !rest.starts_with(|c: char| c == '/' || c.is_alphabetic()) {
+            continue;
+        }
+        let close = rest.find('>').ok_or_else(|| SvgError::Syntax("unclosed tag".to_string()))?;
+        let tag = &rest[..close];
+        rest = &rest[close + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            if skipping > 0 {
+                skipping -= 1;
+            } else if name == "g" || name == "svg" {
+                // The first entry is the default paint, not an element
+                if stack.len() == 1 {
+                    return Err(SvgError::Syntax(format!("</{name}> without an opening tag")));
+                }
+                stack.pop();
+            }
+            continue;
+        }
+        let self_closing = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let name = &tag[..name_end];
+        let attributes = parse_attributes(&tag[name_end..])?;
+        if skipping > 0 {
+            if !self_closing {
+                skipping += 1;
+            }
+            continue;
+        }
+        if attributes.iter().any(|(k, _)| *k == "transform") {
+            return Err(SvgError::Unsupported("transform".to_string()));
+        }
+
+        let attr = |key: &str| attributes.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        let number = |key: &str| -> Result<f32, SvgError> {
+            attr(key).map_or(Ok(0.0), parse_length)
+        };
+        let paint = inherit(stack.last().unwrap(), &attr)?;
+
+        let commands = match name {
+            "svg" => {
+                if viewbox.is_none() {
+                    viewbox = match attr("viewBox") {
+                        Some(v) => {
+                            let v = parse_numbers(v)?;
+                            if v.len() != 4 {
+                                return Err(SvgError::Syntax(format!("viewBox has {} numbers", v.len())));
+                            }
+                            Some([v[0], v[1], v[2], v[3]])
+                        }
+                        None => Some([0.0, 0.0, number("width")?, number("height")?]),
+                    };
+                }
+                if !self_closing {
+                    stack.push(paint);
+                }
+                continue;
+            }
+            "g" => {
+                if !self_closing {
+                    stack.push(paint);
+                }
+                continue;
+            }
+            "path" => parse_path_data(attr("d").unwrap_or(""))?,
+            "rect" => {
+                let (x, y, w, h) = (number("x")?, number("y")?, number("width")?, number("height")?);
+                // A missing radius defaults to the other one
+                let rx = attr("rx").or(attr("ry")).map_or(Ok(0.0), parse_length)?.min(w * 0.5);
+                let ry = attr("ry").or(attr("rx")).map_or(Ok(0.0), parse_length)?.min(h * 0.5);
+                rect_commands(x, y, w, h, rx, ry)
+            }
+            "circle" => {
+                let r = number("r")?;
+                ellipse_commands(number("cx")?, number("cy")?, r, r)
+            }
+            "ellipse" => ellipse_commands(number("cx")?, number("cy")?, number("rx")?, number("ry")?),
+            "line" => vec![
+                PathCommand::MoveTo([number("x1")?, number("y1")?]),
+                PathCommand::LineTo([number("x2")?, number("y2")?]),
+            ],
+            "polyline" | "polygon" => {
+                let points = parse_numbers(attr("points").unwrap_or(""))?;
+                let mut commands: Vec<PathCommand> = points.chunks_exact(2).enumerate().map(|(i, p)| {
+                    if i == 0 { PathCommand::MoveTo([p[0], p[1]]) } else { PathCommand::LineTo([p[0], p[1]]) }
+                }).collect();
+                if name == "polygon" && !commands.is_empty() {
+                    commands.push(PathCommand::Close);
+                }
+                commands
+            }
+            "defs" | "title" | "desc" | "metadata" | "clipPath" | "mask" | "style" => {
+                if !self_closing {
+                    skipping = 1;
+                }
+                continue;
+            }
+            "use" | "text" | "image" => return Err(SvgError::Unsupported(format!("<{name}>"))),
+            // Unknown elements are ignored, but their content isn't
+            _ => continue,
+        };
+        if commands.is_empty() || (paint.fill.is_none() && paint.stroke.is_none()) {
+            continue;
+        }
+        paths.push(Path {
+            viewbox: [0.0; 4],
+            commands,
+            fill: paint.fill,
+            stroke: paint.stroke,
+        });
+    }
+
+    let viewbox = viewbox.ok_or_else(|| SvgError::Syntax("no <svg> element".to_string()))?;
+    for path in &mut paths {
+        path.viewbox = viewbox;
+    }
+    Ok(paths)
+}
+
+/// The paint of an element, from its attributes and its parent's paint.
+fn inherit<'a>(parent: &Paint, attr: &impl Fn(&str) -> Option<&'a str>) -> Result<Paint, SvgError> {
+    let mut paint = parent.clone();
+    let fill_rule = match attr("fill-rule") {
+        Some("evenodd") => Some(FillRule::EvenOdd),
+        Some("nonzero") => Some(FillRule::NonZero),
+        _ => None,
+    };
+    match attr("fill") {
+        Some("none") | Some("transparent") => paint.fill = None,
+        Some(_) => paint.fill = Some(fill_rule.or(paint.fill).unwrap_or(FillRule::NonZero)),
+        None => paint.fill = paint.fill.map(|rule| fill_rule.unwrap_or(rule)),
+    }
+
+    let mut stroke = paint.stroke.unwrap_or(Stroke::new(1.0));
+    if let Some(width) = attr("stroke-width") {
+        stroke.width = parse_length(width)?;
+    }
+    if let Some(limit) = attr("stroke-miterlimit") {
+        stroke.miter_limit = parse_length(limit)?;
+    }
+    stroke.cap = match attr("stroke-linecap") {
+        Some("round") => LineCap::Round,
+        Some("square") => LineCap::Square,
+        Some("butt") => LineCap::Butt,
+        _ => stroke.cap,
+    };
+    stroke.join = match attr("stroke-linejoin") {
+        Some("round") => LineJoin::Round,
+        Some("bevel") => LineJoin::Bevel,
+        Some("miter") => LineJoin::Miter,
+        _ => stroke.join,
+    };
+    paint.stroke = match attr("stroke") {
+        Some("none") | Some("transparent") => None,
+        Some(_) => Some(stroke),
+        None => paint.stroke.map(|_| stroke),
+    };
+    Ok(paint)
+}
+
+/// `name="value"` pairs of a tag, quoted with `"` or `'`.
+fn parse_attributes(mut s: &str) -> Result<Vec<(&str, &str)>, SvgError> {
+    let mut attributes = vec![];
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            return Ok(attributes);
+        }
+        let eq = s.find('=').ok_or_else(|| SvgError::Syntax(format!("attribute without value: {s}")))?;
+        let key = s[..eq].trim();
+        s = s[eq + 1..].trim_start();
+        let quote = s.chars().next().filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| SvgError::Syntax(format!("unquoted value of {key}")))?;
+        let end = s[1..].find(quote).ok_or_else(|| SvgError::Syntax(format!("unclosed value of {key}")))?;
+        attributes.push((key, &s[1..end + 1]));
+        s = &s[end + 2..];
+    }
+}
+
+/// A length in user units, accepting a `px` suffix.
+fn parse_length(s: &str) -> Result<f32, SvgError> {
+    let s = s.trim();
+    s.strip_suffix("px").unwrap_or(s).trim().parse().map_err(|_| SvgError::Syntax(format!("invalid number: {s}")))
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<f32>, SvgError> {
+    let mut scanner = Scanner { s };
+    let mut numbers = vec![];
+    while scanner.has_number() {
+        numbers.push(scanner.number()?);
+    }
+    Ok(numbers)
+}
+
+/// Reads the numbers and flags of path data, which can be separated by whitespace, commas, or nothing
+/// when unambiguous, like in `M1-2.5.5`.
+struct Scanner<'a> {
+    s: &'a str,
+}
+
+impl Scanner<'_> {
+    fn skip_separators(&mut self) {
+        self.s = self.s.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        self.s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
+    }
+
+    fn number(&mut self) -> Result<f32, SvgError> {
+        self.skip_separators();
+        let bytes = self.s.as_bytes();
+        let mut end = 0;
+        if end < bytes.len() && (bytes[end] == b'-' || bytes[end] == b'+') {
+            end += 1;
+        }
+        let mut dot = false;
+        while end < bytes.len() && (bytes[end].is_ascii_digit() || (bytes[end] == b'.' && !dot)) {
+            dot |= bytes[end] == b'.';
+            end += 1;
+        }
+        if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+            let mut exp = end + 1;
+            if exp < bytes.len() && (bytes[exp] == b'-' || bytes[exp] == b'+') {
+                exp += 1;
+            }
+            if exp < bytes.len() && bytes[exp].is_ascii_digit() {
+                end = exp;
+                while end < bytes.len() && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+            }
+        }
+        let number = self.s[..end].parse().map_err(|_| SvgError::Syntax(format!("expected a number at: {}", self.s)))?;
+        self.s = &self.s[end..];
+        Ok(number)
+    }
+
+    /// Arc flags are a single `0` or `1`, and may be followed by a number without separator.
+    fn flag(&mut self) -> Result<bool, SvgError> {
+        self.skip_separators();
+        let flag = match self.s.as_bytes().first() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(SvgError::Syntax(format!("expected a flag at: {}", self.s))),
+        };
+        self.s = &self.s[1..];
+        Ok(flag)
+    }
+
+    fn point(&mut self) -> Result<[f32; 2], SvgError> {
+        Ok([self.number()?, self.number()?])
+    }
+}
+
+/// Parse the `d` attribute of a `<path>`, with absolute coordinates in the commands.
+fn parse_path_data(d: &str) -> Result<Vec<PathCommand>, SvgError> {
+    let mut commands = vec![];
+    let mut scanner = Scanner { s: d };
+    let mut current = [0.0, 0.0];
+    let mut start = [0.0, 0.0];
+    // Control point of the previous curve, reflected by the smooth curve commands
+    let mut last_ctrl: Option<(char, [f32; 2])> = None;
+    let mut command = None;
+
+    loop {
+        scanner.skip_separators();
+        let Some(c) = scanner.s.chars().next() else { break };
+        if c.is_ascii_alphabetic() {
+            command = Some(c);
+            scanner.s = &scanner.s[1..];
+        } else if command.is_none() {
+            return Err(SvgError::Syntax(format!("path data doesn't start with a command: {d}")));
+        }
+        let c = command.unwrap();
+        let relative = c.is_ascii_lowercase();
+        let origin = if relative { current } else { [0.0, 0.0] };
+        let offset = |p: [f32; 2]| [p[0] + origin[0], p[1] + origin[1]];
+        let reflect = |kinds: &str| match last_ctrl {
+            Some((kind, p)) if kinds.contains(kind) => [2.0 * current[0] - p[0], 2.0 * current[1] - p[1]],
+            _ => current,
+        };
+
+        let mut ctrl = None;
+        match c.to_ascii_uppercase() {
+            'M' => {
+                current = offset(scanner.point()?);
+                start = current;
+                commands.push(PathCommand::MoveTo(current));
+                // Following coordinates are implicit line tos
+                command = Some(if relative { 'l' } else { 'L' });
+            }
+            'L' => {
+                current = offset(scanner.point()?);
+                commands.push(PathCommand::LineTo(current));
+            }
+            'H' => {
+                current = [scanner.number()? + origin[0], current[1]];
+                commands.push(PathCommand::LineTo(current));
+            }
+            'V' => {
+                current = [current[0], scanner.number()? + origin[1]];
+                commands.push(PathCommand::LineTo(current));
+            }
+            'Q' => {
+                let c1 = offset(scanner.point()?);
+                current = offset(scanner.point()?);
+                commands.push(PathCommand::QuadTo(c1, current));
+                ctrl = Some(('Q', c1));
+            }
+            'T' => {
+                let c1 = reflect("Q");
+                current = offset(scanner.point()?);
+                commands.push(PathCommand::QuadTo(c1, current));
+                ctrl = Some(('Q', c1));
+            }
+            'C' => {
+                let c1 = offset(scanner.point()?);
+                let c2 = offset(scanner.point()?);
+                current = offset(scanner.point()?);
+                commands.push(PathCommand::CubicTo(c1, c2, current));
+                ctrl = Some(('C', c2));
+            }
+            'S' => {
+                let c1 = reflect("C");
+                let c2 = offset(scanner.point()?);
+                current = offset(scanner.point()?);
+                commands.push(PathCommand::CubicTo(c1, c2, current));
+                ctrl = Some(('C', c2));
+            }
+            'A' => {
+                let radii = scanner.point()?;
+                let rotation = scanner.number()?.to_radians();
+                let large_arc = scanner.flag()?;
+                let sweep = scanner.flag()?;
+                current = offset(scanner.point()?);
+                commands.push(PathCommand::ArcTo { radii, rotation, large_arc, sweep, to: current });
+            }
+            'Z' => {
+                commands.push(PathCommand::Close);
+                current = start;
+                // Another command letter must follow
+                command = None;
+                if scanner.has_number() {
+                    return Err(SvgError::Syntax(format!("numbers after a close path: {}", scanner.s)));
+                }
+            }
+            _ => return Err(SvgError::Unsupported(format!("path command {c}"))),
+        }
+        last_ctrl = ctrl;
+        if command.is_some() && !scanner.has_number() {
+            command = None;
+        }
+    }
+    Ok(commands)
+}
+
+fn rect_commands(x: f32, y: f32, w: f32, h: f32, rx: f32, ry: f32) -> Vec<PathCommand> {
+    if w <= 0.0 || h <= 0.0 {
+        return vec![];
+    }
+    if rx <= 0.0 || ry <= 0.0 {
+        return vec![
+            PathCommand::MoveTo([x, y]),
+            PathCommand::LineTo([x + w, y]),
+            PathCommand::LineTo([x + w, y + h]),
+            PathCommand::LineTo([x, y + h]),
+            PathCommand::Close,
+        ];
+    }
+    let arc = |to: [f32; 2]| PathCommand::ArcTo { radii: [rx, ry], rotation: 0.0, large_arc: false, sweep: true, to };
+    vec![
+        PathCommand::MoveTo([x + rx, y]),
+        PathCommand::LineTo([x + w - rx, y]),
+        arc([x + w, y + ry]),
+        PathCommand::LineTo([x + w, y + h - ry]),
+        arc([x + w - rx, y + h]),
+        PathCommand::LineTo([x + rx, y + h]),
+        arc([x, y + h - ry]),
+        PathCommand::LineTo([x, y + ry]),
+        arc([x + rx, y]),
+        PathCommand::Close,
+    ]
+}
+
+fn ellipse_commands(cx: f32, cy: f32, rx: f32, ry: f32) -> Vec<PathCommand> {
+    if rx <= 0.0 || ry <= 0.0 {
+        return vec![];
+    }
+    let arc = |to: [f32; 2]| PathCommand::ArcTo { radii: [rx, ry], rotation: 0.0, large_arc: false, sweep: true, to };
+    vec![
+        PathCommand::MoveTo([cx + rx, cy]),
+        arc([cx - rx, cy]),
+        arc([cx + rx, cy]),
+        PathCommand::Close,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unbalanced_close_tags() {
+        for svg in ["<svg></g></g><path/>", "</svg>", r#"<svg viewBox="0 0 1 1"><g></g></g><path d="M0 0L1 1"/></svg>"#] {
+            assert!(matches!(parse(svg), Err(SvgError::Syntax(_))), "{svg}");
+        }
+        let paths = parse(r#"<svg viewBox="0 0 24 24"><g fill="none" stroke="red"><path d="M0 0L1 1"/></g><path d="M1 1h2"/></svg>"#).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].fill.is_none() && paths[0].stroke.is_some());
+        assert_eq!(paths[1].fill, Some(FillRule::NonZero));
+        assert_eq!(paths[1].viewbox, [0.0, 0.0, 24.0, 24.0]);
+    }
+
+    #[test]
+    fn path_data() {
+        use PathCommand::*;
+        let commands = parse_path_data("M1-2.5.5 1l1,1H4v-1zm1 1Q2 2 3 3t1 0C1 1 2 2 3 3s1 1 2 2").unwrap();
+        assert_eq!(commands, [
+            MoveTo([1.0, -2.5]),
+            LineTo([0.5, 1.0]),
+            LineTo([1.5, 2.0]),
+            LineTo([4.0, 2.0]),
+            LineTo([4.0, 1.0]),
+            Close,
+            MoveTo([2.0, -1.5]),
+            QuadTo([2.0, 2.0], [3.0, 3.0]),
+            QuadTo([4.0, 4.0], [4.0, 3.0]),
+            CubicTo([1.0, 1.0], [2.0, 2.0], [3.0, 3.0]),
+            CubicTo([4.0, 4.0], [4.0, 4.0], [5.0, 5.0]),
+        ]);
+        assert!(matches!(parse_path_data("1 2"), Err(SvgError::Syntax(_))));
+        assert!(matches!(parse_path_data("M0 0Z 1 2"), Err(SvgError::Syntax(_))));
+        assert!(matches!(parse_path_data("M0 0B1 2"), Err(SvgError::Unsupported(_))));
+    }
+
+    #[test]
+    fn arcs() {
+        // Flags may be followed by numbers without separators
+        let commands = parse_path_data("M0 0A5 6 90 1 0 10 0a5,5 0 0110 10").unwrap();
+        assert_eq!(commands[1], PathCommand::ArcTo { radii: [5.0, 6.0], rotation: 90f32.to_radians(), large_arc: true, sweep: false, to: [10.0, 0.0] });
+        assert_eq!(commands[2], PathCommand::ArcTo { radii: [5.0, 5.0], rotation: 0.0, large_arc: false, sweep: true, to: [20.0, 10.0] });
+        assert!(matches!(parse_path_data("M0 0A5 5 0 2 0 1 1"), Err(SvgError::Syntax(_))));
+
+        let circle = parse(r#"<svg width="10" height="10"><circle cx="5" cy="5" r="4"/></svg>"#).unwrap();
+        assert_eq!(circle[0].viewbox, [0.0, 0.0, 10.0, 10.0]);
+        assert_eq!(circle[0].commands.len(), 4);
+        assert!(matches!(circle[0].commands[1], PathCommand::ArcTo { to: [1.0, 5.0], .. }));
+    }
+}
@@ -0,0 +1,5 @@
+/// Set in `ty[1]` of the selected tab of a tab bar.
+/// Must match `TAB_SELECTED` in `w__fragment.wgsl`.
+pub(super) const TAB_SELECTED: u32 = 1;
+/// Shift of the tab bar in `ty[1]` of tabs. Selecting a tab deselects the others of its bar.
+pub(super) const TAB_BAR_SHIFT: u32 = 8;
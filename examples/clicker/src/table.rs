@@ -0,0 +1,217 @@
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::{list::{DataSource, ItemLayout}, state::{Widget, WidgetType}};
+
+/// `ty[1]` of a table header cell sorting its column in ascending order.
+/// Must match `SORT_ASCENDING` in `w__fragment.wgsl`.
+pub(super) const SORT_ASCENDING: u32 = 1;
+/// `ty[1]` of a table header cell sorting its column in descending order.
+/// Must match `SORT_DESCENDING` in `w__fragment.wgsl`.
+pub(super) const SORT_DESCENDING: u32 = 2;
+/// Distance in pixels from the right edge of a header cell where dragging resizes its column.
+pub(super) const RESIZE_GRIP: f32 = 4.0;
+/// Narrowest a column can be resized to, in pixels.
+pub(super) const MIN_COLUMN_WIDTH: f32 = 16.0;
+
+/// A column of a table, rendering its cells.
+pub(super) trait Column {
+    /// The widget showing the cell of the data row `row` at `limits`.
+    /// Its state must be in its `ty`: sliders, text inputs and scroll views can't be cells.
+    fn cell(&mut self, row: usize, limits: [f32; 4]) -> Widget;
+
+    /// Whether clicking the header sorts the rows by this column.
+    fn sortable(&self) -> bool {
+        false
+    }
+
+    /// How the data rows `a` and `b` compare in this column, for sortable columns.
+    fn compare(&self, _a: usize, _b: usize) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    /// `ty[1]` of the header cell of the sorted column.
+    pub(super) fn ty(&self) -> u32 {
+        match self {
+            Self::Ascending => SORT_ASCENDING,
+            Self::Descending => SORT_DESCENDING,
+        }
+    }
+}
+
+/// Where the parts of a table go, and their styles.
+/// A table is a scroll view holding its header cells, followed by a list view of its rows.
+/// The header doesn't scroll vertically, and scrolls horizontally with the rows.
+#[derive(Clone, Debug)]
+pub(super) struct TableLayout {
+    limits: [f32; 4],
+    header_height: f32,
+    row_height: f32,
+    /// Widths of the columns, in NDC.
+    pub(super) widths: Vec<f32>,
+    header_style: u32,
+    row_style: u32,
+    selected_style: u32,
+}
+
+impl TableLayout {
+    /// A table at `limits` with columns of `widths`, all sizes in NDC.
+    pub(super) fn new(limits: [f32; 4], header_height: f32, row_height: f32, widths: &[f32]) -> Self {
+        Self {
+            limits,
+            header_height,
+            row_height,
+            widths: widths.to_vec(),
+            header_style: 0,
+            row_style: 0,
+            selected_style: 0,
+        }
+    }
+
+    /// Builder method setting the styles of the header cells, of the rows, and of the selected row.
+    pub(super) fn with_styles(mut self, header: u32, row: u32, selected: u32) -> Self {
+        self.header_style = header;
+        self.row_style = row;
+        self.selected_style = selected;
+        self
+    }
+
+    /// The limits of the header cell of `column`, when not scrolled.
+    pub(super) fn header_cell(&self, column: usize) -> [f32; 4] {
+        let left = self.limits[0] + self.widths[..column].iter().sum::<f32>();
+        [left, left + self.widths[column], self.limits[3] - self.header_height, self.limits[3]]
+    }
+
+    /// The layout of the rows, below the header.
+    pub(super) fn body(&self) -> ItemLayout {
+        let [left, right, bottom, top] = self.limits;
+        ItemLayout::table([left, right, bottom, top - self.header_height], self.row_height, &self.widths)
+    }
+
+    /// Move the table by `offset` in NDC, after its widgets were moved.
+    pub(super) fn translate(&mut self, offset: [f32; 2]) {
+        let [left, right, bottom, top] = self.limits;
+        self.limits = [left + offset[0], right + offset[0], bottom + offset[1], top + offset[1]];
+    }
+
+    /// The index of the list view of the rows, for a table at `index`.
+    pub(super) fn body_index(&self, index: usize) -> usize {
+        index + 1 + self.widths.len()
+    }
+
+    /// The widgets of the table, to be put at `index` in the widgets:
+    /// the header's scroll view, its cells, then the list view of the rows and its slots.
+    pub(super) fn widgets(&self, index: usize) -> Vec<Widget> {
+        let [left, right, _, top] = self.limits;
+        let mut widgets = vec![Widget::scroll_view([left, right, top - self.header_height, top]).with_style(self.header_style)];
+        widgets.extend((0..self.widths.len()).map(|column| {
+            Widget::new(self.header_cell(column), WidgetType::TableHeader).with_style(self.header_style).in_scroll_view(index)
+        }));
+        widgets.extend(self.body().widgets(self.body_index(index)));
+        widgets
+    }
+}
+
+/// The rows of a table: their columns, how they are sorted and which one is selected.
+pub(super) struct TableRows {
+    len: usize,
+    columns: Vec<Box<dyn Column>>,
+    /// The data row shown at each position, `None` until sorted.
+    order: Option<Vec<usize>>,
+    pub(super) sort: Option<(usize, SortOrder)>,
+    /// The selected data row.
+    pub(super) selected: Option<usize>,
+    row_style: u32,
+    selected_style: u32,
+}
+
+impl TableRows {
+    pub(super) fn new(layout: &TableLayout, len: usize, columns: Vec<Box<dyn Column>>) -> Self {
+        Self {
+            len,
+            columns,
+            order: None,
+            sort: None,
+            selected: None,
+            row_style: layout.row_style,
+            selected_style: layout.selected_style,
+        }
+    }
+
+    pub(super) fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub(super) fn is_sortable(&self, column: usize) -> bool {
+        self.columns[column].sortable()
+    }
+
+    /// The data row shown at `position`.
+    pub(super) fn row(&self, position: usize) -> usize {
+        self.order.as_ref().map_or(position, |order| order[position])
+    }
+
+    /// The position the data row `row` is shown at.
+    pub(super) fn position(&self, row: usize) -> usize {
+        self.order.as_ref().map_or(row, |order| order.iter().position(|&r| r == row).unwrap_or(row))
+    }
+
+    /// Sort the rows by `column`, keeping the order of equal rows.
+    pub(super) fn sort_by(&mut self, column: usize, sort: SortOrder) {
+        let order = self.order.get_or_insert_with(|| (0..self.len).collect());
+        let column = &self.columns[column];
+        match sort {
+            SortOrder::Ascending => order.sort_by(|&a, &b| column.compare(a, b)),
+            SortOrder::Descending => order.sort_by(|&a, &b| column.compare(b, a)),
+        }
+    }
+}
+
+/// The data source of the list view of a table's rows, sharing them with the state.
+/// Each row is its background, then its cells.
+pub(super) struct TableSource(pub(super) Rc<RefCell<TableRows>>);
+
+impl DataSource for TableSource {
+    fn len(&self) -> usize {
+        let rows = self.0.borrow();
+        rows.len * (rows.columns.len() + 1)
+    }
+
+    fn widget(&mut self, item: usize, limits: [f32; 4]) -> Widget {
+        let mut rows = self.0.borrow_mut();
+        let per_row = rows.columns.len() + 1;
+        let row = rows.row(item / per_row);
+        match item % per_row {
+            0 => {
+                let style = if rows.selected == Some(row) { rows.selected_style } else { rows.row_style };
+                Widget::new(limits, WidgetType::Rect).with_style(style)
+            }
+            column => rows.columns[column - 1].cell(row, limits),
+        }
+    }
+}
+
+/// A table, by the index of its header's scroll view.
+pub(super) struct Table {
+    pub(super) layout: TableLayout,
+    pub(super) rows: Rc<RefCell<TableRows>>,
+    /// Index of the list view of the rows.
+    pub(super) body: usize,
+}
+
+/// A pointer resizing a column of a table by dragging the edge of its header cell.
+pub(super) struct ColumnDrag {
+    pub(super) table: usize,
+    pub(super) column: usize,
+    /// Where the pointer was pressed, in pixels.
+    pub(super) start: f64,
+    /// The width of the column when pressed, in NDC.
+    pub(super) width: f32,
+}
@@ -0,0 +1,351 @@
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{atlas::Atlas, canvas::Canvas, clipboard::Clipboard, font::Fonts};
+
+/// Space between the border of a text widget and its content, in pixels.
+/// Must match `TEXT_PADDING` in `w__fragment.wgsl`.
+pub(super) const TEXT_PADDING: f32 = 4.0;
+/// Width of a character cell relative to the height of the text line, wide characters taking two cells.
+/// Must match `TEXT_CELL_ASPECT` in `w__fragment.wgsl`.
+pub(super) const TEXT_CELL_ASPECT: f32 = 0.5;
+/// Set in `ty[1]` of a text widget when it has the keyboard focus.
+pub(super) const TEXT_FOCUSED: u32 = 0x8000_0000;
+/// Color of the glyphs of text widgets, like their caret.
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+/// Size of the font relative to the height of the text line, fitting the advance of monospace fonts in a cell.
+const TEXT_FONT_SCALE: f32 = 0.8;
+
+/// The editable content of a text widget.
+#[derive(Default)]
+pub(super) struct TextEdit {
+    text: String,
+    /// Byte index of the cursor in `text`.
+    cursor: usize,
+    /// Byte index of the other end of the selection, if there is one.
+    anchor: Option<usize>,
+    /// Text being composed by the IME, with the cursor range inside of it, as given by winit.
+    preedit: Option<(String, Option<(usize, usize)>)>,
+}
+
+impl TextEdit {
+    #[allow(unused)]
+    pub(super) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the whole text with `text`, moving the cursor to its end.
+    pub(super) fn set_text(&mut self, text: &str) {
+        *self = Self { text: text.to_owned(), cursor: text.len(), ..Self::default() };
+    }
+
+    /// The selected byte range, if it isn't empty.
+    pub(super) fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        let range = anchor.min(self.cursor)..anchor.max(self.cursor);
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// Replace the selection (if any) with `s`, and move the cursor after it.
+    pub(super) fn insert(&mut self, s: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
+    pub(super) fn backspace(&mut self) {
+        if !self.delete_selection() && self.cursor > 0 {
+            let previous = self.previous_boundary(self.cursor);
+            self.text.replace_range(previous..self.cursor, "");
+            self.cursor = previous;
+        }
+    }
+
+    pub(super) fn delete(&mut self) {
+        if !self.delete_selection() && self.cursor < self.text.len() {
+            let next = self.next_boundary(self.cursor);
+            self.text.replace_range(self.cursor..next, "");
+        }
+    }
+
+    pub(super) fn move_left(&mut self, select: bool) {
+        self.move_to(self.previous_boundary(self.cursor), select);
+    }
+
+    pub(super) fn move_right(&mut self, select: bool) {
+        self.move_to(self.next_boundary(self.cursor), select);
+    }
+
+    pub(super) fn move_home(&mut self, select: bool) {
+        self.move_to(0, select);
+    }
+
+    pub(super) fn move_end(&mut self, select: bool) {
+        self.move_to(self.text.len(), select);
+    }
+
+    pub(super) fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.text.len();
+    }
+
+    pub(super) fn copy(&self, clipboard: &mut dyn Clipboard) {
+        if let Some(range) = self.selection() {
+            clipboard.set_text(self.text[range].to_owned());
+        }
+    }
+
+    pub(super) fn cut(&mut self, clipboard: &mut dyn Clipboard) {
+        self.copy(clipboard);
+        self.delete_selection();
+    }
+
+    pub(super) fn paste(&mut self, clipboard: &mut dyn Clipboard) {
+        if let Some(text) = clipboard.get_text() {
+            // Text widgets are single line.
+            self.insert(&text.replace(['\r', '\n'], " "));
+        }
+    }
+
+    /// Update the text being composed by the IME. An empty `text` ends the composition.
+    pub(super) fn set_preedit(&mut self, text: String, cursor: Option<(usize, usize)>) {
+        self.preedit = (!text.is_empty()).then_some((text, cursor));
+    }
+
+    /// Insert the text the IME finished composing.
+    pub(super) fn commit(&mut self, text: &str) {
+        self.preedit = None;
+        self.insert(text);
+    }
+
+    /// The cell column the caret is drawn at, accounting for the preedit cursor.
+    pub(super) fn caret_column(&self) -> usize {
+        let column = columns(&self.text[..self.cursor]);
+        match &self.preedit {
+            Some((preedit, Some((_, end)))) => column + columns(&preedit[..*end]),
+            Some((preedit, None)) => column + columns(preedit),
+            None => column,
+        }
+    }
+
+    /// The text as shown, with the preedit text at the cursor.
+    pub(super) fn shown_text(&self) -> String {
+        match &self.preedit {
+            Some((preedit, _)) => [&self.text[..self.cursor], preedit, &self.text[self.cursor..]].concat(),
+            None => self.text.clone(),
+        }
+    }
+
+    /// The glyphs of the shown text in `fonts`, rasterized into `atlas` as needed, centered in the cells of a line
+    /// `height` pixels tall from the top left of the widget past its padding. Characters of no width, like combining marks,
+    /// are drawn over the one before them. Without fonts, or with the atlas full, the pixel font of canvases is used.
+    pub(super) fn glyphs(&self, height: f32, fonts: &mut Fonts, atlas: &mut Atlas) -> Canvas {
+        let cell = height * TEXT_CELL_ASPECT;
+        let size = (height * TEXT_FONT_SCALE).round().max(1.0) as u32;
+        let [ascent, descent] = fonts.v_metrics(size);
+        let baseline = (TEXT_PADDING + (height - ascent + descent) * 0.5 + ascent).round();
+        let mut canvas = Canvas::new();
+        canvas.set_fill_color(TEXT_COLOR);
+        let (mut column, mut pen) = (0, TEXT_PADDING);
+        for c in self.shown_text().chars() {
+            let width = c.width().unwrap_or(0);
+            let left = TEXT_PADDING + column as f32 * cell;
+            column += width;
+            let Some(glyph) = fonts.glyph(atlas, c, size) else {
+                // The pixel font advances by 4/5 of its height, which fits it in the cell
+                let pixel_size = cell * 1.25;
+                canvas.fill_text(&c.to_string(), [left, TEXT_PADDING + (height - pixel_size) * 0.5], pixel_size);
+                continue;
+            };
+            if width > 0 {
+                pen = (left + (width as f32 * cell - glyph.advance) * 0.5).round();
+            }
+            if let Some(image) = glyph.image {
+                let [x0, y0, x1, y1] = glyph.bounds;
+                canvas.fill_image(image, pen + x0, baseline + y0, x1 - x0, y1 - y0);
+            }
+            pen += glyph.advance;
+        }
+        canvas
+    }
+
+    /// The range of cell columns covered by the preedit text, to be underlined.
+    pub(super) fn preedit_columns(&self) -> Option<Range<usize>> {
+        let (preedit, _) = self.preedit.as_ref()?;
+        let start = columns(&self.text[..self.cursor]);
+        Some(start..start + columns(preedit))
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let Some(range) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        self.text.replace_range(range.clone(), "");
+        self.cursor = range.start;
+        self.anchor = None;
+        true
+    }
+
+    fn move_to(&mut self, cursor: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = cursor;
+    }
+
+    fn previous_boundary(&self, index: usize) -> usize {
+        self.text[..index].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, index: usize) -> usize {
+        self.text[index..].chars().next().map_or(index, |c| index + c.len_utf8())
+    }
+}
+
+/// The cells `text` spans, wide characters taking two and combining ones none.
+fn columns(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::MemoryClipboard;
+
+    fn edit(text: &str) -> TextEdit {
+        let mut edit = TextEdit::default();
+        edit.set_text(text);
+        edit
+    }
+
+    #[test]
+    fn insert_and_delete() {
+        let mut e = edit("héllo");
+        e.move_left(false);
+        e.insert("X");
+        assert_eq!(e.text(), "héllXo");
+        e.backspace();
+        e.delete();
+        assert_eq!(e.text(), "héll");
+        e.move_home(false);
+        e.move_right(false);
+        e.delete();
+        assert_eq!(e.text(), "hll");
+        e.backspace();
+        e.backspace();
+        assert_eq!(e.text(), "ll");
+        e.move_end(false);
+        e.delete();
+        assert_eq!(e.text(), "ll");
+    }
+
+    #[test]
+    fn selection() {
+        let mut e = edit("hello world");
+        assert_eq!(e.selection(), None);
+        e.move_left(true);
+        e.move_left(true);
+        assert_eq!(e.selection(), Some(9..11));
+        e.insert("!");
+        assert_eq!(e.text(), "hello wor!");
+        assert_eq!(e.selection(), None);
+        e.move_home(true);
+        e.move_right(false);
+        assert_eq!(e.selection(), None);
+        e.select_all();
+        e.backspace();
+        assert_eq!(e.text(), "");
+    }
+
+    #[test]
+    fn preedit_columns() {
+        let mut e = edit("aé");
+        e.move_left(false);
+        assert_eq!(e.caret_column(), 1);
+        assert_eq!(e.preedit_columns(), None);
+        // Wide characters take two cells
+        e.set_preedit("かな".to_owned(), Some((3, 3)));
+        assert_eq!(e.preedit_columns(), Some(1..5));
+        assert_eq!(e.caret_column(), 3);
+        assert_eq!(e.shown_text(), "aかなé");
+        e.set_preedit("かな".to_owned(), None);
+        assert_eq!(e.caret_column(), 5);
+        e.commit("仮名");
+        assert_eq!(e.preedit_columns(), None);
+        assert_eq!(e.text(), "a仮名é");
+        assert_eq!(e.caret_column(), 5);
+        // Combining marks take none
+        e.set_preedit("e\u{301}".to_owned(), None);
+        assert_eq!(e.preedit_columns(), Some(5..6));
+        e.set_preedit("x".to_owned(), None);
+        e.set_preedit(String::new(), None);
+        assert_eq!(e.preedit_columns(), None);
+    }
+
+    #[test]
+    fn preedit_glyphs() {
+        let mut fonts = Fonts::default();
+        fonts.add(include_bytes!("../assets/DejaVuSansMono.ttf").to_vec()).unwrap();
+        let mut atlas = Atlas::new(512);
+        let mut e = edit("a");
+        e.set_preedit("ёж日".to_owned(), None);
+        // Cells of 10 pixels, the font being 16 pixels
+        let canvas = e.glyphs(20.0, &mut fonts, &mut atlas);
+
+        let glyph = |fonts: &mut Fonts, atlas: &mut Atlas, c| fonts.glyph(atlas, c, 16).unwrap();
+        let question = glyph(&mut fonts, &mut atlas, '?').image;
+        let images: Vec<_> = canvas.primitives().iter().map(|p| p.image()).collect();
+        let expected: Vec<_> = "aёж日".chars().map(|c| glyph(&mut fonts, &mut atlas, c).image).collect();
+        assert_eq!(images, expected);
+        assert!(images.iter().all(|&image| image.is_some() && image != question));
+        assert_eq!(atlas.regions().len(), 5);
+
+        // Each glyph is centered in its cells, '日' spanning two
+        let centers: Vec<_> = canvas.primitives().iter().map(|p| (p.shape()[0] + p.shape()[2]) * 0.5).collect();
+        for (center, cells) in centers.iter().zip([4.0..14.0, 14.0..24.0, 24.0..34.0, 34.0..54.0]) {
+            assert!(cells.contains(center), "Glyph centered at {center} outside of {cells:?}");
+        }
+        assert_eq!(e.preedit_columns(), Some(1..5));
+        assert_eq!(e.caret_column(), 5);
+
+        // Drawing again rasterizes nothing more
+        e.glyphs(20.0, &mut fonts, &mut atlas);
+        assert_eq!(atlas.regions().len(), 5);
+    }
+
+    #[test]
+    fn clipboard() {
+        let mut clipboard = MemoryClipboard::default();
+        let mut e = edit("copy me");
+        e.paste(&mut clipboard);
+        assert_eq!(e.text(), "copy me");
+        e.copy(&mut clipboard);
+        assert_eq!(clipboard.get_text(), None);
+
+        e.move_home(false);
+        for _ in 0..4 {
+            e.move_right(true);
+        }
+        e.copy(&mut clipboard);
+        assert_eq!(clipboard.get_text().as_deref(), Some("copy"));
+        assert_eq!(e.text(), "copy me");
+        e.move_end(false);
+        e.move_left(true);
+        e.move_left(true);
+        e.cut(&mut clipboard);
+        assert_eq!(e.text(), "copy ");
+        e.paste(&mut clipboard);
+        e.paste(&mut clipboard);
+        assert_eq!(e.text(), "copy meme");
+
+        // Pasted lines are joined on the single line
+        clipboard.set_text("a\nb\r\nc".to_owned());
+        e.select_all();
+        e.paste(&mut clipboard);
+        assert_eq!(e.text(), "a b  c");
+    }
+}
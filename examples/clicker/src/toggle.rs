@@ -0,0 +1,72 @@
+use web_time::{Duration, Instant};
+
+/// Duration of the transition between the unchecked and checked looks of a toggle widget.
+const TRANSITION_DURATION: Duration = Duration::from_millis(120);
+/// The bits of `ty[1]` of a toggle widget holding its `CheckState`, the others hold its radio group.
+pub(super) const CHECK_STATE_MASK: u32 = 0xff;
+/// Shift of the radio group in `ty[1]` of radio buttons.
+pub(super) const RADIO_GROUP_SHIFT: u32 = 8;
+
+/// State of a checkbox, radio button or toggle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum CheckState {
+    Unchecked,
+    Checked,
+    /// Neither checked nor unchecked, like a checkbox for a partially selected group.
+    /// Only set by the application, activating the widget checks it.
+    Indeterminate,
+}
+
+impl CheckState {
+    pub(super) fn ty(&self) -> u32 {
+        match self {
+            Self::Unchecked => 0,
+            Self::Checked => 1,
+            Self::Indeterminate => 2,
+        }
+    }
+
+    pub(super) fn from_ty(ty: u32) -> Self {
+        match ty & CHECK_STATE_MASK {
+            1 => Self::Checked,
+            2 => Self::Indeterminate,
+            _ => Self::Unchecked,
+        }
+    }
+
+    /// The state after the user activates a checkbox or toggle in this state.
+    pub(super) fn toggled(&self) -> Self {
+        match self {
+            Self::Checked => Self::Unchecked,
+            Self::Unchecked | Self::Indeterminate => Self::Checked,
+        }
+    }
+
+    /// Progress of the transition when this state is fully shown, as stored in `ty[2]`.
+    pub(super) fn progress(&self) -> f32 {
+        match self {
+            Self::Unchecked => 0.0,
+            Self::Checked | Self::Indeterminate => 1.0,
+        }
+    }
+}
+
+/// A running transition of a toggle widget between two progresses.
+pub(super) struct Transition {
+    from: f32,
+    to: f32,
+    start: Instant,
+}
+
+impl Transition {
+    pub(super) fn new(from: f32, to: f32) -> Self {
+        Self { from, to, start: Instant::now() }
+    }
+
+    /// The eased progress at `now`, and whether the transition is over.
+    pub(super) fn progress(&self, now: Instant) -> (f32, bool) {
+        let t = (now.saturating_duration_since(self.start).as_secs_f32() / TRANSITION_DURATION.as_secs_f32()).min(1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        (self.from + (self.to - self.from) * eased, t >= 1.0)
+    }
+}
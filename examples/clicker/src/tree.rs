@@ -0,0 +1,286 @@
+use std::{cell::RefCell, collections::{HashMap, HashSet}, rc::Rc};
+
+use crate::{list::DataSource, state::Widget};
+
+/// Indentation of each level of a tree view, in pixels. The arrows of expandable nodes fill one.
+/// Must match `TREE_INDENT` in `w__fragment.wgsl`.
+pub(super) const TREE_INDENT: f32 = 16.0;
+/// Mask of the depth of the node in `ty[1]` of tree rows.
+pub(super) const TREE_DEPTH_MASK: u32 = 0xffff;
+/// Set in `ty[1]` of tree rows whose node may have children, with an arrow.
+/// Must match `TREE_EXPANDABLE` in `w__fragment.wgsl`.
+pub(super) const TREE_EXPANDABLE: u32 = 1 << 16;
+/// Set in `ty[1]` of tree rows whose node is expanded, its arrow pointing down.
+/// Must match `TREE_EXPANDED` in `w__fragment.wgsl`.
+pub(super) const TREE_EXPANDED: u32 = 1 << 17;
+/// Shift of the `DropPosition` shown on a tree row in `ty[1]`, 0 for none.
+/// Must match `TREE_DROP_SHIFT` in `w__fragment.wgsl`.
+pub(super) const TREE_DROP_SHIFT: u32 = 18;
+
+/// Identifies a node of a tree view, chosen by its `TreeSource`.
+pub(super) type NodeId = u64;
+
+/// The nodes of a tree view, loaded as they are expanded.
+pub(super) trait TreeSource {
+    /// The children of `node`, or the roots for `None`. Asked when the node is first expanded.
+    fn children(&mut self, node: Option<NodeId>) -> Vec<NodeId>;
+
+    /// Whether `node` may have children, giving it an arrow to expand it.
+    fn has_children(&self, node: NodeId) -> bool;
+
+    /// The widget showing `node` at `limits`, right of its arrow.
+    /// Its state must be in its `ty`: sliders, text inputs and scroll views can't be nodes.
+    fn widget(&mut self, node: NodeId, limits: [f32; 4]) -> Widget;
+}
+
+/// Where dragged nodes would be dropped, relative to the node under the pointer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum DropPosition {
+    Before,
+    After,
+    /// As a child of the node.
+    Inside,
+}
+
+impl DropPosition {
+    /// Shown on the tree row of the node, shifted by `TREE_DROP_SHIFT`.
+    pub(super) fn ty(&self) -> u32 {
+        match self {
+            Self::Before => 1,
+            Self::After => 2,
+            Self::Inside => 3,
+        }
+    }
+}
+
+/// How a press changes the selection of a tree view.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) enum SelectMode {
+    /// Select only the node.
+    Single,
+    /// Add or remove the node, with control.
+    Toggle,
+    /// Select the rows from the last selected node to the node, with shift.
+    Range,
+}
+
+/// A visible node of a tree view.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct TreeRow {
+    pub(super) node: NodeId,
+    pub(super) depth: u32,
+    pub(super) expandable: bool,
+    pub(super) expanded: bool,
+}
+
+/// The nodes of a tree view: those loaded, expanded and selected, and the rows of those visible.
+pub(super) struct TreeModel {
+    source: Box<dyn TreeSource>,
+    /// The children of the nodes loaded so far, by parent, `None` for the roots.
+    children: HashMap<Option<NodeId>, Vec<NodeId>>,
+    expanded: HashSet<NodeId>,
+    /// The visible nodes, in order.
+    pub(super) rows: Vec<TreeRow>,
+    pub(super) selected: HashSet<NodeId>,
+    /// The node moved by the keyboard, the last one pressed.
+    pub(super) cursor: Option<NodeId>,
+    /// The node ranges are selected from, the last one selected without shift.
+    anchor: Option<NodeId>,
+    /// Where the dragged nodes would be dropped, by row.
+    pub(super) drop: Option<(usize, DropPosition)>,
+    /// Indentation of each level in NDC, from `TREE_INDENT` and the width of the surface.
+    pub(super) indent: f32,
+    row_style: u32,
+    selected_style: u32,
+}
+
+impl TreeModel {
+    pub(super) fn new(source: Box<dyn TreeSource>, row_style: u32, selected_style: u32) -> Self {
+        let mut tree = Self {
+            source,
+            children: HashMap::new(),
+            expanded: HashSet::new(),
+            rows: vec![],
+            selected: HashSet::new(),
+            cursor: None,
+            anchor: None,
+            drop: None,
+            indent: 0.0,
+            row_style,
+            selected_style,
+        };
+        tree.rebuild();
+        tree
+    }
+
+    /// Forget the loaded children, asking the source for them again, keeping the expanded nodes that are still there.
+    pub(super) fn reload(&mut self) {
+        self.children.clear();
+        self.rebuild();
+        self.forget_hidden();
+    }
+
+    /// Deselect the nodes that aren't visible anymore.
+    fn forget_hidden(&mut self) {
+        let visible: HashSet<NodeId> = self.rows.iter().map(|r| r.node).collect();
+        self.selected.retain(|n| visible.contains(n));
+        self.cursor = self.cursor.filter(|n| visible.contains(n));
+        self.anchor = self.anchor.filter(|n| visible.contains(n));
+    }
+
+    /// Flatten the expanded nodes into rows, loading the children of those expanded for the first time.
+    /// Nodes already in a row are skipped, so that a source listing an ancestor as a child doesn't loop forever.
+    fn rebuild(&mut self) {
+        let mut rows = vec![];
+        let mut shown = HashSet::new();
+        let mut stack: Vec<(NodeId, u32)> = self.load(None).into_iter().rev().map(|n| (n, 0)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            if !shown.insert(node) {
+                continue;
+            }
+            let expandable = self.source.has_children(node);
+            let expanded = expandable && self.expanded.contains(&node);
+            rows.push(TreeRow { node, depth, expandable, expanded });
+            if expanded {
+                stack.extend(self.load(Some(node)).into_iter().rev().map(|n| (n, depth + 1)));
+            }
+        }
+        self.rows = rows;
+    }
+
+    fn load(&mut self, node: Option<NodeId>) -> Vec<NodeId> {
+        let source = &mut self.source;
+        self.children.entry(node).or_insert_with(|| source.children(node)).clone()
+    }
+
+    pub(super) fn row_of(&self, node: NodeId) -> Option<usize> {
+        self.rows.iter().position(|r| r.node == node)
+    }
+
+    /// The row of the parent of the node at `row`.
+    pub(super) fn parent_row(&self, row: usize) -> Option<usize> {
+        let depth = self.rows[row].depth;
+        self.rows[..row].iter().rposition(|r| r.depth < depth)
+    }
+
+    /// Expand or collapse `node`. Returns whether it changed.
+    pub(super) fn set_expanded(&mut self, node: NodeId, expanded: bool) -> bool {
+        let changed = if expanded && self.source.has_children(node) {
+            self.expanded.insert(node)
+        } else {
+            self.expanded.remove(&node)
+        };
+        if changed {
+            self.rebuild();
+            self.forget_hidden();
+        }
+        changed
+    }
+
+    /// Select `node` according to `mode`, making it the cursor. Returns whether the selection changed.
+    pub(super) fn select(&mut self, node: NodeId, mode: SelectMode) -> bool {
+        let old = self.selected.clone();
+        match (mode, self.anchor.and_then(|a| self.row_of(a)), self.row_of(node)) {
+            (SelectMode::Range, Some(from), Some(to)) => {
+                // The range keeps growing from the same end
+                self.selected = self.rows[from.min(to)..=from.max(to)].iter().map(|r| r.node).collect();
+            }
+            (SelectMode::Toggle, ..) => {
+                if !self.selected.remove(&node) {
+                    self.selected.insert(node);
+                }
+                self.anchor = Some(node);
+            }
+            _ => {
+                self.selected = HashSet::from([node]);
+                self.anchor = Some(node);
+            }
+        }
+        self.cursor = Some(node);
+        self.selected != old
+    }
+
+    /// The tree row widget of `row`: its background, indentation guides, arrow and drop indicator.
+    fn row_widget(&self, row: usize, limits: [f32; 4]) -> Widget {
+        let r = self.rows[row];
+        let mut ty = r.depth & TREE_DEPTH_MASK;
+        if r.expandable {
+            ty |= TREE_EXPANDABLE;
+        }
+        if r.expanded {
+            ty |= TREE_EXPANDED;
+        }
+        if let Some((_, position)) = self.drop.filter(|(drop, _)| *drop == row) {
+            ty |= position.ty() << TREE_DROP_SHIFT;
+        }
+        let style = if self.selected.contains(&r.node) { self.selected_style } else { self.row_style };
+        Widget::tree_row(limits, ty).with_style(style)
+    }
+}
+
+/// The data source of the list view of a tree, sharing its nodes with the state.
+/// Each row is its tree row widget, then the widget of its node.
+pub(super) struct TreeRows(pub(super) Rc<RefCell<TreeModel>>);
+
+impl DataSource for TreeRows {
+    fn len(&self) -> usize {
+        self.0.borrow().rows.len() * 2
+    }
+
+    fn widget(&mut self, item: usize, limits: [f32; 4]) -> Widget {
+        let mut tree = self.0.borrow_mut();
+        let row = item / 2;
+        if item.is_multiple_of(2) {
+            return tree.row_widget(row, limits);
+        }
+        let r = tree.rows[row];
+        let left = limits[0] + (r.depth + 1) as f32 * tree.indent;
+        tree.source.widget(r.node, [left.min(limits[1]), limits[1], limits[2], limits[3]])
+    }
+}
+
+/// The mouse dragging the selected nodes of a tree view to reorder them.
+pub(super) struct TreeDrag {
+    pub(super) tree: usize,
+    /// Where the pointer was pressed, in pixels.
+    pub(super) start: [f64; 2],
+    /// Whether the pointer moved past `TOUCH_SLOP`, the nodes being dragged.
+    pub(super) moving: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::WidgetType;
+
+    /// Node `n` has the children `2n + 1` and `2n + 2`, but node 5 has its grandparent 0 and its sibling 6 as children.
+    struct Cyclic;
+
+    impl TreeSource for Cyclic {
+        fn children(&mut self, node: Option<NodeId>) -> Vec<NodeId> {
+            match node {
+                None => vec![0],
+                Some(5) => vec![0, 6],
+                Some(n) => vec![2 * n + 1, 2 * n + 2],
+            }
+        }
+
+        fn has_children(&self, _: NodeId) -> bool {
+            true
+        }
+
+        fn widget(&mut self, _: NodeId, limits: [f32; 4]) -> Widget {
+            Widget::new(limits, WidgetType::Rect)
+        }
+    }
+
+    #[test]
+    fn cycles() {
+        let mut tree = TreeModel::new(Box::new(Cyclic), 0, 0);
+        for node in [0, 2, 5] {
+            assert!(tree.set_expanded(node, true));
+        }
+        let rows: Vec<(NodeId, u32)> = tree.rows.iter().map(|r| (r.node, r.depth)).collect();
+        assert_eq!(rows, [(0, 0), (1, 1), (2, 1), (5, 2), (6, 3)]);
+    }
+}
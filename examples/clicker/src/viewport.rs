@@ -0,0 +1,51 @@
+use winit::event::{KeyEvent, MouseButton};
+
+/// Something the user did to a viewport, positions being in pixels from its top left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum ViewportEvent {
+    /// The pointer moved over the viewport, or anywhere while a button pressed on it is held.
+    PointerMoved { position: [f32; 2] },
+    /// The pointer left the viewport.
+    PointerLeft,
+    /// A mouse button was pressed on the viewport, or released after being pressed on it.
+    Button { button: MouseButton, pressed: bool, position: [f32; 2] },
+    /// The wheel turned over the viewport, by `delta` pixels, +Y being up.
+    Wheel { delta: [f32; 2], position: [f32; 2] },
+    /// A key was pressed or released while the viewport has the focus.
+    Key(KeyEvent),
+}
+
+/// A scene of the application shown by a viewport widget, rendered with the device of `State` to a texture it owns.
+pub(super) trait Viewport {
+    /// Create the texture of `size` pixels the scene is rendered to, returning a view of it to show.
+    /// Its format must be filterable and sampled as float. Called before the viewport is first drawn,
+    /// and whenever the size of the widget in pixels changes.
+    fn resize(&mut self, device: &wgpu::Device, size: [u32; 2]) -> wgpu::TextureView;
+
+    /// Render the scene to its texture with `encoder`, before the widgets are drawn, each frame the viewport is shown.
+    fn draw(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder);
+
+    /// React to `event`. Returns whether it was consumed, drawing the viewport again.
+    fn input(&mut self, event: &ViewportEvent) -> bool;
+
+    /// Whether the scene moves by itself, the screen being redrawn continuously.
+    fn is_animated(&self) -> bool {
+        false
+    }
+}
+
+/// A viewport widget with the texture it shows.
+pub(super) struct ViewportModel {
+    pub(super) scene: Box<dyn Viewport>,
+    /// Size of the texture in pixels, 0 before it is created.
+    pub(super) size: [u32; 2],
+    pub(super) view: Option<wgpu::TextureView>,
+    /// The textures with the texture of the scene in place of the atlas.
+    pub(super) bind_group: Option<wgpu::BindGroup>,
+}
+
+impl ViewportModel {
+    pub(super) fn new(scene: Box<dyn Viewport>) -> Self {
+        Self { scene, size: [0, 0], view: None, bind_group: None }
+    }
+}
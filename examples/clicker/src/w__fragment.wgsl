@@ -2,15 +2,88 @@ struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) pos: vec4<f32>,
     @location(1) @interpolate(flat) id: u32,
+    // Slot of the series of chart quads, and their instance, the index of their point,
+    // or the index of the primitive of canvas quads in y
+    @location(2) @interpolate(flat) series: vec2<u32>,
 };
 
 struct General {
     resolution: vec2<u32>,
     resized: vec2<u32>,
+    // Seconds since the start, animating widgets
+    time: f32,
 }
 struct Widget {
     @location(0) limits: vec4<f32>,
     @location(1) ty: vec4<u32>,
+    // Index + 1 of the scroll view or popup containing the widget, 0 for none
+    parent: u32,
+    // For list and grid views, how far down their items are placed from, not included in ty[2]
+    scroll_base: f32,
+    // How far the content of scroll views extends past their right and bottom, in NDC
+    scroll_max: vec2<f32>,
+};
+struct Style {
+    fill: vec4<f32>,
+    border_color: vec4<f32>,
+    // top left, top right, bottom right, bottom left
+    radii: vec4<f32>,
+    border_width: f32,
+    opacity: f32,
+    // 0: solid, 1: linear, 2: radial, 3: conic
+    gradient: u32,
+    stop_count: u32,
+    gradient_params: vec4<f32>,
+    stop_colors: array<vec4<f32>, 4>,
+    stop_offsets: vec4<f32>,
+    shadow_color: vec4<f32>,
+    // offset x, offset y, blur, spread
+    shadow: vec4<f32>,
+    backdrop_blur: f32,
+    // Index + 1 of the atlas image replacing the fill, 0 for none
+    skin: u32,
+    // left, top, right, bottom in pixels
+    skin_insets: vec4<f32>,
+};
+struct ImageRegion {
+    // left, top, right, bottom in the atlas
+    uv: vec4<f32>,
+    // left, top, right, bottom in pixels
+    insets: vec4<f32>,
+    size: vec2<f32>,
+};
+// See `PathPrimitive` in path.rs
+struct PathPrimitive {
+    a: vec4<f32>,
+    b: vec4<f32>,
+};
+struct StyleSet {
+    normal: Style,
+    hovered: Style,
+    pressed: Style,
+    disabled: Style,
+};
+// See `SeriesHeader` in chart.rs
+struct ChartSeries {
+    color: vec4<f32>,
+    kind: u32,
+    start: u32,
+    len: u32,
+    _padding: u32,
+};
+// See `CanvasPrimitive` in canvas.rs
+struct CanvasPrimitive {
+    color: vec4<f32>,
+    // Left, top, right, bottom in pixels from the top left of the canvas
+    bounds: vec4<f32>,
+    // Rows of the map from pixels of the canvas to the coordinates of the shape, and the pixels a unit of them spans
+    to_local: array<vec4<f32>, 2>,
+    shape: vec4<f32>,
+    kind: u32,
+    line_width: f32,
+    param: u32,
+    // Hit region + 1, 0 for none
+    region: u32,
 };
 
 
@@ -19,42 +92,1565 @@ var<uniform> gen: General;
 
 // Widgets buffer
 @group(1) @binding(0)
-var<storage,read_write> widget: array<Widget>;
+var<storage,read> widget: array<Widget>;
+// Styles buffer, indexed by widget.ty[3] & STYLE_INDEX_MASK
+@group(1) @binding(1)
+var<storage,read> styles: array<StyleSet>;
+// Images of the atlas, indexed by widget.ty[1] of image widgets
+@group(1) @binding(2)
+var<storage,read> images: array<ImageRegion>;
+// Path headers, indexed by widget.ty[1] of path widgets, then their edges, circles and quads
+@group(1) @binding(3)
+var<storage,read> paths: array<PathPrimitive>;
+// Chart headers indexed by widget.ty[1] of chart widgets, the points of their series, and the primitives of the canvases,
+// each canvas quad instanced over its own. Must match `DataLayout` in state.rs: a header of where the points
+// and the primitives start, then CHART_SIZE vec4s per chart, two points per vec4, and PRIMITIVE_SIZE vec4s per primitive.
+@group(1) @binding(4)
+var<storage,read> packed: array<vec4<f32>>;
 // ID buffer
 @group(2) @binding(0)
 var<storage,read_write> ids: array<u32>;
+// Blurred backdrop, for widgets with a backdrop blur
+@group(3) @binding(0)
+var backdrop: texture_2d<f32>;
+@group(3) @binding(1)
+var linear_sampler: sampler;
+// Images of image widgets
+@group(3) @binding(2)
+var atlas: texture_2d<f32>;
+@group(3) @binding(3)
+var nearest_sampler: sampler;
+
+// Must match the constants in text.rs.
+const TEXT_PADDING: f32 = 4.0;
+const TEXT_CELL_ASPECT: f32 = 0.5;
+const TEXT_FOCUSED: u32 = 0x80000000u;
+// Must match the constants in style.rs.
+const STATE_HOVERED: u32 = 0x1000000u;
+const STATE_PRESSED: u32 = 0x2000000u;
+const STATE_DISABLED: u32 = 0x4000000u;
+const STATE_FOCUSED: u32 = 0x8000000u;
+const STYLE_INDEX_MASK: u32 = 0xffffffu;
+// Must match the constants in state.rs.
+const SHADOW_BIT: u32 = 0x80000000u;
+const SCROLLBAR_BIT: u32 = 0x40000000u;
+const SCRIM_BIT: u32 = 0x20000000u;
+const SERIES_BIT: u32 = 0x10000000u;
+const PRIMITIVE_BIT: u32 = 0x8000000u;
+const IMAGE_NEAREST: u32 = 0x100u;
+const PROGRESS_INDETERMINATE: u32 = 1u;
+// Must match the constant in toggle.rs.
+const CHECK_STATE_MASK: u32 = 0xffu;
+// Must match the constants in slider.rs.
+const SLIDER_VERTICAL: u32 = 1u;
+const SLIDER_SECOND_THUMB: u32 = 2u;
+const SLIDER_TICKS_SHIFT: u32 = 8u;
+// Must match the constant in path.rs.
+const PATH_EVEN_ODD: u32 = 0x80000000u;
+// Must match the constants in table.rs.
+const SORT_ASCENDING: u32 = 1u;
+const SORT_DESCENDING: u32 = 2u;
+// Must match the constants in tree.rs.
+const TREE_INDENT: f32 = 16.0;
+const TREE_EXPANDABLE: u32 = 0x10000u;
+const TREE_EXPANDED: u32 = 0x20000u;
+const TREE_DROP_SHIFT: u32 = 18u;
+// Must match the constants in menu.rs.
+const MENU_SUBMENU: u32 = 1u;
+const MENU_SEPARATOR: u32 = 2u;
+const COMBO_OPEN: u32 = 1u;
+// Must match the constant in tab.rs.
+const TAB_SELECTED: u32 = 1u;
+// Must match the constants in color.rs.
+const COLOR_WHEEL: u32 = 0x10000u;
+const COLOR_VERTICAL: u32 = 0x10000u;
+const COLOR_CHANNEL_SHIFT: u32 = 17u;
+// Must match the constants in chart.rs.
+const MAX_CHART_SERIES: u32 = 8u;
+// vec4s of a chart header, its view and hovered point then two per series
+const CHART_SIZE: u32 = 18u;
+const CHART_INSETS: vec4<f32> = vec4<f32>(48.0, 8.0, 8.0, 20.0);
+const CHART_LINE: u32 = 0u;
+const CHART_BAR: u32 = 1u;
+const CHART_SCATTER: u32 = 2u;
+// Must match the constants in w__vertex.wgsl.
+const CHART_LINE_WIDTH: f32 = 2.0;
+const CHART_MARKER_RADIUS: f32 = 3.5;
+// Rough space between grid lines, in pixels
+const CHART_TICK_SPACING: f32 = 64.0;
+// Must match the constants in canvas.rs.
+const HIT_REGION_SHIFT: u32 = 20u;
+const CANVAS_RECT: u32 = 0u;
+const CANVAS_ELLIPSE: u32 = 1u;
+const CANVAS_LINE: u32 = 2u;
+const CANVAS_GLYPH: u32 = 3u;
+const CANVAS_IMAGE: u32 = 4u;
+const CANVAS_PATH_FILL: u32 = 5u;
+const CANVAS_PATH_STROKE: u32 = 6u;
+// vec4s of a primitive
+const PRIMITIVE_SIZE: u32 = 6u;
+// Digits then '-' and '.', 3x5 pixels each, bit row * 3 + column from the top left, as in `FONT` of canvas.rs
+const GLYPHS: array<u32, 12> = array<u32, 12>(
+    0x7b6fu, 0x749au, 0x73e7u, 0x79e7u, 0x49edu, 0x79cfu, 0x7bcfu, 0x4927u, 0x7befu, 0x79efu, 0x1c0u, 0x2000u,
+);
+const GLYPH_MINUS: u32 = 10u;
+const GLYPH_POINT: u32 = 11u;
+// Glyphs are drawn at twice their size, a pixel apart
+const GLYPH_SCALE: f32 = 2.0;
+// Must match the constant in dock.rs.
+const DIVIDER_VERTICAL: u32 = 1u;
+// Half the length of the grip of dividers, in pixels
+const DIVIDER_GRIP: f32 = 12.0;
+// Must match the constants in scroll.rs.
+const SCROLLBAR_WIDTH: f32 = 8.0;
+const MAX_NESTING: u32 = 8u;
+
+const TAU: f32 = 6.283185307179586;
+// Border of focused checkboxes, radio buttons and toggles, and of focused text inputs.
+const FOCUS_COLOR: vec4<f32> = vec4<f32>(0.3, 0.6, 1.0, 1.0);
+// Dims the widgets below a dialog.
+const SCRIM_COLOR: vec4<f32> = vec4<f32>(0.0, 0.0, 0.0, 0.5);
+
+// The view of the chart `chart`, see `ChartHeader` in chart.rs.
+fn chart_view(chart: u32) -> vec4<f32> {
+    return packed[1u + chart * CHART_SIZE];
+}
+
+// The series + 1 and point under the pointer of the chart `chart` in xy, 0 for none, and its number of series in z.
+fn chart_info(chart: u32) -> vec4<u32> {
+    return bitcast<vec4<u32>>(packed[2u + chart * CHART_SIZE]);
+}
+
+// The series `slot` of the chart `chart`.
+fn chart_series_header(chart: u32, slot: u32) -> ChartSeries {
+    let i = 3u + chart * CHART_SIZE + slot * 2u;
+    let info = bitcast<vec4<u32>>(packed[i + 1u]);
+    return ChartSeries(packed[i], info.x, info.y, info.z, 0u);
+}
+
+// The point `i` of the series of all the charts.
+fn chart_point(i: u32) -> vec2<f32> {
+    let v = packed[bitcast<u32>(packed[0].x) + i / 2u];
+    return select(v.xy, v.zw, i % 2u == 1u);
+}
+
+// The primitive `i` of all the canvases.
+fn canvas_primitive(i: u32) -> CanvasPrimitive {
+    let j = bitcast<u32>(packed[0].y) + i * PRIMITIVE_SIZE;
+    let info = packed[j + 5u];
+    return CanvasPrimitive(
+        packed[j], packed[j + 1u], array<vec4<f32>, 2>(packed[j + 2u], packed[j + 3u]), packed[j + 4u],
+        bitcast<u32>(info.x), info.y, bitcast<u32>(info.z), bitcast<u32>(info.w),
+    );
+}
+
+// Sum of the offsets of the scroll views and popups containing the widget `id`, in NDC.
+fn scroll_offset(id: u32) -> vec2<f32> {
+    var offset = vec2<f32>(0.0);
+    var parent = widget[id].parent;
+    for (var i = 0u; i < MAX_NESTING && parent != 0u; i++) {
+        let ty = widget[parent-1u].ty;
+        offset += vec2<f32>(-bitcast<f32>(ty[1]), bitcast<f32>(ty[2]));
+        parent = widget[parent-1u].parent;
+    }
+    return offset;
+}
+
+// Limits of a widget in NDC, moved by the scroll views containing it.
+fn widget_limits(id: u32) -> vec4<f32> {
+    let offset = scroll_offset(id);
+    return widget[id].limits + offset.xxyy;
+}
+
+// Whether the pixel `p` is inside all the scroll views containing the widget `id`.
+fn unclipped(id: u32, p: vec2<f32>) -> bool {
+    var parent = widget[id].parent;
+    for (var i = 0u; i < MAX_NESTING && parent != 0u; i++) {
+        let r = pixel_limits(parent-1u);
+        if (any(p < r.xy) || any(p >= r.zw)) {
+            return false;
+        }
+        parent = widget[parent-1u].parent;
+    }
+    return true;
+}
+
+// (left, top, right, bottom) limits of a widget in pixels.
+fn pixel_limits(id: u32) -> vec4<f32> {
+    let res = vec2<f32>(gen.resolution);
+    let l = widget_limits(id);
+    return vec4<f32>((l[0]+1.0)*0.5*res.x, (1.0-l[3])*0.5*res.y, (l[1]+1.0)*0.5*res.x, (1.0-l[2])*0.5*res.y);
+}
+
+// Style of a widget in its current state.
+fn widget_style(id: u32) -> Style {
+    let ty = widget[id].ty[3];
+    let index = ty & STYLE_INDEX_MASK;
+    if ((ty & STATE_DISABLED) != 0u) {
+        return styles[index].disabled;
+    }
+    if ((ty & STATE_PRESSED) != 0u) {
+        return styles[index].pressed;
+    }
+    if ((ty & STATE_HOVERED) != 0u) {
+        return styles[index].hovered;
+    }
+    return styles[index].normal;
+}
+
+// Position along the gradient of `style` at `uv`, the widget's local position from (0, 0) top left to (1, 1) bottom right.
+fn gradient_position(style: Style, uv: vec2<f32>) -> f32 {
+    let params = style.gradient_params;
+    switch style.gradient {
+        case 1u: {
+            // Scaled so that the opposite corners are at 0 and 1
+            let dir = vec2<f32>(cos(params.x), sin(params.x));
+            return dot(uv - 0.5, dir) / (abs(dir.x) + abs(dir.y)) + 0.5;
+        }
+        case 2u: {
+            return length(uv - params.xy) / params.z;
+        }
+        default: {
+            let v = uv - params.xy;
+            return fract((atan2(v.y, v.x) - params.z) / TAU);
+        }
+    }
+}
+
+// Fill color of `style` at `uv`.
+fn style_fill(style: Style, uv: vec2<f32>) -> vec4<f32> {
+    if (style.gradient == 0u) {
+        return style.fill;
+    }
+    let t = gradient_position(style, uv);
+    var stops = style.stop_colors;
+    var color = stops[0];
+    for (var i = 1u; i < style.stop_count; i++) {
+        let start = style.stop_offsets[i-1u];
+        let end = style.stop_offsets[i];
+        color = mix(color, stops[i], clamp((t - start) / max(end - start, 1e-6), 0.0, 1.0));
+    }
+    return color;
+}
+
+// Local position of the fragment in the widget, from (0, 0) top left to (1, 1) bottom right.
+fn widget_uv(in: VertexOutput) -> vec2<f32> {
+    let r = pixel_limits(in.id);
+    return (in.position.xy - r.xy) / (r.zw - r.xy);
+}
+
+// `px` is the size of a pixel in NDC, from the screen-space derivatives of the position.
+fn elliptic_button(in: VertexOutput, px: vec2<f32>) -> vec4<f32> {
+    let l = widget_limits(in.id);
+    let center = vec2<f32>(l[0]+l[1], l[2]+l[3]) * 0.5;
+    // Horizontal and vertical radii
+    let radii = abs(vec2<f32>(l[1]-l[0], l[3]-l[2])) * 0.5;
+
+    let p = (in.pos.xy - center) / radii;
+    let point_in = dot(p, p);
+    // First order distance to the ellipse, f/|grad f|, with the gradient taken in pixels
+    let grad = 2.0 * p / radii * px;
+    let d = (point_in - 1.0) / max(length(grad), 1e-6);
+
+    return sdf_paint(d, widget_style(in.id), in);
+}
+
+// Signed distance in pixels from p to a box of half size `half` centered on the origin, y pointing down.
+fn sd_rounded_box(p: vec2<f32>, half: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let side = select(radii.xw, radii.yz, p.x > 0.0); // (top, bottom) of the nearest side
+    let r = min(select(side.x, side.y, p.y > 0.0), min(half.x, half.y));
+    let q = abs(p) - half + r;
+    return min(max(q.x, q.y), 0.0) + length(max(q, vec2<f32>(0.0))) - r;
+}
+
+// Approximate signed distance in pixels from p to an ellipse of radii `radii` centered on the origin.
+fn sd_ellipse(p: vec2<f32>, radii: vec2<f32>) -> f32 {
+    let q = p / radii;
+    return (dot(q, q) - 1.0) / max(length(2.0 * q / radii), 1e-6);
+}
+
+// Fill and border of a shape from its signed distance in pixels, anti-aliased over one pixel.
+fn sdf_paint(d: f32, style: Style, in: VertexOutput) -> vec4<f32> {
+    let coverage = clamp(0.5 - d, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    var fill = widget_fill(style, in);
+    if (style.backdrop_blur > 0.0) {
+        // Seen through the fill, so the widget itself becomes opaque
+        let behind = textureSampleLevel(backdrop, linear_sampler, in.position.xy / vec2<f32>(gen.resolution), 0.0);
+        fill = vec4<f32>(mix(behind.rgb, fill.rgb, fill.a), 1.0);
+    }
+    let inner = clamp(0.5 - (d + style.border_width), 0.0, 1.0);
+    let color = mix(style.border_color, fill, inner);
+    return vec4<f32>(color.rgb, color.a * coverage * style.opacity);
+}
+
+fn rect(in: VertexOutput, radii: vec4<f32>) -> vec4<f32> {
+    let r = pixel_limits(in.id);
+    let center = (r.xy + r.zw) * 0.5;
+    let half = (r.zw - r.xy) * 0.5;
+    let d = sd_rounded_box(in.position.xy - center, half, radii);
+    return sdf_paint(d, widget_style(in.id), in);
+}
+
+// Position in pixels of the image along one axis for nine-slice scaling, from the position `x` in the widget.
+// The insets stay unscaled, unless the widget is too small for them.
+fn nine_slice(x: f32, size: f32, image_size: f32, start: f32, end: f32) -> f32 {
+    let k = min(1.0, size / max(start + end, 1e-6));
+    if (x < start * k) {
+        return x / k;
+    }
+    if (x > size - end * k) {
+        return image_size - (size - x) / k;
+    }
+    return start + (x - start * k) / max(size - (start + end) * k, 1e-6) * (image_size - start - end);
+}
+
+// Fill color of a widget painted with `style`, from its skin if it has one.
+fn widget_fill(style: Style, in: VertexOutput) -> vec4<f32> {
+    if (style.skin == 0u) {
+        return style_fill(style, widget_uv(in));
+    }
+    let img = images[style.skin - 1u];
+    let r = pixel_limits(in.id);
+    let size = r.zw - r.xy;
+    let p = in.position.xy - r.xy;
+    let uv = vec2<f32>(
+        nine_slice(p.x, size.x, img.size.x, style.skin_insets.x, style.skin_insets.z),
+        nine_slice(p.y, size.y, img.size.y, style.skin_insets.y, style.skin_insets.w),
+    ) / img.size;
+    return textureSampleLevel(atlas, linear_sampler, mix(img.uv.xy, img.uv.zw, uv), 0.0);
+}
+
+fn image(in: VertexOutput) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    let img = images[ty[1]];
+    let r = pixel_limits(in.id);
+    let size = r.zw - r.xy;
+    let p = in.position.xy - r.xy;
+
+    // Position in the image, from (0, 0) top left to (1, 1) bottom right
+    var uv: vec2<f32>;
+    switch ty[2] & 0xffu {
+        // Contain, Cover
+        case 1u, 2u: {
+            let scales = size / img.size;
+            let scale = select(min(scales.x, scales.y), max(scales.x, scales.y), (ty[2] & 0xffu) == 2u);
+            let drawn = img.size * scale;
+            uv = (p - (size - drawn) * 0.5) / drawn;
+            if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0))) {
+                discard;
+            }
+        }
+        // Nine-slice
+        case 3u: {
+            uv = vec2<f32>(
+                nine_slice(p.x, size.x, img.size.x, img.insets.x, img.insets.z),
+                nine_slice(p.y, size.y, img.size.y, img.insets.y, img.insets.w),
+            ) / img.size;
+        }
+        // Fill
+        default: {
+            uv = p / size;
+        }
+    }
+    var color: vec4<f32>;
+    if ((ty[2] & IMAGE_NEAREST) != 0u) {
+        // Out to the edges of the image, so that its edge texels are as wide as the others
+        let half = 0.5 / vec2<f32>(textureDimensions(atlas));
+        color = textureSampleLevel(atlas, nearest_sampler, mix(img.uv.xy - half, img.uv.zw + half, uv), 0.0);
+    } else {
+        color = textureSampleLevel(atlas, linear_sampler, mix(img.uv.xy, img.uv.zw, uv), 0.0);
+    }
+
+    // Clip to the rounded corners of the style
+    let style = widget_style(in.id);
+    let d = sd_rounded_box(p - size * 0.5, size * 0.5, style.radii);
+    let coverage = clamp(0.5 - d, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * coverage * style.opacity);
+}
+
+// Distance from p to the segment [a, b].
+fn segment_distance(p: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let e = b - a;
+    let t = clamp(dot(p - a, e) / max(dot(e, e), 1e-12), 0.0, 1.0);
+    return length(p - a - e * t);
+}
+
+// Signed distance from p to the convex quadrilateral v0 v1 v2 v3, in either winding.
+fn sd_quad(p: vec2<f32>, v0: vec2<f32>, v1: vec2<f32>, v2: vec2<f32>, v3: vec2<f32>) -> f32 {
+    var v = array<vec2<f32>, 4>(v0, v1, v2, v3);
+    var d = dot(p - v0, p - v0);
+    var s = 1.0;
+    for (var i = 0u; i < 4u; i++) {
+        let j = (i + 3u) % 4u;
+        let e = v[j] - v[i];
+        let w = p - v[i];
+        let b = w - e * clamp(dot(w, e) / max(dot(e, e), 1e-12), 0.0, 1.0);
+        d = min(d, dot(b, b));
+        // Crossing number
+        let c = vec3<bool>(p.y >= v[i].y, p.y < v[j].y, e.x * w.y > e.y * w.x);
+        if (all(c) || all(!c)) {
+            s = -s;
+        }
+    }
+    return s * sqrt(d);
+}
+
+// Coverage of the fill and stroke of the path `path` at `position`, its viewbox fitted to `r` (left, top, right, bottom),
+// both in units spanning `pixel` pixels. The fill is found from the winding number and the distance to the nearest edge,
+// and the stroke from the distance to the union of its circles and quads, in path units.
+fn path_coverage(path: u32, position: vec2<f32>, r: vec4<f32>, pixel: f32) -> vec2<f32> {
+    let header = paths[path];
+    let viewbox = header.a;
+    let counts = bitcast<vec4<u32>>(header.b);
+    let start = counts.x;
+    let edge_count = counts.y & ~PATH_EVEN_ODD;
+
+    // Fit the viewbox, centered and keeping its aspect ratio
+    let size = r.zw - r.xy;
+    let fit = min(size.x / viewbox.z, size.y / viewbox.w);
+    let origin = r.xy + (size - viewbox.zw * fit) * 0.5;
+    let p = (position - origin) / fit + viewbox.xy;
+    let scale = fit * pixel;
+
+    var fill_coverage = 0.0;
+    if (edge_count > 0u) {
+        var winding = 0;
+        var d = 1e30;
+        for (var i = 0u; i < edge_count; i++) {
+            let e = paths[start + i].a;
+            let a = e.xy;
+            let b = e.zw;
+            d = min(d, segment_distance(p, a, b));
+            let side = (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y);
+            if (a.y <= p.y) {
+                if (b.y > p.y && side > 0.0) {
+                    winding++;
+                }
+            } else if (b.y <= p.y && side < 0.0) {
+                winding--;
+            }
+        }
+        let inside = select(winding != 0, (winding & 1) != 0, (counts.y & PATH_EVEN_ODD) != 0u);
+        fill_coverage = clamp(0.5 - select(d, -d, inside) * scale, 0.0, 1.0);
+    }
+
+    var stroke_coverage = 0.0;
+    if (counts.z + counts.w > 0u) {
+        var d = 1e30;
+        let circles = start + edge_count;
+        for (var i = 0u; i < counts.z; i++) {
+            let c = paths[circles + i].a;
+            d = min(d, length(p - c.xy) - c.z);
+        }
+        let quads = circles + counts.z;
+        for (var i = 0u; i < counts.w; i++) {
+            let q = paths[quads + i];
+            d = min(d, sd_quad(p, q.a.xy, q.a.zw, q.b.xy, q.b.zw));
+        }
+        stroke_coverage = clamp(0.5 - d * scale, 0.0, 1.0);
+    }
+    return vec2<f32>(fill_coverage, stroke_coverage);
+}
+
+// Vector path widget.
+fn path(in: VertexOutput) -> vec4<f32> {
+    let coverage = path_coverage(widget[in.id].ty[1], in.position.xy, pixel_limits(in.id), 1.0);
+    let fill_coverage = coverage.x;
+    let stroke_coverage = coverage.y;
+
+    // Stroke over fill, premultiplied
+    let style = widget_style(in.id);
+    let fill = widget_fill(style, in);
+    let stroke = style.border_color;
+    let fill_alpha = fill.a * fill_coverage;
+    let stroke_alpha = stroke.a * stroke_coverage;
+    let alpha = stroke_alpha + fill_alpha * (1.0 - stroke_alpha);
+    if (alpha <= 0.0) {
+        discard;
+    }
+    let rgb = (stroke.rgb * stroke_alpha + fill.rgb * fill_alpha * (1.0 - stroke_alpha)) / alpha;
+    return vec4<f32>(rgb, alpha * style.opacity);
+}
+
+// Style of a toggle widget with the solid fill `fill`, and a focus ring as border when focused.
+fn toggle_style(id: u32, fill: vec4<f32>) -> Style {
+    var style = widget_style(id);
+    style.fill = fill;
+    style.gradient = 0u;
+    style.skin = 0u;
+    if ((widget[id].ty[3] & STATE_FOCUSED) != 0u) {
+        style.border_color = FOCUS_COLOR;
+        style.border_width = max(style.border_width, 2.0);
+    }
+    return style;
+}
+
+// Blend the mark `color` over `base` with `coverage`.
+fn blend_mark(base: vec4<f32>, color: vec4<f32>, coverage: f32) -> vec4<f32> {
+    let a = color.a * coverage;
+    return vec4<f32>(mix(base.rgb, color.rgb, a), base.a);
+}
+
+// Box filled with the border color as it gets checked, then a check mark or a dash drawn with the fill color.
+fn checkbox(in: VertexOutput) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    let t = bitcast<f32>(ty[2]);
+    let style = widget_style(in.id);
+    let fill = widget_fill(style, in);
+    let r = pixel_limits(in.id);
+    let center = (r.xy + r.zw) * 0.5;
+    let half = (r.zw - r.xy) * 0.5;
+    let p = in.position.xy - center;
+    let color = sdf_paint(sd_rounded_box(p, half, style.radii), toggle_style(in.id, mix(fill, style.border_color, t)), in);
+
+    // The mark is drawn in a square of side 2, and grows with the transition
+    let size = min(half.x, half.y);
+    let q = p / size;
+    var d: f32;
+    if ((ty[1] & CHECK_STATE_MASK) == 2u) {
+        d = segment_distance(q, vec2<f32>(-0.5 * t, 0.0), vec2<f32>(0.5 * t, 0.0));
+    } else {
+        let a = vec2<f32>(-0.5, 0.0);
+        let b = vec2<f32>(-0.15, 0.35);
+        let c = vec2<f32>(0.55, -0.35);
+        let drawn = t * (length(b - a) + length(c - b));
+        d = segment_distance(q, a, a + (b - a) * clamp(drawn / length(b - a), 0.0, 1.0));
+        if (drawn > length(b - a)) {
+            d = min(d, segment_distance(q, b, b + (c - b) * clamp((drawn - length(b - a)) / length(c - b), 0.0, 1.0)));
+        }
+    }
+    let coverage = select(0.0, clamp(0.5 - (d - 0.12) * size, 0.0, 1.0), t > 0.0);
+    return blend_mark(color, fill, coverage);
+}
+
+// Circle, with a dot of the border color growing as it gets checked.
+fn radio_button(in: VertexOutput) -> vec4<f32> {
+    let t = bitcast<f32>(widget[in.id].ty[2]);
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+    let half = (r.zw - r.xy) * 0.5;
+    let p = in.position.xy - (r.xy + r.zw) * 0.5;
+    let color = sdf_paint(sd_ellipse(p, half), toggle_style(in.id, widget_fill(style, in)), in);
+
+    let d = length(p) - min(half.x, half.y) * 0.5 * t;
+    let coverage = select(0.0, clamp(0.5 - d, 0.0, 1.0), t > 0.0);
+    return blend_mark(color, style.border_color, coverage);
+}
+
+// Pill shaped track getting the border color when on, with a knob sliding from left to right.
+fn toggle(in: VertexOutput) -> vec4<f32> {
+    let t = bitcast<f32>(widget[in.id].ty[2]);
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+    let half = (r.zw - r.xy) * 0.5;
+    let p = in.position.xy - (r.xy + r.zw) * 0.5;
+    let radius = min(half.x, half.y);
+    let color = sdf_paint(sd_rounded_box(p, half, vec4<f32>(radius)), toggle_style(in.id, mix(widget_fill(style, in), style.border_color, t)), in);
+
+    let knob_radius = max(radius - 3.0, 1.0);
+    let knob_x = mix(-half.x + radius, half.x - radius, t);
+    let d = length(p - vec2<f32>(knob_x, 0.0)) - knob_radius;
+    return blend_mark(color, vec4<f32>(1.0, 1.0, 1.0, 1.0), clamp(0.5 - d, 0.0, 1.0));
+}
+
+// `color` with `coverage` over `base`, neither being premultiplied.
+fn over(base: vec4<f32>, color: vec4<f32>, coverage: f32) -> vec4<f32> {
+    let a = color.a * coverage;
+    let alpha = a + base.a * (1.0 - a);
+    if (alpha <= 0.0) {
+        return vec4<f32>(0.0);
+    }
+    return vec4<f32>((color.rgb * a + base.rgb * base.a * (1.0 - a)) / alpha, alpha);
+}
+
+// Track filled with the border color up to the thumb, or between the two thumbs of range sliders,
+// over optional tick marks. The thumbs have a focus ring when they are moved by the keyboard.
+fn slider(in: VertexOutput, range: bool) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+
+    // Position along the track from its minimum, and across it from its middle, in pixels
+    var size = r.zw - r.xy;
+    var p = in.position.xy - r.xy;
+    if ((ty[2] & SLIDER_VERTICAL) != 0u) {
+        p = vec2<f32>(size.y - p.y, p.x - size.x * 0.5);
+        size = size.yx;
+    } else {
+        p.y -= size.y * 0.5;
+    }
+    // Thumbs stop half the thickness from the ends
+    let inset = size.y * 0.5;
+    let track_length = max(size.x - 2.0 * inset, 0.0);
+    let start = inset + f32(ty[1] & 0xffffu) / 65535.0 * track_length;
+    let end = inset + f32(ty[1] >> 16u) / 65535.0 * track_length;
+
+    var color = vec4<f32>(0.0);
+    let ticks = (ty[2] >> SLIDER_TICKS_SHIFT) & 0xffffu;
+    if (ticks >= 2u) {
+        let spacing = track_length / f32(ticks - 1u);
+        let k = clamp(round((p.x - inset) / max(spacing, 1e-6)), 0.0, f32(ticks - 1u));
+        let d = max(abs(p.x - inset - k * spacing) - 0.5, abs(p.y) - size.y * 0.4);
+        color = over(color, vec4<f32>(style.border_color.rgb, style.border_color.a * 0.5), clamp(0.5 - d, 0.0, 1.0));
+    }
+
+    let track = segment_distance(p, vec2<f32>(inset, 0.0), vec2<f32>(inset + track_length, 0.0)) - max(size.y * 0.15, 1.5);
+    let filled = p.x >= start && p.x <= end;
+    color = over(color, select(widget_fill(style, in), style.border_color, filled), clamp(0.5 - track, 0.0, 1.0));
+
+    let focused = (ty[3] & STATE_FOCUSED) != 0u;
+    let radius = max(inset - 1.0, 1.0);
+    for (var thumb = select(1u, 0u, range); thumb < 2u; thumb++) {
+        let x = select(start, end, thumb == 1u);
+        let d = length(p - vec2<f32>(x, 0.0)) - radius;
+        let moved_by_keys = focused && (!range || (thumb == 1u) == ((ty[2] & SLIDER_SECOND_THUMB) != 0u));
+        color = over(color, select(style.border_color, FOCUS_COLOR, moved_by_keys), clamp(0.5 - d, 0.0, 1.0));
+        color = over(color, vec4<f32>(1.0, 1.0, 1.0, 1.0), clamp(0.5 - (d + 1.5), 0.0, 1.0));
+    }
+
+    if (color.a <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * style.opacity);
+}
+
+// Track filled up to the progress, or with a segment sliding along it when the progress is unknown.
+fn progress_bar(in: VertexOutput) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+    let half = (r.zw - r.xy) * 0.5;
+    let p = in.position.xy - (r.xy + r.zw) * 0.5;
+    let d = sd_rounded_box(p, half, style.radii);
+    let color = sdf_paint(d, style, in);
+
+    // Filled part, as fractions of the width
+    var start = 0.0;
+    var end = bitcast<f32>(ty[1]);
+    if ((ty[2] & PROGRESS_INDETERMINATE) != 0u) {
+        let center = mix(-0.2, 1.2, fract(gen.time / 1.5));
+        start = center - 0.15;
+        end = center + 0.15;
+    }
+    let x = p.x / (2.0 * half.x) + 0.5;
+    let bar = max(d + style.border_width, max(start - x, x - end) * 2.0 * half.x);
+    return blend_mark(color, style.border_color, clamp(0.5 - bar, 0.0, 1.0));
+}
+
+// Ring with an arc spinning around it, growing and shrinking.
+fn spinner(in: VertexOutput) -> vec4<f32> {
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+    let half = (r.zw - r.xy) * 0.5;
+    let p = in.position.xy - (r.xy + r.zw) * 0.5;
+    let thickness = max(min(half.x, half.y) * 0.2, 2.0);
+    let radius = min(half.x, half.y) - thickness * 0.5 - 1.0;
+    let ring = abs(length(p) - radius) - thickness * 0.5;
+
+    // The arc goes back from its head by `sweep` radians, with round ends
+    let head = gen.time * TAU * 0.8;
+    let sweep = mix(0.1, 0.75, 0.5 + 0.5 * sin(gen.time * 2.5)) * TAU;
+    let behind = fract((head - atan2(p.y, p.x)) / TAU) * TAU;
+    var arc = ring;
+    if (behind > sweep) {
+        let tail = head - sweep;
+        let ends = min(length(p - radius * vec2<f32>(cos(head), sin(head))), length(p - radius * vec2<f32>(cos(tail), sin(tail))));
+        arc = ends - thickness * 0.5;
+    }
+
+    var color = over(vec4<f32>(0.0), widget_fill(style, in), clamp(0.5 - ring, 0.0, 1.0));
+    color = over(color, style.border_color, clamp(0.5 - arc, 0.0, 1.0));
+    if (color.a <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * style.opacity);
+}
+
+// Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7
+fn erf(x: f32) -> f32 {
+    let t = 1.0 / (1.0 + 0.3275911 * abs(x));
+    let y = 1.0 - (((((1.061405429 * t - 1.453152027) * t + 1.421413741) * t - 0.284496736) * t + 0.254829592) * t) * exp(-x * x);
+    return sign(x) * y;
+}
+
+// Gaussian shadow of the widget `id`, from the distance to its shape grown by the spread.
+fn shadow(in: VertexOutput, id: u32) -> vec4<f32> {
+    let style = widget_style(id);
+    let offset = style.shadow.xy;
+    let blur = style.shadow.z;
+    let spread = style.shadow.w;
+    let r = pixel_limits(id);
+    let p = in.position.xy - (r.xy + r.zw) * 0.5 - offset;
+    let half = max((r.zw - r.xy) * 0.5 + spread, vec2<f32>(0.0));
+
+    var d: f32;
+    switch widget[id].ty[0] {
+        case 0u, 7u: {
+            d = sd_ellipse(p, half);
+        }
+        case 3u: {
+            // Like CSS, the spread only grows rounded corners
+            d = sd_rounded_box(p, half, select(vec4<f32>(0.0), max(style.radii + spread, vec4<f32>(0.0)), style.radii > vec4<f32>(0.0)));
+        }
+        default: {
+            d = sd_rounded_box(p, half, vec4<f32>(0.0));
+        }
+    }
+    // The blur radius is twice the standard deviation, as in CSS
+    let sigma = max(blur * 0.5, 0.5);
+    let alpha = 0.5 - 0.5 * erf(d / (sigma * sqrt(2.0)));
+    return vec4<f32>(style.shadow_color.rgb, style.shadow_color.a * alpha * style.opacity);
+}
+
+// Scrollbars of the scroll view `id` along its right and bottom edges, where it can scroll.
+// Tracks are tinted with the border color, with a rounded thumb showing the visible part of the content.
+// Transparent elsewhere.
+fn scrollbars(in: VertexOutput, id: u32) -> vec4<f32> {
+    let r = pixel_limits(id);
+    let w = widget[id];
+    let offset = vec2<f32>(bitcast<f32>(w.ty[1]), bitcast<f32>(w.ty[2]) + w.scroll_base);
+    let view = vec2<f32>(w.limits[1]-w.limits[0], w.limits[3]-w.limits[2]);
+    let bars = w.scroll_max > vec2<f32>(0.0);
+    // With both scrollbars, the vertical one stops above the horizontal one, as in ScrollModel::thumb
+    let track = r.zw - r.xy - select(vec2<f32>(0.0), vec2<f32>(SCROLLBAR_WIDTH), bars.yx);
+    let length = min(max(track * view / (view + w.scroll_max), vec2<f32>(SCROLLBAR_WIDTH * 2.0)), track);
+    let start = (track - length) * offset / max(w.scroll_max, vec2<f32>(1e-6));
+    let p = in.position.xy - r.xy;
+
+    // Position along the track, and across it from its center
+    var along: f32;
+    var across: f32;
+    var axis: u32;
+    if (bars.y && p.x >= r.z - r.x - SCROLLBAR_WIDTH && p.y < track.y) {
+        axis = 1u;
+        along = p.y;
+        across = p.x - (r.z - r.x - SCROLLBAR_WIDTH * 0.5);
+    } else if (bars.x && p.y >= r.w - r.y - SCROLLBAR_WIDTH && p.x < track.x) {
+        axis = 0u;
+        along = p.x;
+        across = p.y - (r.w - r.y - SCROLLBAR_WIDTH * 0.5);
+    } else {
+        return vec4<f32>(0.0);
+    }
+
+    let style = widget_style(id);
+    let color = style.border_color;
+    let base = vec4<f32>(color.rgb, color.a * 0.15);
+    let half = vec2<f32>(length[axis] * 0.5, SCROLLBAR_WIDTH * 0.5) - 1.0;
+    let d = sd_rounded_box(vec2<f32>(along - start[axis] - length[axis] * 0.5, across), half, vec4<f32>(half.y));
+    let thumb = vec4<f32>(color.rgb, color.a * 0.6);
+    let painted = over(base, thumb, clamp(0.5 - d, 0.0, 1.0));
+    return vec4<f32>(painted.rgb, painted.a * style.opacity);
+}
+
+// Rounded rectangle with a triangle of the border color at its right end, pointing up when sorted ascending
+// and down when sorted descending.
+fn table_header(in: VertexOutput) -> vec4<f32> {
+    let color = rect(in, widget_style(in.id).radii);
+    let sort = widget[in.id].ty[1];
+    if (sort != SORT_ASCENDING && sort != SORT_DESCENDING) {
+        return color;
+    }
+    let r = pixel_limits(in.id);
+    let size = (r.w - r.y) * 0.2;
+    var q = in.position.xy - vec2<f32>(r.z - (r.w - r.y) * 0.5, (r.y + r.w) * 0.5);
+    if (sort == SORT_DESCENDING) {
+        q.y = -q.y;
+    }
+    // Apex at the top, base at the bottom, as wide as it is tall
+    let d = max(q.y - size, abs(q.x) - (q.y + size) * 0.5);
+    let style = widget_style(in.id);
+    let mark = vec4<f32>(style.border_color.rgb, style.border_color.a * style.opacity);
+    return over(color, mark, clamp(0.5 - d, 0.0, 1.0));
+}
+
+// Rectangle with the radii of its style, with faint indentation guides and an arrow of its border color,
+// pointing right when collapsed and down when expanded. While dragging nodes over it,
+// a line above or below it, or an outline, shows where they would be dropped.
+fn tree_row(in: VertexOutput) -> vec4<f32> {
+    let style = widget_style(in.id);
+    var color = rect(in, style.radii);
+    let r = pixel_limits(in.id);
+    let p = in.position.xy - r.xy;
+    let size = r.zw - r.xy;
+    let ty = widget[in.id].ty[1];
+    let depth = f32(ty & 0xffffu);
+    let mark = vec4<f32>(style.border_color.rgb, style.border_color.a * style.opacity);
+
+    // 1px guides in the middle of the indentation of each ancestor
+    let level = floor(p.x / TREE_INDENT);
+    if (level < depth) {
+        let guide = vec4<f32>(mark.rgb, mark.a * 0.4);
+        color = over(color, guide, clamp(1.0 - abs(p.x - (level + 0.5) * TREE_INDENT), 0.0, 1.0));
+    }
+
+    if ((ty & TREE_EXPANDABLE) != 0u) {
+        var q = p - vec2<f32>((depth + 0.5) * TREE_INDENT, size.y * 0.5);
+        if ((ty & TREE_EXPANDED) != 0u) {
+            q = q.yx;
+        }
+        // Apex on the right, as tall as it is wide
+        let s = min(TREE_INDENT, size.y) * 0.25;
+        let d = max(-q.x - s, abs(q.y) - (s - q.x) * 0.5);
+        color = over(color, mark, clamp(0.5 - d, 0.0, 1.0));
+    }
+
+    switch (ty >> TREE_DROP_SHIFT) & 3u {
+        case 1u: {
+            color = over(color, FOCUS_COLOR, select(0.0, 1.0, p.y < 2.0));
+        }
+        case 2u: {
+            color = over(color, FOCUS_COLOR, select(0.0, 1.0, p.y >= size.y - 2.0));
+        }
+        case 3u: {
+            let inside = all(p >= vec2<f32>(2.0)) && all(p < size - 2.0);
+            color = over(color, FOCUS_COLOR, select(1.0, 0.0, inside));
+        }
+        default: {}
+    }
+    return color;
+}
+
+// Rectangle with the top radii of its style, joining the page below it, underlined with its border color when selected.
+fn tab(in: VertexOutput) -> vec4<f32> {
+    let style = widget_style(in.id);
+    let color = rect(in, vec4<f32>(style.radii.xy, 0.0, 0.0));
+    if ((widget[in.id].ty[1] & TAB_SELECTED) == 0u) {
+        return color;
+    }
+    let r = pixel_limits(in.id);
+    let mark = vec4<f32>(style.border_color.rgb, style.border_color.a * style.opacity);
+    return over(color, mark, select(0.0, 1.0, in.position.y >= r.w - 2.0));
+}
+
+// Rectangle with the radii of its style, with a grip of its border color across its middle,
+// upright for dividers between panels side by side.
+fn divider(in: VertexOutput) -> vec4<f32> {
+    let style = widget_style(in.id);
+    let color = rect(in, style.radii);
+    let r = pixel_limits(in.id);
+    var d = abs(in.position.xy - (r.xy + r.zw) * 0.5);
+    if ((widget[in.id].ty[1] & DIVIDER_VERTICAL) != 0u) {
+        d = d.yx;
+    }
+    let mark = vec4<f32>(style.border_color.rgb, style.border_color.a * style.opacity);
+    return over(color, mark, select(0.0, 1.0, d.x <= DIVIDER_GRIP && d.y <= 1.0));
+}
+
+// Rectangle with the radii of its style, in its hovered style while highlighted by the keyboard,
+// with an arrow of its border color on the right of items opening a submenu.
+// Separators are only a horizontal line of the border color.
+fn menu_item(in: VertexOutput) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    var style = widget_style(in.id);
+    if ((ty[3] & (STATE_FOCUSED | STATE_PRESSED | STATE_DISABLED)) == STATE_FOCUSED) {
+        style = styles[ty[3] & STYLE_INDEX_MASK].hovered;
+    }
+    let r = pixel_limits(in.id);
+    let p = in.position.xy;
+    let center = (r.xy + r.zw) * 0.5;
+    let mark = vec4<f32>(style.border_color.rgb, style.border_color.a * style.opacity);
+    if ((ty[1] & MENU_SEPARATOR) != 0u) {
+        // Transparent around the line, still picking the separator rather than what is behind the menu
+        return vec4<f32>(mark.rgb, mark.a * clamp(1.0 - abs(p.y - center.y), 0.0, 1.0));
+    }
+    var color = sdf_paint(sd_rounded_box(p - center, (r.zw - r.xy) * 0.5, style.radii), style, in);
+    if ((ty[1] & MENU_SUBMENU) != 0u) {
+        // Apex on the right, as tall as it is wide, like collapsed tree rows
+        let h = r.w - r.y;
+        let q = p - vec2<f32>(r.z - h * 0.5, center.y);
+        let s = h * 0.15;
+        let d = max(-q.x - s, abs(q.y) - (s - q.x) * 0.5);
+        color = over(color, mark, clamp(0.5 - d, 0.0, 1.0));
+    }
+    return color;
+}
+
+// Rectangle with the radii of its style, with an arrow of its border color on the right,
+// pointing down, or up while its options are shown.
+fn combo_box(in: VertexOutput) -> vec4<f32> {
+    let style = widget_style(in.id);
+    let color = rect(in, style.radii);
+    let r = pixel_limits(in.id);
+    let h = r.w - r.y;
+    var q = in.position.xy - vec2<f32>(r.z - h * 0.5, (r.y + r.w) * 0.5);
+    if ((widget[in.id].ty[2] & COMBO_OPEN) == 0u) {
+        q.y = -q.y;
+    }
+    // Apex at the top, base at the bottom, as wide as it is tall
+    let size = h * 0.15;
+    let d = max(q.y - size, abs(q.x) - (q.y + size) * 0.5);
+    let mark = vec4<f32>(style.border_color.rgb, style.border_color.a * style.opacity);
+    return over(color, mark, clamp(0.5 - d, 0.0, 1.0));
+}
+
+// Rectangle with the radii of its style, with a focus ring when focused.
+fn button(in: VertexOutput) -> vec4<f32> {
+    var style = widget_style(in.id);
+    if ((widget[in.id].ty[3] & STATE_FOCUSED) != 0u) {
+        style.border_color = FOCUS_COLOR;
+        style.border_width = max(style.border_width, 2.0);
+    }
+    let r = pixel_limits(in.id);
+    return sdf_paint(sd_rounded_box(in.position.xy - (r.xy + r.zw) * 0.5, (r.zw - r.xy) * 0.5, style.radii), style, in);
+}
+
+// RGB of a color from its hue, saturation and value, all from 0 to 1.
+fn hsv_to_rgb(hsv: vec3<f32>) -> vec3<f32> {
+    let k = abs(fract(hsv.x + vec3<f32>(1.0, 2.0/3.0, 1.0/3.0)) * 6.0 - 3.0) - 1.0;
+    return hsv.z * mix(vec3<f32>(1.0), clamp(k, vec3<f32>(0.0), vec3<f32>(1.0)), hsv.y);
+}
+
+// Light and dark squares seen through transparent colors, from `p` in pixels.
+fn checkerboard(p: vec2<f32>) -> vec4<f32> {
+    let cell = vec2<i32>(floor(p / 6.0));
+    let light = ((cell.x + cell.y) & 1) == 0;
+    return select(vec4<f32>(0.6, 0.6, 0.6, 1.0), vec4<f32>(0.9, 0.9, 0.9, 1.0), light);
+}
+
+// White ring with a dark outline around `center`, of the focus color when the widget `id` is focused.
+fn color_marker(base: vec4<f32>, p: vec2<f32>, center: vec2<f32>, id: u32) -> vec4<f32> {
+    let d = abs(length(p - center) - 5.0);
+    let focused = (widget[id].ty[3] & STATE_FOCUSED) != 0u;
+    let color = over(base, vec4<f32>(0.0, 0.0, 0.0, 0.6), clamp(0.5 - (d - 2.0), 0.0, 1.0));
+    return over(color, select(vec4<f32>(1.0), FOCUS_COLOR, focused), clamp(0.5 - (d - 1.0), 0.0, 1.0));
+}
+
+// Square of saturations from left to right and values from bottom to top for the hue,
+// or wheel of hues around and saturations outward for the value, with a ring at the color.
+fn color_area(in: VertexOutput) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+    let size = r.zw - r.xy;
+    let p = in.position.xy - (r.xy + r.zw) * 0.5;
+    let hue = f32(ty[1] & 0xffffu) / 65535.0;
+    let s = f32(ty[2] & 0xffffu) / 65535.0;
+    let v = f32(ty[2] >> 16u) / 65535.0;
+
+    var d: f32;
+    var rgb: vec3<f32>;
+    var marker: vec2<f32>;
+    if ((ty[1] & COLOR_WHEEL) != 0u) {
+        // Counterclockwise from the right, y pointing down
+        let radius = min(size.x, size.y) * 0.5;
+        d = length(p) - radius;
+        rgb = hsv_to_rgb(vec3<f32>(fract(atan2(-p.y, p.x) / TAU), min(length(p) / max(radius, 1.0), 1.0), v));
+        marker = vec2<f32>(cos(hue * TAU), -sin(hue * TAU)) * s * radius;
+    } else {
+        d = sd_rounded_box(p, size * 0.5, style.radii);
+        let uv = clamp(p / max(size, vec2<f32>(1.0)) + 0.5, vec2<f32>(0.0), vec2<f32>(1.0));
+        rgb = hsv_to_rgb(vec3<f32>(hue, uv.x, 1.0 - uv.y));
+        marker = (vec2<f32>(s, 1.0 - v) - 0.5) * size;
+    }
+    let color = color_marker(vec4<f32>(rgb, clamp(0.5 - d, 0.0, 1.0)), p, marker, in.id);
+    if (color.a <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * style.opacity);
+}
+
+// Track of the hues, of the values up to the color at full value, or of the alphas of the color over a checkerboard,
+// with a thumb at the color's.
+fn color_slider(in: VertexOutput) -> vec4<f32> {
+    let ty = widget[in.id].ty;
+    let style = widget_style(in.id);
+    let r = pixel_limits(in.id);
+
+    // Position along the track from its minimum, and across it from its middle, in pixels
+    var size = r.zw - r.xy;
+    var p = in.position.xy - r.xy;
+    if ((ty[2] & COLOR_VERTICAL) != 0u) {
+        p = vec2<f32>(size.y - p.y, p.x - size.x * 0.5);
+        size = size.yx;
+    } else {
+        p.y -= size.y * 0.5;
+    }
+    let t = clamp(p.x / max(size.x, 1.0), 0.0, 1.0);
+    let end = unpack4x8unorm(ty[1]);
+    var fill: vec4<f32>;
+    switch (ty[2] >> COLOR_CHANNEL_SHIFT) {
+        case 0u: {
+            fill = vec4<f32>(hsv_to_rgb(vec3<f32>(t, 1.0, 1.0)), 1.0);
+        }
+        case 1u: {
+            fill = vec4<f32>(end.rgb * t, 1.0);
+        }
+        default: {
+            fill = over(checkerboard(in.position.xy - r.xy), vec4<f32>(end.rgb, t), 1.0);
+        }
+    }
+    let d = sd_rounded_box(in.position.xy - (r.xy + r.zw) * 0.5, (r.zw - r.xy) * 0.5, style.radii);
+    var color = over(vec4<f32>(0.0), fill, clamp(0.5 - d, 0.0, 1.0));
+
+    // A bar across the track, outlined like the marker of color areas
+    let x = f32(ty[2] & 0xffffu) / 65535.0 * size.x;
+    let thumb = abs(sd_rounded_box(p - vec2<f32>(x, 0.0), vec2<f32>(3.0, size.y * 0.5), vec4<f32>(2.0)));
+    let focused = (ty[3] & STATE_FOCUSED) != 0u;
+    color = over(color, vec4<f32>(0.0, 0.0, 0.0, 0.6), clamp(0.5 - (thumb - 2.0), 0.0, 1.0));
+    color = over(color, select(vec4<f32>(1.0), FOCUS_COLOR, focused), clamp(0.5 - (thumb - 1.0), 0.0, 1.0));
+    if (color.a <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * style.opacity);
+}
+
+// Rectangle with the radii and border of its style, filled with its color over a checkerboard.
+fn swatch(in: VertexOutput) -> vec4<f32> {
+    let r = pixel_limits(in.id);
+    var style = widget_style(in.id);
+    style.fill = over(checkerboard(in.position.xy - r.xy), unpack4x8unorm(widget[in.id].ty[1]), 1.0);
+    style.gradient = 0u;
+    style.skin = 0u;
+    style.backdrop_blur = 0.0;
+    return sdf_paint(sd_rounded_box(in.position.xy - (r.xy + r.zw) * 0.5, (r.zw - r.xy) * 0.5, style.radii), style, in);
+}
+
+fn log_10(x: f32) -> f32 {
+    return log2(x) / log2(10.0);
+}
+
+fn pow10u(n: u32) -> u32 {
+    var p = 1u;
+    for (var i = 0u; i < n; i++) {
+        p *= 10u;
+    }
+    return p;
+}
+
+// Distance between grid lines over `range` of data shown in `length` pixels: 1, 2 or 5 times a power of ten.
+fn tick_step(range: f32, length: f32) -> f32 {
+    let raw = range * CHART_TICK_SPACING / max(length, 1.0);
+    let base = pow(10.0, floor(log_10(raw)));
+    let m = raw / base;
+    if (m <= 1.0) {
+        return base;
+    }
+    if (m <= 2.0) {
+        return base * 2.0;
+    }
+    if (m <= 5.0) {
+        return base * 5.0;
+    }
+    return base * 10.0;
+}
+
+// Digits after the point needed to tell apart values `step` apart.
+fn step_decimals(step: f32) -> u32 {
+    return u32(clamp(-floor(log_10(step) + 1e-3), 0.0, 6.0));
+}
+
+// A number split into its digits: whether it is negative, its integer part, its fractional part and its count of integer digits.
+struct Digits {
+    negative: bool,
+    integer: u32,
+    fraction: u32,
+    integer_digits: u32,
+};
 
-fn elliptic_button(in: VertexOutput) -> vec4<f32> {
-    var x = in.pos.x;
-    var y = in.pos.y;
-    var h = (widget[in.id].limits[0]+widget[in.id].limits[1]) * 0.5;
-    var k = (widget[in.id].limits[2]+widget[in.id].limits[3]) * 0.5;
-    var rad1 = abs(widget[in.id].limits[1]-widget[in.id].limits[0])/2.0;
-    var rad2 = abs(widget[in.id].limits[3]-widget[in.id].limits[2])/2.0;
-    var a = max(rad1, rad2);
-    var b = min(rad1, rad2);
-    
-    var point_in = (x-h)*(x-h)/(a*a) + (y-k)*(y-k)/(b*b);
-    if (point_in > 1.0) {
+fn digits(value: f32, decimals: u32) -> Digits {
+    let scale = pow10u(decimals);
+    let n = u32(min(round(abs(value) * f32(scale)), 4.0e9));
+    var d: Digits;
+    d.negative = value < 0.0 && n > 0u;
+    d.integer = n / scale;
+    d.fraction = n % scale;
+    d.integer_digits = 1u;
+    for (var i = d.integer; i >= 10u; i /= 10u) {
+        d.integer_digits++;
+    }
+    return d;
+}
+
+// Glyphs of `value` with `decimals` digits after the point.
+fn number_length(value: f32, decimals: u32) -> u32 {
+    let d = digits(value, decimals);
+    return u32(d.negative) + d.integer_digits + select(0u, decimals + 1u, decimals > 0u);
+}
+
+// Width in pixels of `value` with `decimals` digits after the point.
+fn number_width(value: f32, decimals: u32) -> f32 {
+    return (f32(number_length(value, decimals)) * 4.0 - 1.0) * GLYPH_SCALE;
+}
+
+// Whether the pixel `p` is lit by `value` written from `origin`, its top left, with `decimals` digits after the point.
+fn number_coverage(p: vec2<f32>, origin: vec2<f32>, value: f32, decimals: u32) -> f32 {
+    let q = (p - origin) / GLYPH_SCALE;
+    if (q.x < 0.0 || q.y < 0.0 || q.y >= 5.0) {
+        return 0.0;
+    }
+    let column = u32(q.x) % 4u;
+    var k = u32(q.x) / 4u;
+    let d = digits(value, decimals);
+    if (column == 3u || k >= number_length(value, decimals)) {
+        return 0.0;
+    }
+    var glyph: u32;
+    if (d.negative && k == 0u) {
+        glyph = GLYPH_MINUS;
+    } else {
+        k -= u32(d.negative);
+        if (k < d.integer_digits) {
+            glyph = d.integer / pow10u(d.integer_digits - 1u - k) % 10u;
+        } else if (k == d.integer_digits) {
+            glyph = GLYPH_POINT;
+        } else {
+            glyph = d.fraction / pow10u(decimals + d.integer_digits - k) % 10u;
+        }
+    }
+    // Constant arrays can't be indexed dynamically
+    var glyphs = GLYPHS;
+    return f32((glyphs[glyph] >> (u32(q.y) * 3u + column)) & 1u);
+}
+
+// (left, top, right, bottom) limits of the plot of the chart `id` in pixels.
+fn chart_plot(id: u32) -> vec4<f32> {
+    return pixel_limits(id) + vec4<f32>(CHART_INSETS.xy, -CHART_INSETS.zw);
+}
+
+// Position in pixels of the data point `p` in `plot`, showing `view`.
+fn chart_pixel(p: vec2<f32>, plot: vec4<f32>, view: vec4<f32>) -> vec2<f32> {
+    let t = (p - view.xz) / (view.yw - view.xz);
+    return vec2<f32>(mix(plot.x, plot.z, t.x), mix(plot.w, plot.y, t.y));
+}
+
+// Half the width of the bar of the point `i` of `series`, most of the way to its neighbors, in pixels.
+fn bar_half_width(series: ChartSeries, i: u32, plot: vec4<f32>, view: vec4<f32>) -> f32 {
+    let x = chart_pixel(chart_point(i), plot, view).x;
+    if (i + 1u < series.start + series.len) {
+        return abs(chart_pixel(chart_point(i + 1u), plot, view).x - x) * 0.4;
+    }
+    if (i > series.start) {
+        return abs(x - chart_pixel(chart_point(i - 1u), plot, view).x) * 0.4;
+    }
+    return (plot.z - plot.x) * 0.05;
+}
+
+// Background of its style with a grid at round values of the data shown, axes on the left and bottom of the plot,
+// and the values of the grid lines next to them. The series are drawn over it by their own quads.
+fn chart(in: VertexOutput) -> vec4<f32> {
+    var style = widget_style(in.id);
+    if ((widget[in.id].ty[3] & STATE_FOCUSED) != 0u) {
+        style.border_color = FOCUS_COLOR;
+        style.border_width = max(style.border_width, 2.0);
+    }
+    let r = pixel_limits(in.id);
+    let p = in.position.xy;
+    var color = sdf_paint(sd_rounded_box(p - (r.xy + r.zw) * 0.5, (r.zw - r.xy) * 0.5, style.radii), style, in);
+
+    let view = chart_view(widget[in.id].ty[1]);
+    let plot = chart_plot(in.id);
+    let size = max(plot.zw - plot.xy, vec2<f32>(1.0));
+    let step = vec2<f32>(tick_step(view.y - view.x, size.x), tick_step(view.w - view.z, size.y));
+    // Data at the pixel, and the grid lines nearest to it
+    let data = view.xz + (vec2<f32>(p.x - plot.x, plot.w - p.y) / size) * (view.yw - view.xz);
+    let tick = round(data / step) * step;
+    let line = chart_pixel(tick, plot, view);
+    let ink = style.border_color;
+    if (all(p >= plot.xy) && all(p <= plot.zw)) {
+        let grid = max(clamp(1.0 - abs(p.x - line.x), 0.0, 1.0), clamp(1.0 - abs(p.y - line.y), 0.0, 1.0));
+        color = over(color, vec4<f32>(ink.rgb, ink.a * 0.2), grid);
+    }
+    let axes = max(
+        select(0.0, clamp(1.0 - abs(p.x - plot.x), 0.0, 1.0), p.y >= plot.y && p.y <= plot.w + 1.0),
+        select(0.0, clamp(1.0 - abs(p.y - plot.w), 0.0, 1.0), p.x >= plot.x - 1.0 && p.x <= plot.z),
+    );
+    color = over(color, ink, axes);
+
+    // Values centered under the x grid lines, and right-aligned left of the y ones
+    let decimals = vec2<u32>(step_decimals(step.x), step_decimals(step.y));
+    var label = 0.0;
+    if (p.y > plot.w && line.x >= plot.x - 0.5 && line.x <= plot.z + 0.5) {
+        let origin = vec2<f32>(line.x - number_width(tick.x, decimals.x) * 0.5, plot.w + 4.0);
+        label = number_coverage(p, origin, tick.x, decimals.x);
+    } else if (p.x < plot.x && line.y >= plot.y - 0.5 && line.y <= plot.w + 0.5) {
+        let origin = vec2<f32>(plot.x - 4.0 - number_width(tick.y, decimals.y), line.y - 2.5 * GLYPH_SCALE);
+        label = number_coverage(p, origin, tick.y, decimals.y);
+    }
+    return over(color, vec4<f32>(ink.rgb, ink.a * style.opacity), label);
+}
+
+// Point `in.series.y` of the series `in.series.x` of the chart `id`, cut to its plot:
+// a segment to the next point, a bar from 0, a dot, or a segment over the region between it and 0.
+fn chart_series(in: VertexOutput, id: u32) -> vec4<f32> {
+    let chart = widget[id].ty[1];
+    let view = chart_view(chart);
+    let plot = chart_plot(id);
+    let p = in.position.xy;
+    if (any(p < plot.xy) || any(p > plot.zw)) {
+        discard;
+    }
+    let series = chart_series_header(chart, in.series.x);
+    let i = in.series.y;
+    let a = chart_pixel(chart_point(i), plot, view);
+    let base = clamp(chart_pixel(vec2<f32>(0.0), plot, view).y, plot.y, plot.w);
+    var color = vec4<f32>(0.0);
+    switch series.kind {
+        case CHART_LINE: {
+            let b = chart_pixel(chart_point(i + 1u), plot, view);
+            color = over(color, series.color, clamp(CHART_LINE_WIDTH * 0.5 + 0.5 - segment_distance(p, a, b), 0.0, 1.0));
+        }
+        case CHART_BAR: {
+            let half = vec2<f32>(bar_half_width(series, i, plot, view), abs(a.y - base) * 0.5);
+            let d = sd_rounded_box(p - vec2<f32>(a.x, (a.y + base) * 0.5), half, vec4<f32>(0.0));
+            color = over(color, series.color, clamp(0.5 - d, 0.0, 1.0));
+        }
+        case CHART_SCATTER: {
+            color = over(color, series.color, clamp(CHART_MARKER_RADIUS + 0.5 - length(p - a), 0.0, 1.0));
+        }
+        default: {
+            // Each column of pixels is filled by the one segment it starts in, so that neighbors don't overlap
+            let b = chart_pixel(chart_point(i + 1u), plot, view);
+            if (p.x >= min(a.x, b.x) && p.x < max(a.x, b.x)) {
+                let y = mix(a.y, b.y, (p.x - a.x) / (b.x - a.x));
+                let d = max(min(y, base) - p.y, p.y - max(y, base));
+                color = over(color, vec4<f32>(series.color.rgb, series.color.a * 0.3), clamp(0.5 - d, 0.0, 1.0));
+            }
+            color = over(color, series.color, clamp(CHART_LINE_WIDTH * 0.5 + 0.5 - segment_distance(p, a, b), 0.0, 1.0));
+        }
+    }
+    if (color.a <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(color.rgb, color.a * widget_style(id).opacity);
+}
+
+// Box of the background of the chart `id` with its border, at `rect` (left, top, right, bottom) in pixels.
+fn chart_box(base: vec4<f32>, p: vec2<f32>, rect: vec4<f32>, style: Style) -> vec4<f32> {
+    let d = sd_rounded_box(p - (rect.xy + rect.zw) * 0.5, (rect.zw - rect.xy) * 0.5, vec4<f32>(3.0));
+    let color = over(base, vec4<f32>(style.fill.rgb, 0.9), clamp(0.5 - d, 0.0, 1.0));
+    return over(color, style.border_color, clamp(1.0 - abs(d + 0.5), 0.0, 1.0));
+}
+
+// Over the series of the chart `id`: a legend of their numbers and colors in the top right of its plot,
+// and a ring at the point under the pointer with a box of its x and y.
+fn chart_overlay(in: VertexOutput, id: u32) -> vec4<f32> {
+    let chart = widget[id].ty[1];
+    let style = widget_style(id);
+    let plot = chart_plot(id);
+    let p = in.position.xy;
+    var color = vec4<f32>(0.0);
+
+    // Rows of a sample of the color and the number of the series from 1
+    let count = chart_info(chart).z;
+    if (count > 0u) {
+        let legend = vec4<f32>(plot.z - 34.0, plot.y + 4.0, plot.z - 4.0, plot.y + 8.0 + f32(count) * 14.0);
+        color = chart_box(color, p, legend, style);
+        let row = u32(max(p.y - legend.y - 4.0, 0.0) / 14.0);
+        if (p.x >= legend.x && p.x < legend.z && row < count) {
+            let top = legend.y + 4.0 + f32(row) * 14.0;
+            let sample = sd_rounded_box(p - vec2<f32>(legend.x + 9.0, top + 5.0), vec2<f32>(5.0, 4.0), vec4<f32>(1.0));
+            color = over(color, chart_series_header(chart, row).color, clamp(0.5 - sample, 0.0, 1.0));
+            color = over(color, style.border_color, number_coverage(p, vec2<f32>(legend.x + 18.0, top), f32(row + 1u), 0u));
+        }
+    }
+
+    let hovered = chart_info(chart).xy;
+    if (hovered.x > 0u && hovered.x <= count) {
+        let view = chart_view(chart);
+        let series = chart_series_header(chart, hovered.x - 1u);
+        let value = chart_point(series.start + hovered.y);
+        let a = chart_pixel(value, plot, view);
+        let ring = abs(length(p - a) - CHART_MARKER_RADIUS - 2.0);
+        color = over(color, vec4<f32>(1.0), clamp(2.0 - ring, 0.0, 1.0));
+        color = over(color, series.color, clamp(1.0 - ring, 0.0, 1.0));
+
+        // A digit more than the grid lines, above and right of the point, or left of it near the right of the chart
+        let size = max(plot.zw - plot.xy, vec2<f32>(1.0));
+        let decimals = vec2<u32>(
+            step_decimals(tick_step(view.y - view.x, size.x)) + 1u,
+            step_decimals(tick_step(view.w - view.z, size.y)) + 1u,
+        );
+        let width = max(number_width(value.x, decimals.x), number_width(value.y, decimals.y)) + 8.0;
+        let r = pixel_limits(id);
+        var left = a.x + 8.0;
+        if (left + width > r.z) {
+            left = a.x - 8.0 - width;
+        }
+        let top = max(a.y - 40.0, r.y);
+        let readout = vec4<f32>(left, top, left + width, top + 32.0);
+        color = chart_box(color, p, readout, style);
+        color = over(color, style.border_color, number_coverage(p, readout.xy + 4.0, value.x, decimals.x));
+        color = over(color, style.border_color, number_coverage(p, readout.xy + vec2<f32>(4.0, 18.0), value.y, decimals.y));
+    }
+    if (color.a <= 0.0) {
         discard;
     }
-    return vec4<f32>(point_in, 0.0, 0.0, 1.0);
+    return vec4<f32>(color.rgb, color.a * style.opacity);
+}
+
+// The texture of the scene, bound in place of the atlas, with the radii, border and opacity of its style,
+// and a focus ring when focused.
+fn viewport(in: VertexOutput) -> vec4<f32> {
+    var style = widget_style(in.id);
+    if ((widget[in.id].ty[3] & STATE_FOCUSED) != 0u) {
+        style.border_color = FOCUS_COLOR;
+        style.border_width = max(style.border_width, 2.0);
+    }
+    style.fill = textureSampleLevel(atlas, linear_sampler, widget_uv(in), 0.0);
+    style.gradient = 0u;
+    style.skin = 0u;
+    style.backdrop_blur = 0.0;
+    let r = pixel_limits(in.id);
+    return sdf_paint(sd_rounded_box(in.position.xy - (r.xy + r.zw) * 0.5, (r.zw - r.xy) * 0.5, style.radii), style, in);
+}
+
+// Position of the pixel `in.position` in the coordinates of the primitive `prim` of the canvas `id`.
+fn canvas_local(in: VertexOutput, id: u32, prim: CanvasPrimitive) -> vec2<f32> {
+    let q = vec3<f32>(in.position.xy - pixel_limits(id).xy, 1.0);
+    return vec2<f32>(dot(prim.to_local[0].xyz, q), dot(prim.to_local[1].xyz, q));
+}
+
+// Coverage of the point `p` by the primitive `prim` of a canvas, antialiased over a pixel.
+fn canvas_coverage(prim: CanvasPrimitive, p: vec2<f32>) -> f32 {
+    let shape = prim.shape;
+    let pixel = prim.to_local[0].w;
+    let half = prim.line_width * 0.5;
+    var d: f32;
+    switch prim.kind {
+        case CANVAS_RECT: {
+            d = sd_rounded_box(p - (shape.xy + shape.zw) * 0.5, abs(shape.zw - shape.xy) * 0.5, vec4<f32>(bitcast<f32>(prim.param)));
+        }
+        case CANVAS_ELLIPSE: {
+            d = sd_ellipse(p - shape.xy, max(abs(shape.zw), vec2<f32>(1e-6)));
+        }
+        case CANVAS_LINE: {
+            return clamp(0.5 - (segment_distance(p, shape.xy, shape.zw) - half) * pixel, 0.0, 1.0);
+        }
+        case CANVAS_GLYPH: {
+            // Pixels of the font are drawn as squares, like those of the chart labels
+            let cell = floor((p - shape.xy) / (shape.zw - shape.xy) * vec2<f32>(3.0, 5.0));
+            if (any(cell < vec2<f32>(0.0)) || any(cell >= vec2<f32>(3.0, 5.0))) {
+                return 0.0;
+            }
+            return f32((prim.param >> (u32(cell.y) * 3u + u32(cell.x))) & 1u);
+        }
+        case CANVAS_IMAGE: {
+            return clamp(0.5 - sd_rounded_box(p - (shape.xy + shape.zw) * 0.5, abs(shape.zw - shape.xy) * 0.5, vec4<f32>(0.0)) * pixel, 0.0, 1.0);
+        }
+        case CANVAS_PATH_FILL: {
+            return path_coverage(prim.param, p, shape, pixel).x;
+        }
+        case CANVAS_PATH_STROKE: {
+            return path_coverage(prim.param, p, shape, pixel).y;
+        }
+        default: {
+            return 0.0;
+        }
+    }
+    // Outlines are centered on the edge
+    if (half > 0.0) {
+        d = abs(d) - half;
+    }
+    return clamp(0.5 - d * pixel, 0.0, 1.0);
+}
+
+// Color of the primitive `prim` of a canvas at the point `p`, before its coverage.
+fn canvas_color(prim: CanvasPrimitive, p: vec2<f32>) -> vec4<f32> {
+    if (prim.kind != CANVAS_IMAGE) {
+        return prim.color;
+    }
+    let img = images[prim.param];
+    let uv = (p - prim.shape.xy) / (prim.shape.zw - prim.shape.xy);
+    return textureSampleLevel(atlas, linear_sampler, mix(img.uv.xy, img.uv.zw, uv), 0.0) * prim.color;
 }
 
+fn text_input(in: VertexOutput) -> vec4<f32> {
+    let p = in.position.xy;
+    let rect = pixel_limits(in.id);
+    let ty = widget[in.id].ty;
+    let focused = (ty[1] & TEXT_FOCUSED) != 0u;
+    let style = widget_style(in.id);
+
+    // 1px border, highlighted when focused
+    if (p.x < rect.x+1.0 || p.x > rect.z-1.0 || p.y < rect.y+1.0 || p.y > rect.w-1.0) {
+        let border = select(style.border_color, FOCUS_COLOR, focused);
+        return vec4<f32>(border.rgb, border.a * style.opacity);
+    }
+
+    let cell_height = rect.w - rect.y - 2.0*TEXT_PADDING;
+    let cell_width = cell_height * TEXT_CELL_ASPECT;
+    let x = p.x - rect.x - TEXT_PADDING;
+    let y = p.y - rect.y - TEXT_PADDING;
+
+    // Caret
+    let caret = f32(ty[1] & ~TEXT_FOCUSED) * cell_width;
+    if (focused && x >= caret && x < caret+1.0 && y >= 0.0 && y < cell_height) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    // IME preedit underline
+    let preedit_start = f32(ty[2] & 0xffffu) * cell_width;
+    let preedit_end = f32(ty[2] >> 16u) * cell_width;
+    if (x >= preedit_start && x < preedit_end && y >= cell_height-1.0 && y < cell_height+1.0) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    let fill = widget_fill(style, in);
+    return vec4<f32>(fill.rgb, fill.a * style.opacity);
+}
+
+// Functions of the custom widgets, composed by `CustomWidgets::compose`
+// CUSTOM WIDGET FUNCTIONS
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     var coords: vec2<u32> = vec2<u32>(u32((in.pos.x+1.0)*0.5*f32(gen.resolution.x)), u32((-in.pos.y+1.0)*0.5*f32(gen.resolution.y)));
+    // Derivatives are only defined in uniform control flow, so take them before dispatching
+    let px = fwidth(in.pos.xy);
+    // Widgets in scroll views are cut to them
+    let id = in.id & ~(SHADOW_BIT | SCROLLBAR_BIT | SCRIM_BIT | SERIES_BIT | PRIMITIVE_BIT);
+    if (!unclipped(id, in.position.xy)) {
+        discard;
+    }
+    // Shadows aren't picked, so they don't write to the ID buffer
+    if ((in.id & SHADOW_BIT) != 0u) {
+        return shadow(in, id);
+    }
+    // Scrollbars pick their scroll view
+    if ((in.id & SCROLLBAR_BIT) != 0u) {
+        let color = scrollbars(in, id);
+        if (color.a <= 0.0) {
+            discard;
+        }
+        if (gen.resized.x%2 == 1) {
+            ids[coords.y * gen.resolution.x + coords.x] = id + 1;
+        }
+        return color;
+    }
+    // The scrim of dialogs picks them, keeping the pointer from the widgets below
+    if ((in.id & SCRIM_BIT) != 0u) {
+        if (gen.resized.x%2 == 1) {
+            ids[coords.y * gen.resolution.x + coords.x] = id + 1;
+        }
+        return SCRIM_COLOR;
+    }
+    // Series of charts pick the chart below them
+    if ((in.id & SERIES_BIT) != 0u) {
+        if (in.series.x == MAX_CHART_SERIES) {
+            return chart_overlay(in, id);
+        }
+        return chart_series(in, id);
+    }
+    // Primitives of canvases report their hit region where they cover the pixel, the canvas below picking elsewhere
+    if ((in.id & PRIMITIVE_BIT) != 0u) {
+        let prim = canvas_primitive(in.series.y);
+        let p = canvas_local(in, id, prim);
+        let coverage = canvas_coverage(prim, p);
+        if (prim.region != 0u && coverage >= 0.5 && gen.resized.x%2 == 1) {
+            ids[coords.y * gen.resolution.x + coords.x] = (id + 1u) | (prim.region << HIT_REGION_SHIFT);
+        }
+        let color = canvas_color(prim, p);
+        let alpha = color.a * coverage * widget_style(id).opacity;
+        if (alpha <= 0.0) {
+            discard;
+        }
+        return vec4<f32>(color.rgb, alpha);
+    }
     var color: vec4<f32>;
-    // switch widget[in.id].ty {
-    //     case 0u: {
-            color = elliptic_button(in); // Elliptic mask AND button function
-    //         break;
-    //     }
-    //     case default: {
-    //         color = vec4<f32>(0.0, 1.0, 0.0, 1.0);
-    //         break;
-    //     }
-    // }
+    switch widget[in.id].ty[0] {
+        case 0u: {
+            color = elliptic_button(in, px); // Elliptic mask AND button function
+        }
+        case 1u: {
+            color = text_input(in);
+        }
+        case 2u: {
+            color = rect(in, vec4<f32>(0.0));
+        }
+        case 3u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        case 4u: {
+            color = image(in);
+        }
+        case 5u: {
+            color = path(in);
+        }
+        case 6u: {
+            color = checkbox(in);
+        }
+        case 7u: {
+            color = radio_button(in);
+        }
+        case 8u: {
+            color = toggle(in);
+        }
+        case 9u: {
+            color = slider(in, false);
+        }
+        case 10u: {
+            color = slider(in, true);
+        }
+        case 11u: {
+            color = progress_bar(in);
+        }
+        case 12u: {
+            color = spinner(in);
+        }
+        case 13u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        case 14u: {
+            color = table_header(in);
+        }
+        case 15u: {
+            color = tree_row(in);
+        }
+        case 16u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        case 17u: {
+            color = tab(in);
+        }
+        case 18u: {
+            color = menu_item(in);
+        }
+        case 19u: {
+            color = combo_box(in);
+        }
+        case 20u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        case 21u: {
+            color = button(in);
+        }
+        case 22u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        case 23u: {
+            color = color_area(in);
+        }
+        case 24u: {
+            color = color_slider(in);
+        }
+        case 25u: {
+            color = swatch(in);
+        }
+        case 26u: {
+            color = chart(in);
+        }
+        case 27u: {
+            color = viewport(in);
+        }
+        case 28u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        case 29u: {
+            color = divider(in);
+        }
+        case 30u: {
+            color = rect(in, widget_style(in.id).radii);
+        }
+        // CUSTOM WIDGET CASES
+        default: {
+            color = vec4<f32>(0.0, 1.0, 0.0, 1.0);
+        }
+    }
     // DEBUG // DO IN ANOTHER SHADER
     if (gen.resized.x%2 == 1) {
         ids[coords.y * gen.resolution.x + coords.x] = in.id + 1; 
@@ -7,17 +7,75 @@ struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) pos: vec4<f32>,
     @location(1) @interpolate(flat) id: u32,
+    // Slot of the series of chart quads, and their instance, the index of their point,
+    // or the index of the primitive of canvas quads in y
+    @location(2) @interpolate(flat) series: vec2<u32>,
 };
 
 struct General {
     resolution: vec2<u32>,
     resized: vec2<u32>,
+    // Seconds since the start, animating widgets
+    time: f32,
 }
 
 struct Widget {
     @location(0) limits: vec4<f32>,
-    @location(1) ty: u32,
-    @location(2) ty2: vec3<u32>,
+    @location(1) ty: vec4<u32>,
+    // Index + 1 of the scroll view or popup containing the widget, 0 for none
+    parent: u32,
+    // For list and grid views, how far down their items are placed from, not included in ty[2]
+    scroll_base: f32,
+    // How far the content of scroll views extends past their right and bottom, in NDC
+    scroll_max: vec2<f32>,
+};
+struct Style {
+    fill: vec4<f32>,
+    border_color: vec4<f32>,
+    radii: vec4<f32>,
+    border_width: f32,
+    opacity: f32,
+    gradient: u32,
+    stop_count: u32,
+    gradient_params: vec4<f32>,
+    stop_colors: array<vec4<f32>, 4>,
+    stop_offsets: vec4<f32>,
+    shadow_color: vec4<f32>,
+    // offset x, offset y, blur, spread
+    shadow: vec4<f32>,
+    backdrop_blur: f32,
+    // Index + 1 of the atlas image replacing the fill, 0 for none
+    skin: u32,
+    // left, top, right, bottom in pixels
+    skin_insets: vec4<f32>,
+};
+struct StyleSet {
+    normal: Style,
+    hovered: Style,
+    pressed: Style,
+    disabled: Style,
+};
+// See `SeriesHeader` in chart.rs
+struct ChartSeries {
+    color: vec4<f32>,
+    kind: u32,
+    start: u32,
+    len: u32,
+    _padding: u32,
+};
+// See `CanvasPrimitive` in canvas.rs
+struct CanvasPrimitive {
+    color: vec4<f32>,
+    // Left, top, right, bottom in pixels from the top left of the canvas
+    bounds: vec4<f32>,
+    // Rows of the map from pixels of the canvas to the coordinates of the shape, and the pixels a unit of them spans
+    to_local: array<vec4<f32>, 2>,
+    shape: vec4<f32>,
+    kind: u32,
+    line_width: f32,
+    param: u32,
+    // Hit region + 1, 0 for none
+    region: u32,
 };
 
 
@@ -27,16 +85,254 @@ var<uniform> gen: General;
 
 // Widgets buffer
 @group(1) @binding(0)
-var<storage,read_write> widget: array<Widget>;
-// ID buffer
-@group(2) @binding(0)
-var<storage,read_write> ids: array<u32>;
+var<storage,read> widget: array<Widget>;
+// Styles buffer
+@group(1) @binding(1)
+var<storage,read> styles: array<StyleSet>;
+// Chart headers indexed by widget.ty[1] of chart widgets, the points of their series, and the primitives of the canvases,
+// each canvas quad instanced over its own. Must match `DataLayout` in state.rs: a header of where the points
+// and the primitives start, then CHART_SIZE vec4s per chart, two points per vec4, and PRIMITIVE_SIZE vec4s per primitive.
+@group(1) @binding(4)
+var<storage,read> packed: array<vec4<f32>>;
+
+// Must match the constants in style.rs and state.rs.
+const STATE_HOVERED: u32 = 0x1000000u;
+const STATE_PRESSED: u32 = 0x2000000u;
+const STATE_DISABLED: u32 = 0x4000000u;
+const STATE_HIDDEN: u32 = 0x10000000u;
+const STYLE_INDEX_MASK: u32 = 0xffffffu;
+const SHADOW_BIT: u32 = 0x80000000u;
+const SCROLLBAR_BIT: u32 = 0x40000000u;
+const SCRIM_BIT: u32 = 0x20000000u;
+const SERIES_BIT: u32 = 0x10000000u;
+const PRIMITIVE_BIT: u32 = 0x8000000u;
+// Must match the constant in scroll.rs.
+const MAX_NESTING: u32 = 8u;
+// Must match the constants in chart.rs.
+const MAX_CHART_SERIES: u32 = 8u;
+// vec4s of a chart header, its view and hovered point then two per series
+const CHART_SIZE: u32 = 18u;
+const CHART_INSETS: vec4<f32> = vec4<f32>(48.0, 8.0, 8.0, 20.0);
+const CHART_LINE: u32 = 0u;
+const CHART_BAR: u32 = 1u;
+const CHART_SCATTER: u32 = 2u;
+const CHART_AREA: u32 = 3u;
+// Must match the constants in w__fragment.wgsl.
+const CHART_LINE_WIDTH: f32 = 2.0;
+const CHART_MARKER_RADIUS: f32 = 3.5;
+// Must match the size of `CanvasPrimitive` in canvas.rs, in vec4s.
+const PRIMITIVE_SIZE: u32 = 6u;
+
+// The view of the chart `chart`, see `ChartHeader` in chart.rs.
+fn chart_view(chart: u32) -> vec4<f32> {
+    return packed[1u + chart * CHART_SIZE];
+}
+
+// The series + 1 and point under the pointer of the chart `chart` in xy, 0 for none, and its number of series in z.
+fn chart_info(chart: u32) -> vec4<u32> {
+    return bitcast<vec4<u32>>(packed[2u + chart * CHART_SIZE]);
+}
+
+// The series `slot` of the chart `chart`.
+fn chart_series_header(chart: u32, slot: u32) -> ChartSeries {
+    let i = 3u + chart * CHART_SIZE + slot * 2u;
+    let info = bitcast<vec4<u32>>(packed[i + 1u]);
+    return ChartSeries(packed[i], info.x, info.y, info.z, 0u);
+}
+
+// The point `i` of the series of all the charts.
+fn chart_point(i: u32) -> vec2<f32> {
+    let v = packed[bitcast<u32>(packed[0].x) + i / 2u];
+    return select(v.xy, v.zw, i % 2u == 1u);
+}
+
+// The primitive `i` of all the canvases.
+fn canvas_primitive(i: u32) -> CanvasPrimitive {
+    let j = bitcast<u32>(packed[0].y) + i * PRIMITIVE_SIZE;
+    let info = packed[j + 5u];
+    return CanvasPrimitive(
+        packed[j], packed[j + 1u], array<vec4<f32>, 2>(packed[j + 2u], packed[j + 3u]), packed[j + 4u],
+        bitcast<u32>(info.x), info.y, bitcast<u32>(info.z), bitcast<u32>(info.w),
+    );
+}
+
+// Shadow parameters of a widget in its current state.
+fn widget_shadow(id: u32) -> vec4<f32> {
+    let ty = widget[id].ty[3];
+    let index = ty & STYLE_INDEX_MASK;
+    if ((ty & STATE_DISABLED) != 0u) {
+        return styles[index].disabled.shadow;
+    }
+    if ((ty & STATE_PRESSED) != 0u) {
+        return styles[index].pressed.shadow;
+    }
+    if ((ty & STATE_HOVERED) != 0u) {
+        return styles[index].hovered.shadow;
+    }
+    return styles[index].normal.shadow;
+}
+
+// Sum of the offsets of the scroll views containing the widget `id`, in NDC.
+fn scroll_offset(id: u32) -> vec2<f32> {
+    var offset = vec2<f32>(0.0);
+    var parent = widget[id].parent;
+    for (var i = 0u; i < MAX_NESTING && parent != 0u; i++) {
+        let ty = widget[parent-1u].ty;
+        offset += vec2<f32>(-bitcast<f32>(ty[1]), bitcast<f32>(ty[2]));
+        parent = widget[parent-1u].parent;
+    }
+    return offset;
+}
+
+// Whether the widget `id` or one of the widgets containing it is hidden.
+fn is_hidden(id: u32) -> bool {
+    if ((widget[id].ty[3] & STATE_HIDDEN) != 0u) {
+        return true;
+    }
+    var parent = widget[id].parent;
+    for (var i = 0u; i < MAX_NESTING && parent != 0u; i++) {
+        if ((widget[parent-1u].ty[3] & STATE_HIDDEN) != 0u) {
+            return true;
+        }
+        parent = widget[parent-1u].parent;
+    }
+    return false;
+}
+
+// (left, top, right, bottom) limits of a widget in pixels, moved by the scroll views containing it.
+fn pixel_limits(id: u32) -> vec4<f32> {
+    let res = vec2<f32>(gen.resolution);
+    let l = widget[id].limits + scroll_offset(id).xxyy;
+    return vec4<f32>((l[0]+1.0)*0.5*res.x, (1.0-l[3])*0.5*res.y, (l[1]+1.0)*0.5*res.x, (1.0-l[2])*0.5*res.y);
+}
+
+// (left, top, right, bottom) limits of the plot of the chart `id` in pixels.
+fn chart_plot(id: u32) -> vec4<f32> {
+    return pixel_limits(id) + vec4<f32>(CHART_INSETS.xy, -CHART_INSETS.zw);
+}
+
+// Position in pixels of the data point `p` in `plot`, showing `view`.
+fn chart_pixel(p: vec2<f32>, plot: vec4<f32>, view: vec4<f32>) -> vec2<f32> {
+    let t = (p - view.xz) / (view.yw - view.xz);
+    return vec2<f32>(mix(plot.x, plot.z, t.x), mix(plot.w, plot.y, t.y));
+}
+
+// Half the width of the bar of the point `i` of `series`, most of the way to its neighbors, in pixels.
+fn bar_half_width(series: ChartSeries, i: u32, plot: vec4<f32>, view: vec4<f32>) -> f32 {
+    let x = chart_pixel(chart_point(i), plot, view).x;
+    if (i + 1u < series.start + series.len) {
+        return abs(chart_pixel(chart_point(i + 1u), plot, view).x - x) * 0.4;
+    }
+    if (i > series.start) {
+        return abs(x - chart_pixel(chart_point(i - 1u), plot, view).x) * 0.4;
+    }
+    return (plot.z - plot.x) * 0.05;
+}
+
+// Corner `corner` of the quad of the chart `id` drawing the point `i` of its series `slot`, in pixels,
+// or of the whole chart for its legend and readout after the series. Corners go counterclockwise from the bottom left.
+fn series_corner(id: u32, slot: u32, corner: u32, i: u32) -> vec2<f32> {
+    let chart = widget[id].ty[1];
+    let view = chart_view(chart);
+    let plot = chart_plot(id);
+    // 0 or 1, right and up
+    let c = vec2<f32>(f32(corner >= 2u), f32(corner == 1u || corner == 2u));
+    let side = c * 2.0 - 1.0;
+    if (slot == MAX_CHART_SERIES) {
+        let r = pixel_limits(id);
+        return vec2<f32>(mix(r.x, r.z, c.x), mix(r.w, r.y, c.y));
+    }
+    let series = chart_series_header(chart, slot);
+    let a = chart_pixel(chart_point(i), plot, view);
+    let last = i + 1u >= series.start + series.len;
+    // Zero is where bars and areas rise from, kept in the plot
+    let base = clamp(chart_pixel(vec2<f32>(0.0), plot, view).y, plot.y, plot.w);
+    switch series.kind {
+        case CHART_LINE: {
+            if (last) {
+                break;
+            }
+            let b = chart_pixel(chart_point(i + 1u), plot, view);
+            let dir = select(vec2<f32>(1.0, 0.0), normalize(b - a), length(b - a) > 1e-6);
+            let half = CHART_LINE_WIDTH * 0.5 + 1.0;
+            return select(a, b, c.x > 0.5) + dir * side.x * half + vec2<f32>(dir.y, -dir.x) * side.y * half;
+        }
+        case CHART_BAR: {
+            let half = bar_half_width(series, i, plot, view) + 1.0;
+            return vec2<f32>(a.x + side.x * half, select(max(a.y, base) + 1.0, min(a.y, base) - 1.0, c.y > 0.5));
+        }
+        case CHART_SCATTER: {
+            return a + side * vec2<f32>(1.0, -1.0) * (CHART_MARKER_RADIUS + 1.0);
+        }
+        default: {
+            if (last) {
+                break;
+            }
+            // From zero up to the line, with room for its width
+            let b = chart_pixel(chart_point(i + 1u), plot, view);
+            let top = select(a.y, b.y, c.x > 0.5);
+            let half = CHART_LINE_WIDTH * 0.5 + 1.0;
+            return vec2<f32>(select(a.x, b.x, c.x > 0.5), select(base, top + select(half, -half, top < base), c.y > 0.5));
+        }
+    }
+    // The last point of lines and areas has no segment after it
+    return vec2<f32>(-1.0e6);
+}
+
+// Corner `corner` of the quad of the canvas `id` drawing its primitive `i`, in pixels, cut to the canvas.
+// Corners go counterclockwise from the bottom left.
+fn primitive_corner(id: u32, corner: u32, i: u32) -> vec2<f32> {
+    let r = pixel_limits(id);
+    let b = canvas_primitive(i).bounds;
+    let lo = clamp(r.xy + b.xy, r.xy, r.zw);
+    let hi = clamp(r.xy + b.zw, lo, r.zw);
+    return vec2<f32>(select(lo.x, hi.x, corner >= 2u), select(hi.y, lo.y, corner == 1u || corner == 2u));
+}
 
 @vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
+fn vs_main(in: VertexInput, @builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> VertexOutput {
+    var pos = in.pos;
+    let id = in.id & ~(SHADOW_BIT | SCROLLBAR_BIT | SCRIM_BIT | SERIES_BIT | PRIMITIVE_BIT);
+    var series = vec2<u32>(0u);
+    // Quads of widgets, their shadows and scrollbars follow their limits, letting them move
+    if ((in.id & (SCRIM_BIT | SERIES_BIT | PRIMITIVE_BIT)) == 0u) {
+        let l = widget[id].limits;
+        let corner = vertex % 4u;
+        pos = vec2<f32>(select(l[0], l[1], corner >= 2u), select(l[2], l[3], corner == 1u || corner == 2u));
+    }
+    // Quads of chart series are placed by their points, x holding their slot
+    if ((in.id & SERIES_BIT) != 0u) {
+        series = vec2<u32>(u32(in.pos.x), instance);
+        let p = series_corner(id, series.x, vertex % 4u, instance);
+        pos = vec2<f32>(p.x, -p.y) / vec2<f32>(gen.resolution) * 2.0 + vec2<f32>(-1.0, 1.0);
+    }
+    // Quads of canvases are placed by their primitives
+    if ((in.id & PRIMITIVE_BIT) != 0u) {
+        series = vec2<u32>(0u, instance);
+        let p = primitive_corner(id, vertex % 4u, instance);
+        pos = vec2<f32>(p.x, -p.y) / vec2<f32>(gen.resolution) * 2.0 + vec2<f32>(-1.0, 1.0);
+    }
+    // Grow shadow quads to fit the blur and spread, and move them by the offset
+    if ((in.id & SHADOW_BIT) != 0u) {
+        let shadow = widget_shadow(id);
+        let l = widget[id].limits;
+        let center = vec2<f32>(l[0]+l[1], l[2]+l[3]) * 0.5;
+        let to_ndc = 2.0 / vec2<f32>(gen.resolution);
+        let margin = max(shadow.w + 1.5 * shadow.z, 0.0) + 1.0;
+        pos += sign(pos - center) * margin * to_ndc + vec2<f32>(shadow.x, -shadow.y) * to_ndc;
+    }
+    if ((in.id & (SERIES_BIT | PRIMITIVE_BIT)) == 0u) {
+        pos += scroll_offset(id);
+    }
+    // Hidden widgets collapse to a point off screen, covering no pixel
+    if (is_hidden(id)) {
+        pos = vec2<f32>(-2.0);
+    }
+
     var out: VertexOutput;
-    out.position = vec4<f32>(in.pos, 0.0, 1.0);
-    out.pos = vec4<f32>(in.pos, 0.0, 1.0);
+    out.position = vec4<f32>(pos, 0.0, 1.0);
+    out.pos = vec4<f32>(pos, 0.0, 1.0);
     out.id = in.id;
+    out.series = series;
     return out;
-}
\ No newline at end of file
+}